@@ -0,0 +1,361 @@
+//! rendering of all the templates of a directory, or matching a glob pattern, in parallel
+
+use crate::include::{dependencies, normalize};
+use crate::{parent_directory, render, CliError, Output, RenderArgs, EXIT_INVALID};
+use rayon::prelude::*;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+fn is_pattern(value: &str) -> bool {
+    value.contains('*') || value.contains('?')
+}
+
+/// rather the input designates several templates, a directory or a glob pattern
+pub fn is_batch(input: &str) -> bool {
+    is_pattern(input) || Path::new(input).is_dir()
+}
+
+/// match a file name with a pattern, where `*` matches any characters and `?` a single one
+fn match_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|index| match_name(rest, &name[index..])),
+        Some(('?', rest)) => !name.is_empty() && match_name(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_name(rest, &name[1..]),
+    }
+}
+
+/// match the components of a path with a pattern, where `**` matches any number of directories
+fn match_path(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|index| match_path(rest, &path[index..]))
+        }
+        Some((first, rest)) => {
+            let first: Vec<char> = first.chars().collect();
+            match path.split_first() {
+                Some((name, path)) => {
+                    let name: Vec<char> = name.chars().collect();
+                    match_name(&first, &name) && match_path(rest, path)
+                }
+                None => false,
+            }
+        }
+    }
+}
+
+fn components(path: &Path) -> Vec<String> {
+    path.components()
+        .map(|item| item.as_os_str().to_string_lossy().to_string())
+        .collect()
+}
+
+/// directory to walk and pattern of the templates relative to it, a directory
+/// matching all its `.mjml` files
+fn split_pattern(input: &str) -> (PathBuf, Vec<String>) {
+    let mut base = PathBuf::new();
+    let mut pattern = vec![];
+    for component in Path::new(input).components() {
+        let value = component.as_os_str().to_string_lossy().to_string();
+        if pattern.is_empty() && !is_pattern(&value) {
+            base.push(component);
+        } else {
+            pattern.push(value);
+        }
+    }
+    if pattern.is_empty() {
+        pattern = vec!["**".into(), "*.mjml".into()];
+    }
+    if base.as_os_str().is_empty() {
+        base.push(".");
+    }
+    (base, pattern)
+}
+
+/// templates matching the input, relative to the directory they've been found in,
+/// without the ones included by the other templates
+pub fn collect(input: &str) -> (PathBuf, Vec<PathBuf>) {
+    let (base, pattern) = split_pattern(input);
+    let files: Vec<PathBuf> = WalkDir::new(&base)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(&base)
+                .ok()
+                .map(|path| path.to_path_buf())
+        })
+        .filter(|path| match_path(&pattern, &components(path)))
+        .collect();
+    let included: HashSet<PathBuf> = files
+        .iter()
+        .flat_map(|path| dependencies(&base.join(path)))
+        .collect();
+    let files = files
+        .into_iter()
+        .filter(|path| !included.contains(&normalize(&base.join(path))))
+        .collect();
+    (base, files)
+}
+
+/// rather the output has been modified after the template
+fn is_up_to_date(source: &Path, target: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|item| item.modified()).ok();
+    match (modified(source), modified(target)) {
+        (Some(source), Some(target)) => target >= source,
+        _ => false,
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    let units = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Rendered { size: usize, warnings: usize },
+    Skipped,
+    Failed(String),
+}
+
+/// rendering of many templates into a directory
+pub struct Batch<'a> {
+    pub options: &'a RenderArgs,
+    pub text: bool,
+    pub out_dir: &'a Path,
+    pub changed_only: bool,
+}
+
+impl<'a> Batch<'a> {
+    fn target(&self, relative: &Path) -> PathBuf {
+        let extension = if self.text { "txt" } else { "html" };
+        self.out_dir.join(relative).with_extension(extension)
+    }
+
+    /// render the template, returning the size of the output and the number of findings
+    fn render_file(&self, source: &Path, target: &Path) -> Result<(usize, usize), String> {
+        let template = fs::read_to_string(source).map_err(|err| err.to_string())?;
+        let options = self
+            .options
+            .options(parent_directory(source))
+            .map_err(|err| err.to_string())?;
+        let (content, report) =
+            render(&template, options, self.text).map_err(|err| err.to_string())?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(target, &content).map_err(|err| err.to_string())?;
+        Ok((content.len(), report.findings.len()))
+    }
+
+    fn process(&self, source: &Path, target: &Path) -> Outcome {
+        if self.changed_only && is_up_to_date(source, target) {
+            return Outcome::Skipped;
+        }
+        match self.render_file(source, target) {
+            Ok((size, warnings)) => Outcome::Rendered { size, warnings },
+            Err(message) => Outcome::Failed(message),
+        }
+    }
+
+    /// render the templates in parallel, continuing past the failures, and summarize the results
+    pub fn run(&self, input: &str, as_json: bool) -> Result<Output, CliError> {
+        let (base, files) = collect(input);
+        if files.is_empty() {
+            return Err(CliError::Usage(format!("no template matches {}", input)));
+        }
+        Ok(self.render_files(&base, &files, as_json))
+    }
+
+    /// render the given templates, relative to the base directory
+    pub fn render_files(&self, base: &Path, files: &[PathBuf], as_json: bool) -> Output {
+        let outcomes: Vec<(&PathBuf, Outcome)> = files
+            .par_iter()
+            .map(|relative| {
+                let outcome = self.process(&base.join(relative), &self.target(relative));
+                (relative, outcome)
+            })
+            .collect();
+        let mut rendered = 0;
+        let mut skipped = 0;
+        let mut warnings = 0;
+        let mut size = 0;
+        let mut failures = vec![];
+        for (path, outcome) in outcomes.iter() {
+            match outcome {
+                Outcome::Rendered {
+                    size: file_size,
+                    warnings: file_warnings,
+                } => {
+                    rendered += 1;
+                    size += file_size;
+                    warnings += file_warnings;
+                }
+                Outcome::Skipped => skipped += 1,
+                Outcome::Failed(message) => failures.push((path.display().to_string(), message)),
+            };
+        }
+        let exit_code = if failures.is_empty() { 0 } else { EXIT_INVALID };
+        let content = if as_json {
+            let failures: Vec<_> = failures
+                .iter()
+                .map(|(path, message)| json!({ "path": path, "error": message }))
+                .collect();
+            json!({
+                "rendered": rendered,
+                "failed": failures.len(),
+                "skipped": skipped,
+                "warnings": warnings,
+                "size": size,
+                "failures": failures,
+            })
+            .to_string()
+        } else {
+            let mut lines: Vec<String> = failures
+                .iter()
+                .map(|(path, message)| format!("error: {}: {}", path, message))
+                .collect();
+            lines.push(format!(
+                "{} rendered, {} failed, {} up to date, {} warning(s), {}",
+                rendered,
+                failures.len(),
+                skipped,
+                warnings,
+                format_size(size)
+            ));
+            lines.join("\n")
+        };
+        Output { content, exit_code }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser;
+
+    const TEMPLATE: &str = "<mjml><mj-body><mj-section><mj-column><mj-text>Hello</mj-text></mj-column></mj-section></mj-body></mjml>";
+
+    fn pattern(value: &str) -> Vec<String> {
+        value.split('/').map(String::from).collect()
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(match_path(&pattern("*.mjml"), &pattern("a.mjml")));
+        assert!(!match_path(&pattern("*.mjml"), &pattern("emails/a.mjml")));
+        assert!(match_path(&pattern("**/*.mjml"), &pattern("a.mjml")));
+        assert!(match_path(
+            &pattern("**/*.mjml"),
+            &pattern("emails/en/a.mjml")
+        ));
+        assert!(match_path(
+            &pattern("emails/?.mjml"),
+            &pattern("emails/a.mjml")
+        ));
+        assert!(!match_path(
+            &pattern("emails/?.mjml"),
+            &pattern("emails/ab.mjml")
+        ));
+        assert_eq!(
+            split_pattern("templates/**/*.mjml"),
+            (PathBuf::from("templates"), pattern("**/*.mjml"))
+        );
+        assert_eq!(
+            split_pattern("*.mjml"),
+            (PathBuf::from("."), pattern("*.mjml"))
+        );
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(3 * 1024 * 1024 + 200 * 1024), "3.2 MB");
+    }
+
+    fn run(args: &[&str]) -> Output {
+        let cli = Cli::try_parse_from(std::iter::once("mrml").chain(args.iter().copied())).unwrap();
+        match cli.command {
+            crate::Command::Render {
+                input,
+                options,
+                text,
+                out_dir,
+                changed_only,
+                ..
+            } => {
+                let batch = Batch {
+                    options: &options,
+                    text,
+                    out_dir: out_dir.as_ref().unwrap(),
+                    changed_only,
+                };
+                batch.run(&input.input, cli.json).unwrap()
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn render_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let input = root.path().join("templates");
+        let output = root.path().join("output");
+        fs::create_dir_all(input.join("en")).unwrap();
+        fs::write(input.join("welcome.mjml"), TEMPLATE).unwrap();
+        fs::write(input.join("en/goodbye.mjml"), TEMPLATE).unwrap();
+        fs::write(input.join("en/broken.mjml"), "<mjml><mj-body>").unwrap();
+        fs::write(
+            input.join("en/footer.mjml"),
+            r#"<mjml><mj-body><mj-include path="partials/footer.mjml" /></mj-body></mjml>"#,
+        )
+        .unwrap();
+        fs::create_dir_all(input.join("en/partials")).unwrap();
+        fs::write(input.join("en/partials/footer.mjml"), "<mj-section />").unwrap();
+        fs::write(input.join("readme.md"), "not a template").unwrap();
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+        let result = run(&["render", input, "--out-dir", output]);
+        assert_eq!(result.exit_code, EXIT_INVALID);
+        let lines: Vec<&str> = result.content.lines().collect();
+        assert!(lines[0].starts_with("error: en/broken.mjml: "));
+        assert!(lines
+            .last()
+            .unwrap()
+            .starts_with("3 rendered, 1 failed, 0 up to date, 0 warning(s), "));
+        let html = fs::read_to_string(Path::new(output).join("en/goodbye.html")).unwrap();
+        assert!(html.contains("Hello"));
+        assert!(Path::new(output).join("welcome.html").exists());
+
+        let pattern = format!("{}/**/welcome.mjml", input);
+        let result = run(&["render", &pattern, "--out-dir", output, "--changed-only"]);
+        assert_eq!(result.exit_code, 0);
+        assert!(result
+            .content
+            .starts_with("0 rendered, 0 failed, 1 up to date, "));
+
+        let result = run(&["render", &pattern, "--out-dir", output, "--text", "--json"]);
+        let value: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(value["rendered"], 1);
+        assert_eq!(value["size"], 6);
+        assert_eq!(
+            fs::read_to_string(Path::new(output).join("welcome.txt")).unwrap(),
+            "Hello\n"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! templates included by the other templates, to know which ones to render again
+
+use mrml::parser::{parse_fragment, Element};
+use mrml::util::include::join_path;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// path of the file, absolute when it exists to be compared with the notified ones
+pub fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// paths of the `mj-include` elements of the template and its children
+fn include_paths(elements: &[Element], result: &mut Vec<(String, bool)>) {
+    for element in elements.iter() {
+        if let Element::Node(node) = element {
+            if node.name.as_str() == "mj-include" {
+                let mut path = None;
+                let mut mjml = true;
+                for (key, value) in node.attributes.iter() {
+                    match key.as_str() {
+                        "path" => path = Some(value.as_str().to_string()),
+                        "type" => mjml = value.as_str() == "mjml",
+                        _ => (),
+                    };
+                }
+                if let Some(path) = path {
+                    result.push((path, mjml));
+                }
+            }
+            include_paths(&node.children, result);
+        }
+    }
+}
+
+/// files included by the template, directly or through other included templates,
+/// resolved like the `mj-include` elements are when rendering
+pub fn dependencies(template: &Path) -> Vec<PathBuf> {
+    let root = template.parent().unwrap_or_else(|| Path::new("."));
+    let mut result: Vec<PathBuf> = vec![];
+    let mut queue: Vec<(Option<String>, PathBuf)> = vec![(None, template.to_path_buf())];
+    while let Some((current, path)) = queue.pop() {
+        let content = match fs::read_to_string(&path) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let elements = match parse_fragment(&content) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let mut includes = vec![];
+        include_paths(&elements, &mut includes);
+        for (include, mjml) in includes.into_iter() {
+            let resolved = join_path(current.as_deref(), &include);
+            let file = normalize(&root.join(&resolved));
+            if result.contains(&file) {
+                continue;
+            }
+            result.push(file.clone());
+            if mjml {
+                queue.push((Some(resolved), file));
+            }
+        }
+    }
+    result
+}
@@ -1,54 +1,734 @@
-use clap::{crate_authors, crate_version, Clap};
+mod batch;
+mod include;
+mod serve;
+mod watch;
+
+use clap::{Args, Parser, Subcommand};
+use mrml::elements::prelude::Print;
+use mrml::util::asset::{AssetEmbedding, FileAssetResolver};
+use mrml::util::config::{self, Config, ConfigError};
+use mrml::util::include::FileIncludeLoader;
 use mrml::util::size::Size::Pixel;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::Error as IOError;
-
-#[derive(Clap, Debug)]
-#[clap(
-    version = crate_version!(),
-    author = crate_authors!()
-)]
-struct Options {
-    #[clap(short, long, about = "Keeps comments from mjml in output")]
-    pub keep_comments: bool,
-    #[clap(short, long, about = "Size of the breakpoint in pixels")]
-    pub breakpoint: Option<f32>,
-    #[clap(about = "Path to your mjml file")]
-    pub input: String,
-}
-
-impl Into<mrml::Options> for Options {
-    fn into(self) -> mrml::Options {
-        let mut res = mrml::Options::default();
-        res.keep_comments = self.keep_comments;
+use mrml::validation::{Report, ValidationLevel};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+
+/// exit code when the template cannot be parsed or rendered, or isn't valid
+const EXIT_INVALID: i32 = 1;
+/// exit code when the input cannot be read or the output cannot be written,
+/// clap already exits with 2 on usage errors
+const EXIT_IO: i32 = 3;
+/// exit code when the arguments cannot be used together
+const EXIT_USAGE: i32 = 2;
+
+#[derive(Parser, Debug)]
+#[clap(version, author, about)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+    /// Prints the results and the errors as json
+    #[clap(long, global = true)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct Input {
+    /// Path to your mjml file, `-` to read it from the standard input
+    #[clap(default_value = "-")]
+    input: String,
+    /// Path of the file to write the result into, `-` for the standard output
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+#[derive(Args, Debug)]
+struct RenderArgs {
+    /// Keeps comments from mjml in output
+    #[clap(short, long, overrides_with = "no-keep-comments")]
+    keep_comments: bool,
+    /// Removes the comments from the output, even when the configuration file keeps them
+    #[clap(long, overrides_with = "keep-comments")]
+    no_keep_comments: bool,
+    /// Size of the breakpoint in pixels
+    #[clap(short, long)]
+    breakpoint: Option<f32>,
+    /// Minifies the generated html
+    #[clap(long, overrides_with = "no-minify")]
+    minify: bool,
+    /// Doesn't minify the generated html, even when the configuration file does
+    #[clap(long, overrides_with = "minify")]
+    no_minify: bool,
+    /// How strictly the template is checked before rendering: strict, soft or skip [default: skip]
+    #[clap(long)]
+    validation: Option<ValidationLevel>,
+    /// Reads the options from this file instead of the closest mrml.toml or .mrmlrc
+    #[clap(long, conflicts_with = "no-config")]
+    config: Option<PathBuf>,
+    /// Ignores the mrml.toml and .mrmlrc files
+    #[clap(long)]
+    no_config: bool,
+}
+
+impl RenderArgs {
+    /// options of the renderer, the included templates being resolved in the given directory,
+    /// overridden by the closest configuration file and then by the flags
+    fn options(&self, directory: &Path) -> Result<mrml::Options, CliError> {
+        let mut res = mrml::Options {
+            keep_comments: false,
+            include_loader: Arc::new(FileIncludeLoader::new(directory)),
+            ..mrml::Options::default()
+        };
+        let config_file = if self.no_config {
+            None
+        } else {
+            self.config.clone().or_else(|| config::discover(directory))
+        };
+        if let Some(path) = config_file {
+            Config::from_file(&path)
+                .map_err(|err| CliError::Config(path, err))?
+                .apply(&mut res);
+        }
+        if self.keep_comments {
+            res.keep_comments = true;
+        } else if self.no_keep_comments {
+            res.keep_comments = false;
+        }
+        if self.minify {
+            res.minify = true;
+        } else if self.no_minify {
+            res.minify = false;
+        }
+        if let Some(level) = self.validation {
+            res.validation_level = level;
+        }
         if let Some(bp) = self.breakpoint {
             res.breakpoint = Pixel(bp);
         }
-        res
+        Ok(res)
     }
 }
 
-fn read_file(path: &str) -> Result<String, IOError> {
-    let mut file = File::open(path)?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
-    Ok(content)
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Renders the template as html, or all the templates of a directory or a glob pattern
+    Render {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+        /// Renders the plain text version of the template instead
+        #[clap(long)]
+        text: bool,
+        /// Directory to write the rendered templates into, mirroring the input directory
+        #[clap(long, conflicts_with = "output")]
+        out_dir: Option<PathBuf>,
+        /// Only renders the templates modified after their output
+        #[clap(long, requires = "out-dir")]
+        changed_only: bool,
+        /// Renders the templates again when they, or the templates they include, change
+        #[clap(long, requires = "out-dir")]
+        watch: bool,
+        /// Scans the files to detect the changes instead of relying on the notifications
+        #[clap(long, requires = "watch")]
+        poll: bool,
+    },
+    /// Checks the template against the mjml rules
+    Validate {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+    },
+    /// Prints the template back as canonical mjml
+    Format {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+    },
+    /// Prints the title of the template
+    Title {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+    },
+    /// Prints the preview of the template
+    Preview {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+    },
+    /// Builds the mime message of the email, with the local images attached
+    Email {
+        #[clap(flatten)]
+        input: Input,
+        #[clap(flatten)]
+        options: RenderArgs,
+        /// Sender of the email
+        #[clap(long)]
+        from: Option<String>,
+        /// Recipients of the email
+        #[clap(long)]
+        to: Vec<String>,
+    },
+    /// Serves a preview of the templates of a directory on localhost
+    Serve {
+        /// Directory containing the templates
+        #[clap(default_value = ".")]
+        directory: String,
+        /// Port to listen to
+        #[clap(short, long, default_value = "3000")]
+        port: u16,
+        #[clap(flatten)]
+        options: RenderArgs,
+    },
 }
 
-fn main() {
-    let opts = Options::parse();
-    let content = match read_file(&opts.input) {
-        Ok(content) => content,
-        Err(err) => {
-            panic!("couldn't read input file: {:?}", err);
+#[derive(Debug)]
+enum CliError {
+    Read(String, io::Error),
+    Write(String, io::Error),
+    Mrml(Box<mrml::Error>),
+    Usage(String),
+    Watch(String, notify::Error),
+    Bind(String, String),
+    Config(PathBuf, ConfigError),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Read(_, _)
+            | CliError::Write(_, _)
+            | CliError::Watch(_, _)
+            | CliError::Bind(_, _) => EXIT_IO,
+            CliError::Mrml(_) => EXIT_INVALID,
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Config(_, ConfigError::Io(_, _)) => EXIT_IO,
+            CliError::Config(_, _) => EXIT_USAGE,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            CliError::Mrml(err) => json!({
+                "error": err.to_string().lines().next().unwrap_or_default(),
+                "line": err.location().line,
+                "column": err.location().column,
+                "path": err.location().path,
+            }),
+            other => json!({ "error": other.to_string() }),
         }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Read(path, err) => write!(f, "couldn't read {}: {}", path, err),
+            CliError::Write(path, err) => write!(f, "couldn't write {}: {}", path, err),
+            CliError::Mrml(err) => write!(f, "{}", err),
+            CliError::Usage(message) => f.write_str(message),
+            CliError::Watch(path, err) => write!(f, "couldn't watch {}: {}", path, err),
+            CliError::Bind(address, err) => write!(f, "couldn't listen to {}: {}", address, err),
+            CliError::Config(_, ConfigError::Io(path, err)) => {
+                write!(f, "couldn't read {}: {}", path.display(), err)
+            }
+            CliError::Config(path, err) => {
+                write!(f, "invalid configuration {}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl From<mrml::Error> for CliError {
+    fn from(err: mrml::Error) -> Self {
+        CliError::Mrml(Box::new(err))
+    }
+}
+
+/// directory containing the file, the current one for a relative file name
+fn parent_directory(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+impl Input {
+    fn is_stdin(&self) -> bool {
+        self.input == "-"
+    }
+
+    /// directory used to resolve the includes and the images of the template
+    fn directory(&self) -> &Path {
+        if self.is_stdin() {
+            Path::new(".")
+        } else {
+            parent_directory(Path::new(&self.input))
+        }
+    }
+
+    fn read(&self) -> Result<String, CliError> {
+        let mut content = String::new();
+        if self.is_stdin() {
+            io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|err| CliError::Read("standard input".into(), err))?;
+        } else {
+            content = fs::read_to_string(&self.input)
+                .map_err(|err| CliError::Read(self.input.clone(), err))?;
+        }
+        Ok(content)
+    }
+
+    fn write(&self, content: &str) -> Result<(), CliError> {
+        let mut content = content.to_string();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        if self.output == "-" {
+            io::stdout()
+                .write_all(content.as_bytes())
+                .map_err(|err| CliError::Write("standard output".into(), err))
+        } else {
+            fs::write(&self.output, content)
+                .map_err(|err| CliError::Write(self.output.clone(), err))
+        }
+    }
+}
+
+/// result of a command, written to the output
+#[derive(Debug)]
+struct Output {
+    content: String,
+    exit_code: i32,
+}
+
+impl From<String> for Output {
+    fn from(content: String) -> Self {
+        Self {
+            content,
+            exit_code: 0,
+        }
+    }
+}
+
+/// html or plain text version of the template, with the findings of its validation
+fn render(
+    template: &str,
+    options: mrml::Options,
+    text: bool,
+) -> Result<(String, Report), CliError> {
+    let (element, report) = mrml::parse_with_report(template, options)?;
+    let content = if text {
+        element.get_text()
+    } else {
+        element
+            .get_html()
+            .map_err(|err| mrml::Error::from_mjml(template, err))?
+    };
+    Ok((content, report))
+}
+
+/// prints a single value, or an object with the value as the given field with `--json`
+fn format_value(name: &str, value: String, as_json: bool) -> Output {
+    if as_json {
+        Output::from(json!({ name: value }).to_string())
+    } else {
+        Output::from(value)
+    }
+}
+
+fn format_report(report: &Report, as_json: bool) -> Output {
+    let exit_code = if report.is_valid() { 0 } else { EXIT_INVALID };
+    let content = if as_json {
+        let findings: Vec<Value> = report
+            .findings
+            .iter()
+            .map(|finding| {
+                json!({
+                    "severity": finding.severity.to_string(),
+                    "message": finding.kind.to_string(),
+                    "line": finding.location.line,
+                    "column": finding.location.column,
+                    "path": finding.location.path,
+                    "file": finding.file,
+                })
+            })
+            .collect();
+        json!({ "valid": report.is_valid(), "findings": findings }).to_string()
+    } else {
+        let mut lines: Vec<String> = report
+            .findings
+            .iter()
+            .map(|item| item.to_string())
+            .collect();
+        lines.push(format!(
+            "{} error(s), {} warning(s)",
+            report.errors().count(),
+            report.warnings().count()
+        ));
+        lines.join("\n")
     };
-    let output = match mrml::to_html(content.as_str(), mrml::Options::default()) {
-        Ok(output) => output,
+    Output { content, exit_code }
+}
+
+impl Command {
+    /// template read by the command, `None` for the server
+    fn input(&self) -> Option<&Input> {
+        match self {
+            Command::Render { input, .. } => Some(input),
+            Command::Validate { input, .. } => Some(input),
+            Command::Format { input, .. } => Some(input),
+            Command::Title { input, .. } => Some(input),
+            Command::Preview { input, .. } => Some(input),
+            Command::Email { input, .. } => Some(input),
+            Command::Serve { .. } => None,
+        }
+    }
+
+    fn execute(&self, input: &Input, template: &str, as_json: bool) -> Result<Output, CliError> {
+        match self {
+            Command::Render { options, text, .. } => {
+                let (content, report) =
+                    render(template, options.options(input.directory())?, *text)?;
+                for finding in report.findings.iter() {
+                    eprintln!("{}", finding);
+                }
+                let name = if *text { "text" } else { "html" };
+                Ok(format_value(name, content, as_json))
+            }
+            Command::Validate { options, .. } => {
+                let report = mrml::validate(template, &options.options(input.directory())?)?;
+                Ok(format_report(&report, as_json))
+            }
+            Command::Format { options, .. } => {
+                let element = mrml::parse(template, options.options(input.directory())?)?;
+                Ok(format_value("mjml", element.to_mjml(), as_json))
+            }
+            Command::Title { options, .. } => {
+                let title = mrml::to_title(template, options.options(input.directory())?)?;
+                Ok(format_value("title", title, as_json))
+            }
+            Command::Preview { options, .. } => {
+                let preview = mrml::to_preview(template, options.options(input.directory())?)?;
+                Ok(format_value("preview", preview, as_json))
+            }
+            Command::Email {
+                options, from, to, ..
+            } => {
+                let options = mrml::Options {
+                    asset_resolver: Arc::new(FileAssetResolver::new(input.directory())),
+                    asset_embedding: AssetEmbedding::ContentId,
+                    ..options.options(input.directory())?
+                };
+                let email = mrml::to_email(template, options)?;
+                if as_json {
+                    return Ok(Output::from(
+                        json!({
+                            "subject": email.subject,
+                            "text": email.text,
+                            "html": email.html,
+                        })
+                        .to_string(),
+                    ));
+                }
+                let mut message = email.message();
+                if let Some(value) = from {
+                    message = message.from(value);
+                }
+                for value in to.iter() {
+                    message = message.to(value);
+                }
+                Ok(Output::from(message.to_string()))
+            }
+            Command::Serve { .. } => {
+                Err(CliError::Usage("the server doesn't read a template".into()))
+            }
+        }
+    }
+
+    fn serve(&self) -> Result<i32, CliError> {
+        if let Command::Serve {
+            directory,
+            port,
+            options,
+        } = self
+        {
+            let preview = serve::Preview {
+                root: Path::new(directory),
+                options,
+            };
+            preview.serve(*port)?;
+        }
+        Ok(0)
+    }
+
+    fn run(&self, as_json: bool) -> Result<i32, CliError> {
+        let input = match self.input() {
+            Some(value) => value,
+            None => return self.serve(),
+        };
+        if let Command::Render {
+            options,
+            text,
+            out_dir,
+            changed_only,
+            watch,
+            poll,
+            ..
+        } = self
+        {
+            match out_dir {
+                Some(out_dir) => {
+                    let batch = batch::Batch {
+                        options,
+                        text: *text,
+                        out_dir,
+                        changed_only: *changed_only,
+                    };
+                    if *watch {
+                        watch::watch(&batch, &input.input, *poll, as_json)?;
+                        return Ok(0);
+                    }
+                    let output = batch.run(&input.input, as_json)?;
+                    input.write(&output.content)?;
+                    return Ok(output.exit_code);
+                }
+                None if batch::is_batch(&input.input) => {
+                    return Err(CliError::Usage(format!(
+                        "--out-dir is required to render {}",
+                        input.input
+                    )));
+                }
+                None => (),
+            };
+        }
+        let template = input.read()?;
+        let output = self.execute(input, &template, as_json)?;
+        input.write(&output.content)?;
+        Ok(output.exit_code)
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let code = match cli.command.run(cli.json) {
+        Ok(code) => code,
         Err(err) => {
-            panic!("couldn't convert mjml: {:?}", err);
+            if cli.json {
+                eprintln!("{}", err.to_json());
+            } else {
+                eprintln!("error: {}", err);
+            }
+            err.exit_code()
         }
     };
-    println!("{}", output);
+    process::exit(code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"<mjml>
+  <mj-head>
+    <mj-title>Hello</mj-title>
+    <mj-preview>World</mj-preview>
+  </mj-head>
+  <mj-body>
+    <!-- comment -->
+    <mj-section><mj-column><mj-text>Hello World</mj-text></mj-column></mj-section>
+  </mj-body>
+</mjml>"#;
+
+    fn execute(args: &[&str], template: &str) -> Result<Output, CliError> {
+        let cli = Cli::try_parse_from(std::iter::once("mrml").chain(args.iter().copied())).unwrap();
+        let input = cli.command.input().unwrap();
+        cli.command.execute(input, template, cli.json)
+    }
+
+    #[test]
+    fn render_with_options() {
+        let output = execute(&["render"], TEMPLATE).unwrap();
+        assert!(!output.content.contains("comment"));
+        assert!(output.content.contains("min-width:480px"));
+        let output = execute(&["render", "--keep-comments", "-b", "320"], TEMPLATE).unwrap();
+        assert!(output.content.contains("comment"));
+        assert!(output.content.contains("min-width:320px"));
+        let output = execute(&["render", "--text"], TEMPLATE).unwrap();
+        assert_eq!(output.content, "Hello World\n");
+    }
+
+    #[test]
+    fn config_file() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("emails")).unwrap();
+        fs::write(
+            root.path().join("mrml.toml"),
+            "breakpoint = \"320px\"\nkeep_comments = true\n",
+        )
+        .unwrap();
+        fs::write(root.path().join("invalid.toml"), "breakpoint 320").unwrap();
+        let input = root.path().join("emails/welcome.mjml");
+        let input = input.to_str().unwrap();
+
+        let output = execute(&["render", input], TEMPLATE).unwrap();
+        assert!(output.content.contains("comment"));
+        assert!(output.content.contains("min-width:320px"));
+        let output = execute(&["render", input, "-b", "400"], TEMPLATE).unwrap();
+        assert!(output.content.contains("min-width:400px"));
+        let output = execute(&["render", input, "--no-keep-comments"], TEMPLATE).unwrap();
+        assert!(!output.content.contains("comment"));
+        assert!(output.content.contains("min-width:320px"));
+        let output = execute(&["render", input, "--no-config"], TEMPLATE).unwrap();
+        assert!(!output.content.contains("comment"));
+        assert!(output.content.contains("min-width:480px"));
+
+        let config = root.path().join("invalid.toml");
+        let err = execute(
+            &["render", input, "--config", config.to_str().unwrap()],
+            TEMPLATE,
+        )
+        .unwrap_err();
+        assert_eq!(err.exit_code(), EXIT_USAGE);
+        assert!(err.to_string().ends_with(
+            "invalid.toml: expected an equals, found an identifier at line 1 column 12"
+        ));
+    }
+
+    #[test]
+    fn config_file_for_every_command() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("partials")).unwrap();
+        fs::write(
+            root.path().join("mrml.toml"),
+            "include_root = \"partials\"\nminify = true\n",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("partials/header.mjml"),
+            "<mj-section><mj-column><mj-text>Header</mj-text></mj-column></mj-section>",
+        )
+        .unwrap();
+        let input = root.path().join("welcome.mjml");
+        let input = input.to_str().unwrap();
+        let template = "<mjml><mj-body><mj-include path=\"header.mjml\" /></mj-body></mjml>";
+
+        let output = execute(&["validate", input], template).unwrap();
+        assert_eq!(output.content, "0 error(s), 0 warning(s)");
+        assert!(execute(&["format", input], template).is_ok());
+        assert!(execute(&["title", input], template).is_ok());
+        assert!(execute(&["preview", input], template).is_ok());
+        assert!(execute(&["format", input, "--no-config"], template).is_err());
+
+        let minified = execute(&["render", input], template).unwrap();
+        let output = execute(&["render", input, "--no-minify"], template).unwrap();
+        assert!(output.content.len() > minified.content.len());
+        let output = execute(&["render", input, "--no-minify", "--minify"], template).unwrap();
+        assert_eq!(output.content, minified.content);
+    }
+
+    #[test]
+    fn json_output() {
+        let output = execute(&["title", "--json"], TEMPLATE).unwrap();
+        assert_eq!(output.content, r#"{"title":"Hello"}"#);
+        let output = execute(&["--json", "preview"], TEMPLATE).unwrap();
+        assert_eq!(output.content, r#"{"preview":"World"}"#);
+    }
+
+    #[test]
+    fn validate() {
+        let output = execute(&["validate"], TEMPLATE).unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(output.content, "0 error(s), 0 warning(s)");
+        let template = "<mjml><mj-body><mj-column /></mj-body></mjml>";
+        let output = execute(&["validate"], template).unwrap();
+        assert_eq!(output.exit_code, EXIT_INVALID);
+        assert!(output
+            .content
+            .starts_with("error: element <mj-column> is not allowed in <mj-body>"));
+        let output = execute(&["validate", "--json"], template).unwrap();
+        let value: Value = serde_json::from_str(&output.content).unwrap();
+        assert_eq!(value["valid"], false);
+        assert_eq!(value["findings"][0]["line"], 1);
+    }
+
+    #[test]
+    fn format() {
+        let output = execute(
+            &["format"],
+            "<mjml><mj-body><mj-text>Hi</mj-text></mj-body></mjml>",
+        )
+        .unwrap();
+        assert_eq!(
+            output.content,
+            "<mjml>\n  <mj-body>\n    <mj-text>Hi</mj-text>\n  </mj-body>\n</mjml>\n"
+        );
+    }
+
+    #[test]
+    fn format_with_include() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("header.mjml"),
+            "<mj-section><mj-column><mj-text>Header</mj-text></mj-column></mj-section>",
+        )
+        .unwrap();
+        let input = root.path().join("welcome.mjml");
+        let output = execute(
+            &["format", input.to_str().unwrap()],
+            "<mjml><mj-body><mj-include path=\"header.mjml\" /></mj-body></mjml>",
+        )
+        .unwrap();
+        assert_eq!(
+            output.content,
+            "<mjml>\n  <mj-body>\n    <mj-include path=\"header.mjml\" />\n  </mj-body>\n</mjml>\n"
+        );
+    }
+
+    #[test]
+    fn email() {
+        let output = execute(
+            &[
+                "email",
+                "--from",
+                "jane@example.com",
+                "--to",
+                "john@example.com",
+            ],
+            TEMPLATE,
+        )
+        .unwrap();
+        assert!(output
+            .content
+            .starts_with("From: jane@example.com\r\nTo: john@example.com\r\nSubject: Hello\r\n"));
+    }
+
+    #[test]
+    fn errors() {
+        let err = execute(&["render"], "<mjml><mj-body>").unwrap_err();
+        assert_eq!(err.exit_code(), EXIT_INVALID);
+        let err = execute(
+            &["render", "--validation", "strict"],
+            "<mjml><mj-body><mj-column /></mj-body></mjml>",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_json()["line"], 1);
+        let input = Input {
+            input: "missing.mjml".into(),
+            output: "-".into(),
+        };
+        let err = input.read().unwrap_err();
+        assert_eq!(err.exit_code(), EXIT_IO);
+        assert!(err.to_string().starts_with("couldn't read missing.mjml: "));
+    }
 }
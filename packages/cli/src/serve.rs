@@ -0,0 +1,383 @@
+//! local server listing the templates of a directory and previewing them as they change
+
+use crate::batch::collect;
+use crate::{format_report, render, CliError, RenderArgs};
+use mrml::util::size::Size;
+use mrml::util::template::{escape, Escape};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tiny_http::{Header, Response, Server};
+use walkdir::WalkDir;
+
+/// checks every second if a file changed, to reload the previewed template
+const RELOAD_SCRIPT: &str = r#"<script>
+(function () {
+  var version = null;
+  setInterval(function () {
+    fetch("/version").then(function (res) { return res.text(); }).then(function (value) {
+      if (version !== null && value !== version) {
+        document.getElementById("preview").contentWindow.location.reload();
+      }
+      version = value;
+    });
+  }, 1000);
+})();
+</script>"#;
+
+const STYLE: &str = "<style>body{font-family:sans-serif;margin:0;background:#eee}\
+nav{padding:8px 16px;background:#333;color:#fff}nav a,nav button{color:#fff;margin-right:12px}\
+nav button{background:none;border:1px solid #fff;cursor:pointer}\
+iframe{display:block;margin:16px auto;border:0;background:#fff;height:calc(100vh - 80px)}\
+ul{padding:16px 32px}li{margin:4px 0}</style>";
+
+/// response of the server, built without the network to be tested
+#[derive(Debug)]
+pub struct Page {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Page {
+    fn html(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/html; charset=utf-8",
+            body,
+        }
+    }
+
+    fn text(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body,
+        }
+    }
+
+    fn json(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(err: &CliError) -> Self {
+        Self::html(
+            500,
+            format!("<pre>{}</pre>", escape(&err.to_string(), Escape::Text)),
+        )
+    }
+
+    fn not_found() -> Self {
+        Self::text(404, "not found".into())
+    }
+}
+
+/// decode the `%XX` sequences of an url path
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let decoded = if bytes[index] == b'%' {
+            value
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match decoded {
+            Some(byte) => {
+                result.push(byte);
+                index += 3;
+            }
+            None => {
+                result.push(bytes[index]);
+                index += 1;
+            }
+        };
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// encode the segments of the path with `%XX` sequences, to be used in an url path
+fn encode(path: &Path) -> String {
+    path.components()
+        .map(|item| {
+            let mut result = String::new();
+            for byte in item.as_os_str().to_string_lossy().bytes() {
+                match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                        result.push(byte as char)
+                    }
+                    _ => result.push_str(&format!("%{:02X}", byte)),
+                };
+            }
+            result
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// width of the preview below the breakpoint, for the mobile layout to be used
+fn mobile_width(breakpoint: &Size) -> f32 {
+    match breakpoint {
+        Size::Pixel(value) if *value > 1.0 => value - 1.0,
+        _ => 479.0,
+    }
+}
+
+/// serves the templates of a directory
+pub struct Preview<'a> {
+    pub root: &'a Path,
+    pub options: &'a RenderArgs,
+}
+
+impl<'a> Preview<'a> {
+    /// path of the template in the served directory, `None` when leaving it
+    fn template_path(&self, value: &str) -> Option<PathBuf> {
+        let relative = PathBuf::from(decode(value));
+        if relative
+            .components()
+            .all(|item| matches!(item, Component::Normal(_)))
+        {
+            Some(self.root.join(relative))
+        } else {
+            None
+        }
+    }
+
+    fn read(&self, value: &str) -> Option<(PathBuf, String)> {
+        let path = self.template_path(value)?;
+        let content = fs::read_to_string(&path).ok()?;
+        Some((path, content))
+    }
+
+    fn index(&self) -> Page {
+        let (_, templates) = collect(&self.root.to_string_lossy());
+        let items: Vec<String> = templates
+            .iter()
+            .map(|path| {
+                format!(
+                    "<li><a href=\"/preview/{}\">{}</a></li>",
+                    encode(path),
+                    escape(&path.to_string_lossy(), Escape::Text)
+                )
+            })
+            .collect();
+        Page::html(
+            200,
+            format!(
+                "<!doctype html><html><head><title>mrml</title>{}</head><body><nav>{} template(s)</nav><ul>{}</ul></body></html>",
+                STYLE,
+                templates.len(),
+                items.join("")
+            ),
+        )
+    }
+
+    fn preview(&self, value: &str) -> Page {
+        let (path, template) = match self.read(value) {
+            Some(item) => item,
+            None => return Page::not_found(),
+        };
+        let options = match self.options.options(crate::parent_directory(&path)) {
+            Ok(options) => options,
+            Err(err) => return Page::error(&err),
+        };
+        let (title, width) = match mrml::parse(&template, options) {
+            Ok(element) => (element.get_title(), mobile_width(&element.get_breakpoint())),
+            Err(_) => (String::new(), mobile_width(&Size::Pixel(480.0))),
+        };
+        let value = encode(Path::new(&decode(value)));
+        Page::html(
+            200,
+            format!(
+                "<!doctype html><html><head><title>{title}</title>{style}</head><body>\
+<nav><a href=\"/\">templates</a>\
+<button onclick=\"document.getElementById('preview').style.width='100%'\">desktop</button>\
+<button onclick=\"document.getElementById('preview').style.width='{width}px'\">mobile</button>\
+<a href=\"/validate/{value}\">findings</a></nav>\
+<iframe id=\"preview\" src=\"/render/{value}\" style=\"width:100%\"></iframe>{script}</body></html>",
+                title = escape(&title, Escape::Text),
+                style = STYLE,
+                width = width,
+                value = value,
+                script = RELOAD_SCRIPT
+            ),
+        )
+    }
+
+    fn render(&self, value: &str) -> Page {
+        let (path, template) = match self.read(value) {
+            Some(item) => item,
+            None => return Page::not_found(),
+        };
+        let result = self
+            .options
+            .options(crate::parent_directory(&path))
+            .and_then(|options| render(&template, options, false));
+        match result {
+            Ok((html, _)) => Page::html(200, html),
+            Err(err) => Page::error(&err),
+        }
+    }
+
+    fn validate(&self, value: &str) -> Page {
+        let (path, template) = match self.read(value) {
+            Some(item) => item,
+            None => return Page::not_found(),
+        };
+        let result = self
+            .options
+            .options(crate::parent_directory(&path))
+            .and_then(|options| mrml::validate(&template, &options).map_err(CliError::from));
+        match result {
+            Ok(report) => Page::json(format_report(&report, true).content),
+            Err(err) => Page::json(err.to_json().to_string()),
+        }
+    }
+
+    /// latest modification of the files of the directory, changing when one is saved
+    fn version(&self) -> Page {
+        let latest = WalkDir::new(self.root)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.metadata().ok())
+            .filter_map(|metadata| metadata.modified().ok())
+            .filter_map(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis())
+            .max()
+            .unwrap_or_default();
+        Page::text(200, latest.to_string())
+    }
+
+    pub fn respond(&self, url: &str) -> Page {
+        let path = url.split('?').next().unwrap_or_default();
+        if path == "/" {
+            self.index()
+        } else if let Some(value) = path.strip_prefix("/preview/") {
+            self.preview(value)
+        } else if let Some(value) = path.strip_prefix("/render/") {
+            self.render(value)
+        } else if let Some(value) = path.strip_prefix("/validate/") {
+            self.validate(value)
+        } else if path == "/version" {
+            self.version()
+        } else {
+            Page::not_found()
+        }
+    }
+
+    /// serves the templates on localhost until interrupted
+    pub fn serve(&self, port: u16) -> Result<(), CliError> {
+        let address = format!("127.0.0.1:{}", port);
+        let server = Server::http(&address)
+            .map_err(|err| CliError::Bind(address.clone(), err.to_string()))?;
+        println!("serving {} on http://{}", self.root.display(), address);
+        for request in server.incoming_requests() {
+            let page = self.respond(request.url());
+            let header = Header::from_bytes(&b"Content-Type"[..], page.content_type.as_bytes())
+                .expect("valid content type");
+            let response = Response::from_string(page.body)
+                .with_status_code(page.status)
+                .with_header(header);
+            if let Err(err) = request.respond(response) {
+                eprintln!("error: {}", err);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser;
+
+    fn options() -> RenderArgs {
+        match Cli::try_parse_from(["mrml", "serve"]).unwrap().command {
+            crate::Command::Serve { options, .. } => options,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn decode_path() {
+        assert_eq!(decode("en/my%20email.mjml"), "en/my email.mjml");
+        assert_eq!(decode("100%"), "100%");
+    }
+
+    #[test]
+    fn encode_path() {
+        let path = Path::new("en/my email #1?.mjml");
+        assert_eq!(encode(path), "en/my%20email%20%231%3F.mjml");
+        assert_eq!(decode(&encode(path)), "en/my email #1?.mjml");
+        assert_eq!(encode(Path::new("été.mjml")), "%C3%A9t%C3%A9.mjml");
+    }
+
+    #[test]
+    fn pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("en")).unwrap();
+        fs::write(
+            root.path().join("en/welcome.mjml"),
+            r#"<mjml><mj-head><mj-title>Welcome</mj-title><mj-breakpoint width="400px" /></mj-head><mj-body><mj-section><mj-column><mj-text>Hello</mj-text></mj-column></mj-section></mj-body></mjml>"#,
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("en/a & b #1.mjml"),
+            "<mjml><mj-body></mj-body></mjml>",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("broken.mjml"),
+            "<mjml><mj-body><mj-column /></mj-body></mjml>",
+        )
+        .unwrap();
+        let options = options();
+        let preview = Preview {
+            root: root.path(),
+            options: &options,
+        };
+
+        let page = preview.respond("/");
+        assert!(page.body.contains("<a href=\"/preview/en/welcome.mjml\">"));
+        assert!(page
+            .body
+            .contains("<a href=\"/preview/en/a%20%26%20b%20%231.mjml\">en/a &amp; b #1.mjml</a>"));
+        assert!(page.body.contains("3 template(s)"));
+
+        let page = preview.respond("/preview/en/a%20%26%20b%20%231.mjml");
+        assert!(page
+            .body
+            .contains("src=\"/render/en/a%20%26%20b%20%231.mjml\""));
+        assert_eq!(
+            preview.respond("/render/en/a%20%26%20b%20%231.mjml").status,
+            200
+        );
+
+        let page = preview.respond("/preview/en/welcome.mjml");
+        assert!(page.body.contains("<title>Welcome</title>"));
+        assert!(page.body.contains("style.width='399px'"));
+        assert!(page.body.contains("fetch(\"/version\")"));
+
+        let page = preview.respond("/render/en/welcome.mjml");
+        assert_eq!(page.status, 200);
+        assert!(page.body.contains("Hello"));
+        assert!(!page.body.contains("/version"));
+
+        let page = preview.respond("/validate/broken.mjml");
+        let value: serde_json::Value = serde_json::from_str(&page.body).unwrap();
+        assert_eq!(value["valid"], false);
+        assert_eq!(value["findings"][0]["line"], 1);
+
+        assert_eq!(preview.respond("/render/../secret.mjml").status, 404);
+        assert_eq!(preview.respond("/render/missing.mjml").status, 404);
+        assert!(preview.respond("/version").body.parse::<u128>().unwrap() > 0);
+    }
+}
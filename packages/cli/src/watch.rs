@@ -0,0 +1,173 @@
+//! rendering of the templates again when they, or the templates they include, change
+
+use crate::batch::{collect, Batch};
+use crate::include::{dependencies, normalize};
+use crate::CliError;
+use notify::{Config, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// time without any change before rendering, to group the bursts of saves
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// interval between two scans of the files when the notifications are not available
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// templates to render again after the given files changed, relative to the base directory
+pub fn affected(base: &Path, templates: &[PathBuf], changes: &HashSet<PathBuf>) -> Vec<PathBuf> {
+    templates
+        .iter()
+        .filter(|relative| {
+            let path = base.join(relative);
+            changes.contains(&normalize(&path))
+                || dependencies(&path)
+                    .iter()
+                    .any(|item| changes.contains(item))
+        })
+        .cloned()
+        .collect()
+}
+
+/// wait for the first change, then for the following ones until nothing changes during
+/// the debounce delay, `None` when the watcher stopped
+fn next_changes(
+    receiver: &Receiver<notify::Result<Event>>,
+    ignored: &Path,
+) -> Option<HashSet<PathBuf>> {
+    let mut changes = HashSet::new();
+    let mut add = |event: notify::Result<Event>| match event {
+        Ok(event) => {
+            for path in event.paths.into_iter() {
+                let path = normalize(&path);
+                if !path.starts_with(ignored) {
+                    changes.insert(path);
+                }
+            }
+        }
+        Err(err) => eprintln!("error: {}", err),
+    };
+    add(receiver.recv().ok()?);
+    loop {
+        match receiver.recv_timeout(DEBOUNCE) {
+            Ok(event) => add(event),
+            Err(RecvTimeoutError::Timeout) => return Some(changes),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+/// watcher notified by the filesystem, or scanning the files when the notifications
+/// are not available, like when the inotify limit is reached
+fn start_watcher(
+    base: &Path,
+    sender: Sender<notify::Result<Event>>,
+    poll: bool,
+) -> Result<Box<dyn Watcher>, CliError> {
+    if !poll {
+        let native =
+            RecommendedWatcher::new(sender.clone(), Config::default()).and_then(|mut watcher| {
+                watcher
+                    .watch(base, RecursiveMode::Recursive)
+                    .map(|_| watcher)
+            });
+        match native {
+            Ok(watcher) => return Ok(Box::new(watcher)),
+            Err(err) => eprintln!("warning: {}, polling the files instead", err),
+        };
+    }
+    let watch_error = |err: notify::Error| CliError::Watch(base.display().to_string(), err);
+    let mut watcher = PollWatcher::new(sender, Config::default().with_poll_interval(POLL_INTERVAL))
+        .map_err(watch_error)?;
+    watcher
+        .watch(base, RecursiveMode::Recursive)
+        .map_err(watch_error)?;
+    Ok(Box::new(watcher))
+}
+
+/// renders the templates matching the input each time they change, until interrupted
+pub fn watch(batch: &Batch, input: &str, poll: bool, as_json: bool) -> Result<(), CliError> {
+    let (base, templates) = collect(input);
+    println!("{}", batch.render_files(&base, &templates, as_json).content);
+    let (sender, receiver) = channel();
+    let _watcher = start_watcher(&base, sender, poll)?;
+    let ignored = normalize(batch.out_dir);
+    while let Some(changes) = next_changes(&receiver, &ignored) {
+        // the templates created since the last rendering are rendered as well
+        let (base, templates) = collect(input);
+        let files = affected(&base, &templates, &changes);
+        if !files.is_empty() {
+            println!("{}", batch.render_files(&base, &files, as_json).content);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::EventKind;
+    use std::fs;
+
+    #[test]
+    fn included_templates() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        fs::create_dir_all(base.join("partials")).unwrap();
+        fs::write(
+            base.join("welcome.mjml"),
+            r#"<mjml><mj-body><mj-include path="partials/header.mjml" /><mj-include path="style.css" type="css" /></mj-body></mjml>"#,
+        )
+        .unwrap();
+        fs::write(
+            base.join("partials/header.mjml"),
+            r#"<mj-section><mj-include path="logo.mjml" /></mj-section>"#,
+        )
+        .unwrap();
+        fs::write(base.join("partials/logo.mjml"), "<mj-column />").unwrap();
+        fs::write(base.join("style.css"), "p { color: red; }").unwrap();
+        fs::write(base.join("goodbye.mjml"), "<mjml><mj-body /></mjml>").unwrap();
+
+        let mut result = dependencies(&base.join("welcome.mjml"));
+        result.sort();
+        assert_eq!(
+            result,
+            vec![
+                normalize(&base.join("partials/header.mjml")),
+                normalize(&base.join("partials/logo.mjml")),
+                normalize(&base.join("style.css")),
+            ]
+        );
+
+        let templates = vec![PathBuf::from("goodbye.mjml"), PathBuf::from("welcome.mjml")];
+        let changes: HashSet<PathBuf> = vec![normalize(&base.join("partials/logo.mjml"))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            affected(base, &templates, &changes),
+            vec![PathBuf::from("welcome.mjml")]
+        );
+        let changes: HashSet<PathBuf> = vec![normalize(&base.join("goodbye.mjml"))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            affected(base, &templates, &changes),
+            vec![PathBuf::from("goodbye.mjml")]
+        );
+    }
+
+    #[test]
+    fn debounce() {
+        let (sender, receiver) = channel();
+        let event = |path: &str| Ok(Event::new(EventKind::Any).add_path(PathBuf::from(path)));
+        sender.send(event("/src/a.mjml")).unwrap();
+        sender.send(event("/src/a.mjml")).unwrap();
+        sender.send(event("/dist/a.html")).unwrap();
+        sender.send(event("/src/b.mjml")).unwrap();
+        let changes = next_changes(&receiver, Path::new("/dist")).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes.contains(Path::new("/src/b.mjml")));
+        drop(sender);
+        assert!(next_changes(&receiver, Path::new("/dist")).is_none());
+    }
+}
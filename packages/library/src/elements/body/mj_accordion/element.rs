@@ -8,8 +8,21 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-accordion-text", "mj-accordion-title", "mj-raw"])
+        .extend(BODY_COMMON)
+        .extend(ICON)
+        .add("background-color", AttributeType::Color)
+        .add("border", AttributeType::String)
+        .add("font-family", AttributeType::String);
+}
 
 const CHILDREN_ATTR: [&str; 9] = [
     "border",
@@ -23,58 +36,73 @@ const CHILDREN_ATTR: [&str; 9] = [
     "icon-unwrapped-alt",
 ];
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJAccordionElement {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     title: Option<MJAccordionTitle>,
     text: Option<MJAccordionText>,
 }
 
 impl MJAccordionElement {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, Attributes::default())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-accordion-element",
+            &self.source_attributes,
+            Attributes::default(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        attrs: &Attributes,
-    ) -> Result<MJAccordionElement, Error> {
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJAccordionElement, Error> {
         if node.name.as_str() != "mj-accordion-element" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
         let mut element = MJAccordionElement {
-            attributes: Self::default_attributes(node, header)
-                .concat(attrs)
-                .concat(node),
-            context: None,
-            title: None,
-            text: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..MJAccordionElement::new()
         };
-        let children_attr = element.get_children_attributes();
         for child in node.children.iter() {
             match child {
                 Element::Node(node) => match node.name.as_str() {
                     "mj-accordion-title" => {
-                        element.title =
-                            Some(MJAccordionTitle::parse(node, header, &children_attr)?);
+                        element.title = Some(MJAccordionTitle::parse(node, header)?);
                     }
                     "mj-accordion-text" => {
-                        element.text = Some(MJAccordionText::parse(node, header, &children_attr)?);
+                        element.text = Some(MJAccordionText::parse(node, header)?);
                     }
-                    name => return Err(Error::UnexpectedElement(name.into())),
+                    name => return Err(Error::UnexpectedElement(name.into(), node.span.into())),
                 },
                 // TODO handle comments
                 Element::Comment(_) => (),
-                Element::Text(_) => return Err(Error::UnexpectedText),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
             };
         }
         Ok(element)
     }
 
+    pub fn title(mut self, title: MJAccordionTitle) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    pub fn text(mut self, text: MJAccordionText) -> Self {
+        self.text = Some(text);
+        self
+    }
+
     fn get_children_attributes(&self) -> Attributes {
         let mut result = Attributes::default();
         for key in CHILDREN_ATTR.iter() {
@@ -88,14 +116,14 @@ impl MJAccordionElement {
     fn render_text(&self, header: &Header, attributes: &Attributes) -> Result<String, Error> {
         match self.text.as_ref() {
             Some(content) => content.render(header),
-            None => MJAccordionText::new(attributes.clone()).render(header),
+            None => MJAccordionText::with_attributes(attributes.clone()).render(header),
         }
     }
 
     fn render_title(&self, header: &Header, attributes: &Attributes) -> Result<String, Error> {
         match self.title.as_ref() {
             Some(content) => content.render(header),
-            None => MJAccordionTitle::new(attributes.clone()).render(header),
+            None => MJAccordionTitle::with_attributes(attributes.clone()).render(header),
         }
     }
 
@@ -145,14 +173,73 @@ impl Component for MJAccordionElement {
     }
 }
 
+impl Builder for MJAccordionElement {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJAccordionElement {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
+
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+        let attrs = self.get_children_attributes();
+        let header = header.scoped(self.get_attribute("mj-class"));
+        if let Some(title) = self.title.as_mut() {
+            title.resolve(&header, Some(&attrs));
+        }
+        if let Some(text) = self.text.as_mut() {
+            text.resolve(&header, Some(&attrs));
+        }
+    }
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        if let Some(title) = self.title.as_mut() {
+            title.update_attributes(update);
+        }
+        if let Some(text) = self.text.as_mut() {
+            text.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
     fn get_current_width(&self) -> Option<Size> {
         None
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        if let Some(title) = self.title.as_ref() {
+            title.write_text(writer);
+        }
+        if let Some(text) = self.text.as_ref() {
+            text.write_text(writer);
+        }
+    }
+}
+
+impl Print for MJAccordionElement {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-accordion-element",
+            &self.source_attributes,
+            self.title.is_none() && self.text.is_none(),
+            |printer| {
+                if let Some(title) = self.title.as_ref() {
+                    title.print(printer);
+                }
+                if let Some(text) = self.text.as_ref() {
+                    text.print(printer);
+                }
+            },
+        );
+    }
 }
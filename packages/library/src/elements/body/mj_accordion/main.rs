@@ -1,4 +1,5 @@
 use super::MJAccordionElement;
+use crate::elements::body::mj_include::MJInclude;
 use crate::elements::body::prelude::*;
 use crate::elements::body::BodyElement;
 use crate::elements::error::Error;
@@ -7,6 +8,8 @@ use crate::parser::{Element, Node};
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 
@@ -35,48 +38,80 @@ lazy_static! {
         .add("icon-unwrapped-url", "https://i.imgur.com/w4uTygT.png")
         .add("icon-unwrapped-alt", "-")
         .add("padding", "10px 25px");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-accordion-element", "mj-raw"])
+        .extend(BODY_COMMON)
+        .extend(ICON)
+        .extend(PADDING)
+        .add("border", AttributeType::String)
+        .add("container-background-color", AttributeType::Color)
+        .add("font-family", AttributeType::String);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJAccordion {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJAccordion {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-accordion",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJAccordion, Error> {
-        let mut result = MJAccordion {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children: vec![],
-        };
-        let child_attrs = result.get_children_attributes();
-        for child in node.children.iter() {
+        Ok(MJAccordion {
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: Self::parse_children(&node.children, header)?,
+            ..MJAccordion::new()
+        })
+    }
+
+    fn parse_children<'a>(
+        elements: &[Element<'a>],
+        header: &Header,
+    ) -> Result<Vec<BodyElement>, Error> {
+        let mut result = vec![];
+        for child in elements.iter() {
             match child {
                 Element::Node(node) => match node.name.as_str() {
                     "mj-accordion-element" => {
-                        let element = MJAccordionElement::parse(node, header, &child_attrs)?;
-                        result
-                            .children
-                            .push(BodyElement::MJAccordionElement(element));
+                        let element = MJAccordionElement::parse(node, header)?;
+                        result.push(BodyElement::MJAccordionElement(element));
                     }
-                    name => return Err(Error::UnexpectedElement(name.into())),
+                    "mj-include" => {
+                        result.extend(MJInclude::parse_with(node, header, Self::parse_children)?);
+                    }
+                    name => return Err(Error::UnexpectedElement(name.into(), node.span.into())),
                 },
                 // TODO handle comments
                 Element::Comment(_) => (),
-                Element::Text(_) => return Err(Error::UnexpectedText),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
             };
         }
         Ok(result)
     }
 
+    pub fn element(mut self, element: MJAccordionElement) -> Self {
+        self.children.push(BodyElement::MJAccordionElement(element));
+        self
+    }
+
     fn get_children_attributes(&self) -> Attributes {
         let mut res = Attributes::default();
         for key in CHILDREN_ATTRIBUTES.iter() {
@@ -153,11 +188,35 @@ impl Component for MJAccordion {
     }
 }
 
+impl Builder for MJAccordion {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJAccordion {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let attrs = self.get_children_attributes();
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, Some(&attrs));
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -171,6 +230,19 @@ impl BodyComponent for MJAccordion {
     }
 }
 
+impl Print for MJAccordion {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-accordion",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
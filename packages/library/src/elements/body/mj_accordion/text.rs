@@ -7,57 +7,83 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
         .add("font-size", "13px")
         .add("padding", "16px");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(PADDING)
+        .add("background-color", AttributeType::Color)
+        .add("color", AttributeType::Color)
+        .add("font-family", AttributeType::String)
+        .add("font-size", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJAccordionText {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<RawElement>,
 }
 
 impl MJAccordionText {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-accordion-text",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        attrs: &Attributes,
-    ) -> Result<MJAccordionText, Error> {
+    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJAccordionText, Error> {
         if node.name.as_str() != "mj-accordion-text" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
-        let attributes = Self::default_attributes(node, header)
-            .concat(attrs)
-            .concat(node);
-        let mut element = MJAccordionText::new(attributes);
+        let mut element = MJAccordionText {
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..MJAccordionText::new()
+        };
         for child in node.children.iter() {
             element
                 .children
-                .push(RawElement::conditional_parse(&child, header, true)?);
+                .push(RawElement::conditional_parse(child, header, true)?);
         }
         Ok(element)
     }
 
-    pub fn new(attributes: Attributes) -> Self {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn with_attributes(attributes: Attributes) -> Self {
         MJAccordionText {
             attributes,
-            context: None,
-            children: vec![],
+            ..MJAccordionText::new()
         }
     }
 
+    /// add some html content to the element
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.children.push(RawElement::Text(content.to_string()));
+        self
+    }
+
     fn render_children(&self, header: &Header) -> Result<String, Error> {
         let mut res = vec![];
         for child in self.children.iter() {
@@ -109,11 +135,32 @@ impl Component for MJAccordionText {
     }
 }
 
+impl Builder for MJAccordionText {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJAccordionText {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -121,4 +168,29 @@ impl BodyComponent for MJAccordionText {
     fn get_current_width(&self) -> Option<Size> {
         None
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJAccordionText {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                "mj-accordion-text",
+                &self.source_attributes,
+                self.children.is_empty(),
+                |printer| {
+                    for child in self.children.iter() {
+                        child.print(printer);
+                    }
+                },
+            )
+        });
+    }
 }
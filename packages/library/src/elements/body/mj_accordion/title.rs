@@ -7,36 +7,48 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
         .add("font-size", "13px")
         .add("padding", "16px");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(PADDING)
+        .add("background-color", AttributeType::Color)
+        .add("color", AttributeType::Color)
+        .add("font-family", AttributeType::String)
+        .add("font-size", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJAccordionTitle {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     content: String,
 }
 
 impl MJAccordionTitle {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-accordion-title",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        attrs: &Attributes,
-    ) -> Result<MJAccordionTitle, Error> {
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJAccordionTitle, Error> {
         if node.name.as_str() != "mj-accordion-title" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
         let content: String = node
             .children
@@ -44,24 +56,35 @@ impl MJAccordionTitle {
             .filter_map(|child| child.as_text())
             .map(|value| value.as_str())
             .collect::<String>();
-        let attributes = Self::default_attributes(node, header)
-            .concat(attrs)
-            .concat(node);
         Ok(MJAccordionTitle {
-            attributes,
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
             content,
+            ..MJAccordionTitle::new()
         })
     }
 
-    pub fn new(attributes: Attributes) -> Self {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn with_attributes(attributes: Attributes) -> Self {
         MJAccordionTitle {
             attributes,
-            context: None,
-            content: "".into(),
+            ..MJAccordionTitle::new()
         }
     }
 
+    /// set the text of the title
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.content = content.to_string();
+        self
+    }
+
     fn set_style_img(&self, tag: Tag) -> Tag {
         tag.set_style("display", "none")
             .maybe_set_style("height", self.get_attribute("icon-height"))
@@ -141,16 +164,50 @@ impl Component for MJAccordionTitle {
     }
 }
 
+impl Builder for MJAccordionTitle {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJAccordionTitle {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+    }
+
     fn get_current_width(&self) -> Option<Size> {
         None
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        writer.text(&self.content);
+        writer.block();
+    }
+}
+
+impl Print for MJAccordionTitle {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content(
+            "mj-accordion-title",
+            &self.source_attributes,
+            self.content.as_str(),
+        );
+    }
 }
@@ -1,55 +1,100 @@
+use super::mj_hero::MJHero;
+use super::mj_section::MJSection;
+use super::mj_wrapper::MJWrapper;
 use super::prelude::*;
 use super::BodyElement;
+use crate::elements::prelude::{Builder, Print};
 use crate::elements::{Component, Error};
 use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 use log::debug;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default().add("width", "600px");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-hero", "mj-raw", "mj-section", "mj-wrapper"])
+        .extend(BODY_COMMON)
+        .add("background-color", AttributeType::Color)
+        .add("width", AttributeType::Size);
 }
 
 #[derive(Clone, Debug)]
 pub struct MJBody {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
     exists: bool,
 }
 
+impl Default for MJBody {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MJBody {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-body",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn empty() -> MJBody {
+    pub fn new() -> MJBody {
         MJBody {
             attributes: Attributes::default(),
+            source_attributes: Attributes::default(),
             children: vec![],
             context: None,
+            exists: true,
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn empty() -> MJBody {
+        MJBody {
             exists: false,
+            ..MJBody::new()
         }
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJBody, Error> {
-        let mut children = vec![];
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(child, header, None::<&Attributes>)?);
-        }
         Ok(MJBody {
-            attributes: Self::default_attributes(node, header).concat(node),
-            children,
-            context: None,
-            exists: true,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..MJBody::new()
         })
     }
 
+    pub fn section(self, section: MJSection) -> Self {
+        self.add_child(section)
+    }
+
+    pub fn wrapper(self, wrapper: MJWrapper) -> Self {
+        self.add_child(wrapper)
+    }
+
+    pub fn hero(self, hero: MJHero) -> Self {
+        self.add_child(hero)
+    }
+
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn set_style_body(&self, tag: Tag) -> Tag {
         tag.maybe_set_style("background-color", self.get_attribute("background-color"))
     }
@@ -98,7 +143,10 @@ impl Component for MJBody {
 
     fn render(&self, header: &Header) -> Result<String, Error> {
         debug!("render");
-        let body = self.set_style_body(Tag::new("body"));
+        let body = self
+            .set_style_body(Tag::new("body"))
+            .maybe_set_attribute("lang", header.lang())
+            .maybe_set_attribute("dir", header.direction());
         let mut res: Vec<String> = vec![];
         res.push(body.open());
         res.push(self.render_preview(&header));
@@ -117,15 +165,38 @@ impl Component for MJBody {
     }
 }
 
+impl Builder for MJBody {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJBody {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, None);
+        }
+    }
+
     fn get_current_width(&self) -> Option<Size> {
         self.get_size_attribute("width")
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -138,6 +209,22 @@ impl BodyComponent for MJBody {
     }
 }
 
+impl Print for MJBody {
+    fn print(&self, printer: &mut Printer) {
+        if !self.exists {
+            return;
+        }
+        printer.element(
+            "mj-body",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::{compare_render, compare_render_with_options};
@@ -1,3 +1,4 @@
+use super::raw::RawElement;
 use super::BodyElement;
 use crate::elements::body::prelude::*;
 use crate::elements::error::Error;
@@ -6,8 +7,11 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -26,34 +30,70 @@ lazy_static! {
         .add("text-decoration", "none")
         .add("text-transform", "none")
         .add("vertical-align", "middle");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(BORDER)
+        .extend(FONT)
+        .extend(LINK)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("background-color", AttributeType::Color)
+        .add("container-background-color", AttributeType::Color)
+        .add("height", AttributeType::Size)
+        .add("inner-padding", AttributeType::Spacing)
+        .add("letter-spacing", AttributeType::String)
+        .add("line-spacing", AttributeType::String)
+        .add("text-align", ALIGN)
+        .add("text-transform", AttributeType::String)
+        .add("vertical-align", VERTICAL_ALIGN)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJButton {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJButton {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-button",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJButton, Error> {
         let mut children = vec![];
         for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
+            children.push(BodyElement::parse(child, header)?);
         }
         Ok(MJButton {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
             children,
+            ..Self::default()
         })
     }
 
+    /// add some html content to the element
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.children
+            .push(BodyElement::Raw(RawElement::Text(content.to_string())));
+        self
+    }
+
     fn get_content(&self, header: &Header) -> Result<String, Error> {
         let mut res = String::from("");
         for item in self.children.iter() {
@@ -149,17 +189,19 @@ impl Component for MJButton {
             .maybe_set_attribute("bgcolor", self.get_attribute("background-color"))
             .set_attribute("role", "presentation")
             .maybe_set_attribute("valign", self.get_attribute("vertical-align"));
-        let link = Tag::new(match self.get_attribute("href") {
+        let href = self
+            .get_attribute("href")
+            .map(|href| header.rewrite_link("mj-button", href));
+        let link = Tag::new(match href {
             Some(_) => "a",
             None => "p",
         })
-        .maybe_set_attribute("href", self.get_attribute("href"))
+        .maybe_set_attribute("href", href.as_ref())
         .maybe_set_attribute("rel", self.get_attribute("rel"))
         .maybe_set_attribute("name", self.get_attribute("name"))
         .maybe_set_attribute(
             "target",
-            self.get_attribute("href")
-                .and_then(|_v| self.get_attribute("target")),
+            href.as_ref().and_then(|_v| self.get_attribute("target")),
         );
         let link = self.set_style_content(link);
 
@@ -167,11 +209,33 @@ impl Component for MJButton {
     }
 }
 
+impl Builder for MJButton {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJButton {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        for child in self.children.iter_mut() {
+            child.resolve(header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -188,6 +252,36 @@ impl BodyComponent for MJButton {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        let label = TextWriter::capture(|writer| {
+            for child in self.children.iter() {
+                child.write_text(writer);
+            }
+        });
+        writer.block();
+        writer.link(
+            "mj-button",
+            &label,
+            self.get_attribute("href").map(|v| v.as_str()),
+        );
+        writer.block();
+    }
+}
+
+impl Print for MJButton {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                "mj-button",
+                &self.source_attributes,
+                self.children.is_empty(),
+                |printer| {
+                    BodyElement::print_all(&self.children, printer);
+                },
+            )
+        });
+    }
 }
 
 #[cfg(test)]
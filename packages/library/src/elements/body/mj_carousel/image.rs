@@ -6,79 +6,75 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default().add("target", "_blank");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .extend(BODY_COMMON)
+        .extend(LINK)
+        .add("alt", AttributeType::String)
+        .add("border-radius", AttributeType::String)
+        .add("src", AttributeType::Url)
+        .add("thumbnails-src", AttributeType::Url);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJCarouselImage {
     attributes: Attributes,
+    source_attributes: Attributes,
     carousel_id: String,
     context: Option<Context>,
     content: Option<String>,
 }
 
 impl MJCarouselImage {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-carousel-image",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse_image<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJCarouselImage, Error> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJCarouselImage, Error> {
         if node.name.as_str() != "mj-carousel-image" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
-        let carousel_id = match extra.and_then(|attrs| attrs.get("carousel-id")) {
-            Some(id) => id,
-            None => return Err(Error::MissingAttribute("carousel-id".into())),
-        };
         let content: Vec<&str> = node
             .children
             .iter()
             .filter_map(|child| child.as_text())
             .map(|value| value.as_str())
             .collect();
-        let content = if content.is_empty() {
-            None
-        } else {
-            Some(content.join(""))
-        };
-        let mut attributes = Self::default_attributes(node, header);
-        if let Some(extra) = extra {
-            attributes.merge(extra);
-        }
-        attributes.merge(node);
         Ok(MJCarouselImage {
-            attributes,
-            carousel_id: carousel_id.to_string(),
-            context: None,
-            content,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content.join(""))
+            },
+            ..MJCarouselImage::new()
         })
     }
 
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJCarouselImage, Error> {
-        let mut attrs = match extra {
-            Some(value) => value.into(),
-            None => Attributes::default(),
-        };
-        if attrs.get("text-padding").is_none() {
-            attrs.set("text-padding", "4px 4px 4px 0");
-        }
-        Self::parse_image(node, header, Some(&attrs))
-    }
-
     fn set_style_images_img(&self, tag: Tag) -> Tag {
         tag.maybe_set_style("border-radius", self.get_attribute("border-radius"))
             .set_style("display", "block")
@@ -174,7 +170,7 @@ impl Component for MJCarouselImage {
         self.context = Some(ctx);
     }
 
-    fn render(&self, _header: &Header) -> Result<String, Error> {
+    fn render(&self, header: &Header) -> Result<String, Error> {
         let index = self.context().map(|ctx| ctx.index()).unwrap_or(0);
         let width = self.context().and_then(|ctx| ctx.container_width());
         let img = self
@@ -189,7 +185,7 @@ impl Component for MJCarouselImage {
         let link = match self.get_attribute("href") {
             None => img,
             Some(href) => Tag::new("a")
-                .set_attribute("href", href)
+                .set_attribute("href", header.rewrite_link("mj-carousel-image", href))
                 .maybe_set_attribute("rel", self.get_attribute("rel"))
                 .set_attribute("target", "_blank")
                 .render(img),
@@ -210,16 +206,58 @@ impl Component for MJCarouselImage {
     }
 }
 
+impl Builder for MJCarouselImage {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJCarouselImage {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut extra = extra.map(Attributes::from).unwrap_or_default();
+        if !extra.has("text-padding") {
+            extra.set("text-padding", "4px 4px 4px 0");
+        }
+        self.carousel_id = extra.get("carousel-id").cloned().unwrap_or_default();
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&extra)
+            .concat(&self.source_attributes);
+    }
+
     fn get_current_width(&self) -> Option<Size> {
         None
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        writer.image(
+            "mj-carousel-image",
+            self.get_attribute("alt").map(|v| v.as_str()),
+            self.get_attribute("href").map(|v| v.as_str()),
+        );
+        writer.block();
+    }
+}
+
+impl Print for MJCarouselImage {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content(
+            "mj-carousel-image",
+            &self.source_attributes,
+            self.content.as_deref().unwrap_or_default(),
+        );
+    }
 }
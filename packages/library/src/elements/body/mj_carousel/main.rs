@@ -1,14 +1,16 @@
 use super::image::MJCarouselImage;
+use crate::elements::body::mj_include::MJInclude;
 use crate::elements::body::prelude::*;
 use crate::elements::body::BodyElement;
 use crate::elements::error::Error;
 use crate::elements::prelude::*;
-use crate::parser::Node;
+use crate::parser::{Element, Node};
 use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
-use crate::util::id::generate as generate_id;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::style::Style;
 use crate::util::tag::Tag;
@@ -25,57 +27,98 @@ lazy_static! {
         .add("tb-border-radius", "6px")
         .add("tb-hover-border-color", "#fead0d")
         .add("tb-selected-border-color", "#cccccc");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-carousel-image"])
+        .extend(BODY_COMMON)
+        .add("align", ALIGN)
+        .add("border-radius", AttributeType::String)
+        .add("container-background-color", AttributeType::Color)
+        .add("icon-width", AttributeType::Size)
+        .add("left-icon", AttributeType::Url)
+        .add("right-icon", AttributeType::Url)
+        .add("tb-border", AttributeType::String)
+        .add("tb-border-radius", AttributeType::String)
+        .add("tb-hover-border-color", AttributeType::Color)
+        .add("tb-selected-border-color", AttributeType::Color)
+        .add("tb-width", AttributeType::Size)
+        .add("thumbnails", AttributeType::Enum(&["visible", "hidden"]));
 }
 
 fn repeat(count: usize, value: &str) -> String {
     (0..count).map(|_idx| value).collect::<Vec<_>>().join("")
 }
 
-fn create_id() -> String {
-    if cfg!(test) {
-        "carousel_id".into()
-    } else {
-        generate_id(8)
-    }
-}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJCarousel {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
     id: String,
 }
 
 impl MJCarousel {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-carousel",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJCarousel, Error> {
-        let mut result = MJCarousel {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children: vec![],
-            id: create_id(),
-        };
-        let attrs = result.get_children_attributes();
-        for child in node.children.iter() {
+        Ok(MJCarousel {
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: Self::parse_children(&node.children, header)?,
+            ..MJCarousel::new()
+        })
+    }
+
+    fn parse_children<'a>(
+        elements: &[Element<'a>],
+        header: &Header,
+    ) -> Result<Vec<BodyElement>, Error> {
+        let mut result = vec![];
+        for child in elements.iter() {
             if let Some(child_node) = child.as_node() {
-                let tag_name = child_node.name.as_str();
-                if tag_name != "mj-carousel-image" {
-                    return Err(Error::UnexpectedElement(tag_name.into()));
-                } else {
-                    let element = MJCarouselImage::parse(&child_node, header, Some(&attrs))?;
-                    result.children.push(BodyElement::MJCarouselImage(element));
+                match child_node.name.as_str() {
+                    "mj-carousel-image" => {
+                        let element = MJCarouselImage::parse(child_node, header)?;
+                        result.push(BodyElement::MJCarouselImage(element));
+                    }
+                    "mj-include" => {
+                        result.extend(MJInclude::parse_with(
+                            child_node,
+                            header,
+                            Self::parse_children,
+                        )?);
+                    }
+                    tag_name => {
+                        return Err(Error::UnexpectedElement(
+                            tag_name.into(),
+                            child_node.span.into(),
+                        ))
+                    }
                 }
             }
         }
         Ok(result)
     }
 
+    pub fn image(mut self, image: MJCarouselImage) -> Self {
+        self.children.push(BodyElement::MJCarouselImage(image));
+        self
+    }
+
     fn get_images(&self) -> Vec<&MJCarouselImage> {
         self.children
             .iter()
@@ -87,7 +130,7 @@ impl MJCarousel {
     }
 
     fn get_thumbnails_width(&self) -> Size {
-        let count = self.children.len();
+        let count = self.get_images().len();
         if count == 0 {
             Size::Raw(0.0)
         } else {
@@ -180,7 +223,7 @@ impl MJCarousel {
             .get_size_attribute("icon-width")
             .map(|value| value.value());
         let items = self
-            .children
+            .get_images()
             .iter()
             .enumerate()
             .map(|(idx, _item)| {
@@ -240,7 +283,7 @@ impl MJCarousel {
     }
 
     fn render_fallback(&self, header: &Header) -> Result<String, Error> {
-        match self.children.first() {
+        match self.get_images().first() {
             Some(child) => Ok(mso_conditional_tag(child.render(header)?)),
             None => Ok("".into()),
         }
@@ -249,7 +292,7 @@ impl MJCarousel {
 
 impl Component for MJCarousel {
     fn update_header(&self, header: &mut Header) {
-        let length = self.children.len();
+        let length = self.get_images().len();
         if length == 0 {
             return;
         }
@@ -424,8 +467,13 @@ impl Component for MJCarousel {
             self.get_raw_siblings(),
             0,
         );
-        for (idx, child) in self.children.iter_mut().enumerate() {
-            child.set_context(child_base.clone().set_index(idx));
+        // the index of the images, the included elements being skipped
+        let mut index = 0;
+        for child in self.children.iter_mut() {
+            child.set_context(child_base.clone().set_index(index));
+            if let BodyElement::MJCarouselImage(_) = child {
+                index += 1;
+            }
         }
     }
 
@@ -447,7 +495,20 @@ impl Component for MJCarousel {
     }
 }
 
+impl Builder for MJCarousel {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJCarousel {
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -460,14 +521,66 @@ impl BodyComponent for MJCarousel {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.id = header.generate_id(8);
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let attrs = self.get_children_attributes();
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, Some(&attrs));
+        }
+    }
+
     fn set_style(&self, _name: &str, tag: Tag) -> Tag {
         tag
     }
 }
 
+impl Print for MJCarousel {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-carousel",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
+    use crate::{to_html, Options};
+
+    #[test]
+    fn ids_from_seed() {
+        let template = include_str!("../../../../test/mj-carousel.mjml");
+        let render = |id_seed| {
+            to_html(
+                template,
+                Options {
+                    id_seed,
+                    ..Options::default()
+                },
+            )
+            .unwrap()
+        };
+        assert_eq!(render(42), render(42));
+        assert_ne!(render(42), render(43));
+    }
+
+    #[test]
+    fn ids_in_scopes() {
+        let template = include_str!("../../../../test/mj-carousel-scopes.mjml");
+        let expected = include_str!("../../../../test/mj-carousel-scopes.html");
+        compare_render(template, expected);
+        assert!(expected.contains("mj-carousel-rIG8YeEI-radio"));
+        assert!(expected.contains("mj-carousel-PaXW093d-radio"));
+    }
 
     #[test]
     fn base() {
@@ -6,6 +6,8 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 
@@ -13,42 +15,69 @@ lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
         .add("direction", "ltr")
         .add("vertical-align", "top");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[
+            "mj-accordion",
+            "mj-button",
+            "mj-carousel",
+            "mj-divider",
+            "mj-image",
+            "mj-navbar",
+            "mj-raw",
+            "mj-social",
+            "mj-spacer",
+            "mj-table",
+            "mj-text",
+        ])
+        .extend(BODY_COMMON)
+        .extend(BORDER)
+        .extend(PADDING)
+        .add("background-color", AttributeType::Color)
+        .add("direction", DIRECTION)
+        .add("inner-background-color", AttributeType::Color)
+        .add("vertical-align", VERTICAL_ALIGN)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJColumn {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJColumn {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
-    }
-
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJColumn, Error> {
-        let mut children = vec![];
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
-        }
-        let mut attributes = Self::default_attributes(node, header);
-        if let Some(extra) = extra {
-            attributes.merge(extra);
-        }
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-column",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJColumn, Error> {
         Ok(MJColumn {
-            attributes: attributes.concat(node),
-            context: None,
-            children,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..Self::default()
         })
     }
 
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn get_column_class(&self) -> Option<(String, Size)> {
         let parsed_width = self.get_parsed_width();
         let classname = match parsed_width {
@@ -197,7 +226,7 @@ impl MJColumn {
         res.push(table.open());
         for child in self.children.iter() {
             match child {
-                BodyElement::Raw(_) => res.push(child.render(header)?),
+                _ if child.is_raw() => res.push(child.render(header)?),
                 _ => res.push(self.render_mj_child(header, &child)?),
             };
         }
@@ -247,11 +276,36 @@ impl Component for MJColumn {
     }
 }
 
+impl Builder for MJColumn {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJColumn {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -310,6 +364,19 @@ impl BodyComponent for MJColumn {
     }
 }
 
+impl Print for MJColumn {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-column",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
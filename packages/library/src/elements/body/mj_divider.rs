@@ -7,8 +7,11 @@ use crate::util::attributes::*;
 use crate::util::condition::conditional_tag;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -17,25 +20,47 @@ lazy_static! {
         .add("border-width", "4px")
         .add("padding", "10px 25px")
         .add("width", "100%");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .extend(BODY_COMMON)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("border-color", AttributeType::Color)
+        .add("border-style", AttributeType::String)
+        .add("border-width", AttributeType::Size)
+        .add("container-background-color", AttributeType::Color)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJDivider {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
 }
 
 impl MJDivider {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-divider",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
-    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJDivider, Error> {
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJDivider, Error> {
         Ok(MJDivider {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..Self::default()
         })
     }
 
@@ -108,11 +133,27 @@ impl Component for MJDivider {
     }
 }
 
+impl Builder for MJDivider {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJDivider {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -127,6 +168,16 @@ impl BodyComponent for MJDivider {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.rule();
+    }
+}
+
+impl Print for MJDivider {
+    fn print(&self, printer: &mut Printer) {
+        printer.empty("mj-divider", &self.source_attributes);
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use super::mj_column::MJColumn;
 use super::BodyElement;
 use crate::elements::body::prelude::*;
 use crate::elements::error::Error;
@@ -7,42 +8,66 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 use std::str::FromStr;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default().add("direction", "ltr");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-column", "mj-raw"])
+        .extend(BODY_COMMON)
+        .add("background-color", AttributeType::Color)
+        .add("direction", DIRECTION)
+        .add("vertical-align", VERTICAL_ALIGN)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJGroup {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJGroup {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-group",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJGroup, Error> {
-        let mut children = vec![];
-        let mut attrs = Attributes::default();
-        attrs.set("mobile-width", "mobile-width");
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, Some(&attrs))?);
-        }
         Ok(MJGroup {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..Self::default()
         })
     }
 
+    pub fn column(self, column: MJColumn) -> Self {
+        self.add_child(column)
+    }
+
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn get_width(&self) -> Option<Size> {
         self.get_current_width().and_then(|width| {
             if width.is_percent() {
@@ -173,10 +198,34 @@ impl Component for MJGroup {
     }
 }
 
+impl Builder for MJGroup {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJGroup {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
+
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let attrs = Attributes::default().add("mobile-width", "mobile-width");
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, Some(&attrs));
+        }
+    }
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -223,6 +272,19 @@ impl BodyComponent for MJGroup {
     }
 }
 
+impl Print for MJGroup {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-group",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
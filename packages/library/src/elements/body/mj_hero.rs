@@ -7,6 +7,8 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 
@@ -18,34 +20,83 @@ lazy_static! {
         .add("mode", "fixed-height")
         .add("padding", "0px")
         .add("vertical-align", "top");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[
+            "mj-accordion",
+            "mj-button",
+            "mj-carousel",
+            "mj-divider",
+            "mj-image",
+            "mj-navbar",
+            "mj-raw",
+            "mj-social",
+            "mj-spacer",
+            "mj-table",
+            "mj-text",
+        ])
+        .extend(BODY_COMMON)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("background-color", AttributeType::Color)
+        .add("background-height", AttributeType::Size)
+        .add("background-position", AttributeType::String)
+        .add("background-url", AttributeType::Url)
+        .add("background-width", AttributeType::Size)
+        .add("container-background-color", AttributeType::Color)
+        .add("height", AttributeType::Size)
+        .add("inner-background-color", AttributeType::Color)
+        .add("inner-padding", AttributeType::Spacing)
+        .add("inner-padding-bottom", AttributeType::Size)
+        .add("inner-padding-left", AttributeType::Size)
+        .add("inner-padding-right", AttributeType::Size)
+        .add("inner-padding-top", AttributeType::Size)
+        .add(
+            "mode",
+            AttributeType::Enum(&["fixed-height", "fluid-height"])
+        )
+        .add("vertical-align", VERTICAL_ALIGN)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJHero {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJHero {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-hero",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJHero, Error> {
-        let mut children = vec![];
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
-        }
         Ok(MJHero {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..Self::default()
         })
     }
 
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn set_style_div(&self, tag: Tag) -> Tag {
         tag.set_style("margin", "0 auto")
             .maybe_set_style("max-width", self.get_container_width_str())
@@ -183,7 +234,7 @@ impl MJHero {
         let mut res = String::from("");
         for child in self.get_children().iter() {
             let result = match child {
-                BodyElement::Raw(_) => child.render(header)?,
+                _ if child.is_raw() => child.render(header)?,
                 _ => self.render_child(header, child)?,
             };
             res.push_str(result.as_str());
@@ -325,11 +376,34 @@ impl Component for MJHero {
     }
 }
 
+impl Builder for MJHero {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJHero {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -357,6 +431,19 @@ impl BodyComponent for MJHero {
     }
 }
 
+impl Print for MJHero {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-hero",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
@@ -6,8 +6,11 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -17,25 +20,56 @@ lazy_static! {
         .add("padding", "10px 25px")
         .add("target", "_blank")
         .add("font-size", "13px");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .extend(BODY_COMMON)
+        .extend(LINK)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("alt", AttributeType::String)
+        .add("border", AttributeType::String)
+        .add("border-radius", AttributeType::String)
+        .add("container-background-color", AttributeType::Color)
+        .add("fluid-on-mobile", AttributeType::Boolean)
+        .add("font-size", AttributeType::Size)
+        .add("full-width", AttributeType::Enum(&["full-width"]))
+        .add("height", AttributeType::String)
+        .add("max-height", AttributeType::Size)
+        .add("src", AttributeType::Url)
+        .add("srcset", AttributeType::String)
+        .add("usemap", AttributeType::String)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJImage {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
 }
 
 impl MJImage {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-image",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJImage, Error> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJImage, Error> {
         Ok(MJImage {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..Self::default()
         })
     }
 
@@ -129,9 +163,13 @@ impl MJImage {
         self.set_style_img(img).closed()
     }
 
-    fn render_link(&self) -> String {
+    fn render_link(&self, header: &Header) -> String {
         Tag::new("a")
-            .maybe_set_attribute("href", self.get_attribute("href"))
+            .maybe_set_attribute(
+                "href",
+                self.get_attribute("href")
+                    .map(|href| header.rewrite_link("mj-image", href)),
+            )
             .maybe_set_attribute("name", self.get_attribute("name"))
             .maybe_set_attribute("rel", self.get_attribute("rel"))
             .maybe_set_attribute("target", self.get_attribute("target"))
@@ -159,7 +197,7 @@ impl Component for MJImage {
         self.context = Some(ctx);
     }
 
-    fn render(&self, _header: &Header) -> Result<String, Error> {
+    fn render(&self, header: &Header) -> Result<String, Error> {
         let table = Tag::new("table")
             .set_attribute("border", 0)
             .set_attribute("cellpadding", 0)
@@ -181,7 +219,7 @@ impl Component for MJImage {
                 None
             });
         let content = if self.get_attribute("href").is_some() {
-            self.render_link()
+            self.render_link(header)
         } else {
             self.render_image()
         };
@@ -189,11 +227,27 @@ impl Component for MJImage {
     }
 }
 
+impl Builder for MJImage {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJImage {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -210,6 +264,22 @@ impl BodyComponent for MJImage {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        writer.image(
+            "mj-image",
+            self.get_attribute("alt").map(|v| v.as_str()),
+            self.get_attribute("href").map(|v| v.as_str()),
+        );
+        writer.block();
+    }
+}
+
+impl Print for MJImage {
+    fn print(&self, printer: &mut Printer) {
+        printer.empty("mj-image", &self.source_attributes);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,252 @@
+use super::raw::RawElement;
+use super::BodyElement;
+use crate::elements::body::prelude::*;
+use crate::elements::error::Error;
+use crate::elements::head::mj_style::MJStyle;
+use crate::elements::head::prelude::HeadComponent;
+use crate::elements::head::HeadElement;
+use crate::elements::include::{Include, IncludeType};
+use crate::elements::prelude::*;
+use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
+use crate::util::context::Context;
+use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::size::Size;
+
+/// `<mj-include />` of the body, kept to print the template back
+///
+/// The included elements follow it in the children of its parent, to be rendered like the
+/// other children. A stylesheet included with `type="css"` renders nothing in place, the
+/// style is added to the head like an `mj-style`. When the included template is a complete
+/// `mjml` document, its `mj-head` is applied to the header of the including template.
+#[derive(Clone, Debug)]
+pub struct MJInclude {
+    context: Option<Context>,
+    include: Include,
+    style: Option<MJStyle>,
+    head: Vec<HeadElement>,
+    expanded: usize,
+}
+
+impl MJInclude {
+    /// parse an `mj-include` element into itself followed by the elements it includes
+    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<Vec<BodyElement>, Error> {
+        Self::parse_with(node, header, BodyElement::parse_all)
+    }
+
+    /// parse an `mj-include` element of a component only accepting some children,
+    /// the included elements being parsed with `parse`
+    pub fn parse_with<'a, F>(
+        node: &Node<'a>,
+        header: &Header,
+        parse: F,
+    ) -> Result<Vec<BodyElement>, Error>
+    where
+        F: FnOnce(&[Element], &Header) -> Result<Vec<BodyElement>, Error>,
+    {
+        let include = Include::parse(node)?;
+        let template = include.load(header)?;
+        let mut style = None;
+        let mut head = vec![];
+        let children = match include.kind {
+            IncludeType::Css => {
+                style = Some(MJStyle::new(template.content, include.css_inline));
+                vec![]
+            }
+            IncludeType::Html => vec![BodyElement::Raw(RawElement::Text(template.content))],
+            IncludeType::Mjml => {
+                if template.is_document() {
+                    head = template.parse_mjml("mj-head", |children| {
+                        HeadElement::parse_all(children, &template.header)
+                    })?;
+                }
+                template.parse_mjml("mj-body", |children| parse(children, &template.header))?
+            }
+        };
+        let mut result = vec![BodyElement::MJInclude(MJInclude {
+            context: None,
+            include,
+            style,
+            head,
+            expanded: children.len(),
+        })];
+        result.extend(children);
+        Ok(result)
+    }
+
+    /// number of elements following the include in its parent that come from it
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    /// apply the `mj-head` of the documents included in the elements, at any depth, to the
+    /// header. It has to be done before resolving the attributes of the elements.
+    pub fn update_header_with_heads(elements: &[BodyElement], header: &mut Header) {
+        for element in elements.iter() {
+            if let BodyElement::MJInclude(include) = element {
+                for item in include.head.iter() {
+                    item.update_header(header);
+                }
+            }
+            Self::update_header_with_heads(element.get_children(), header);
+        }
+    }
+}
+
+impl Component for MJInclude {
+    fn update_header(&self, header: &mut Header) {
+        if let Some(style) = self.style.as_ref() {
+            style.update_header(header);
+        }
+    }
+
+    fn context(&self) -> Option<&Context> {
+        self.context.as_ref()
+    }
+
+    fn set_context(&mut self, ctx: Context) {
+        self.context = Some(ctx);
+    }
+
+    fn render(&self, _header: &Header) -> Result<String, Error> {
+        Ok(String::new())
+    }
+}
+
+impl BodyComponent for MJInclude {
+    fn attributes(&self) -> Option<&Attributes> {
+        None
+    }
+
+    fn get_children(&self) -> &Vec<BodyElement> {
+        &EMPTY_CHILDREN
+    }
+
+    fn get_current_width(&self) -> Option<Size> {
+        None
+    }
+}
+
+impl Print for MJInclude {
+    fn print(&self, printer: &mut Printer) {
+        self.include.print(printer);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::tests::compare_render_with_options;
+    use crate::util::include::{FileIncludeLoader, MemoryIncludeLoader};
+    use crate::{to_html, to_mjml, Options};
+    use std::sync::Arc;
+
+    fn options() -> Options {
+        Options {
+            include_loader: Arc::new(FileIncludeLoader::new("test")),
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn base() {
+        compare_render_with_options(
+            include_str!("../../../test/mj-include.mjml"),
+            include_str!("../../../test/mj-include.html"),
+            options(),
+        );
+    }
+
+    #[test]
+    fn with_types() {
+        compare_render_with_options(
+            include_str!("../../../test/mj-include-types.mjml"),
+            include_str!("../../../test/mj-include-types.html"),
+            options(),
+        );
+    }
+
+    #[test]
+    fn in_containers() {
+        compare_render_with_options(
+            include_str!("../../../test/mj-include-containers.mjml"),
+            include_str!("../../../test/mj-include-containers.html"),
+            options(),
+        );
+    }
+
+    #[test]
+    fn with_document_head() {
+        compare_render_with_options(
+            include_str!("../../../test/mj-include-document.mjml"),
+            include_str!("../../../test/mj-include-document.html"),
+            options(),
+        );
+    }
+
+    #[test]
+    fn print() {
+        let template = include_str!("../../../test/mj-include-print.mjml");
+        assert_eq!(to_mjml(template, options()).unwrap(), template);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let result = to_html(
+            include_str!("../../../test/mj-include.mjml"),
+            Options::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn cycle() {
+        let mut loader = MemoryIncludeLoader::default();
+        loader.insert("a.mjml", "<mj-include path=\"b.mjml\" />");
+        loader.insert("b.mjml", "<mj-include path=\"./a.mjml\" />");
+        let opts = Options {
+            include_loader: Arc::new(loader),
+            ..Options::default()
+        };
+        let template = "<mjml><mj-body><mj-include path=\"a.mjml\" /></mj-body></mjml>";
+        let error = to_html(template, opts).unwrap_err();
+        let message = error.to_string();
+        assert!(message.starts_with("unable to include \"a.mjml\": unable to include \"b.mjml\": unable to include \"a.mjml\": include cycle detected"));
+    }
+
+    #[test]
+    fn max_depth() {
+        let mut loader = MemoryIncludeLoader::default();
+        loader.insert("a.mjml", "<mj-include path=\"b.mjml\" />");
+        loader.insert("b.mjml", "<mj-include path=\"c.mjml\" />");
+        loader.insert("c.mjml", "<mj-section />");
+        let opts = Options {
+            include_loader: Arc::new(loader),
+            include_max_depth: 2,
+            ..Options::default()
+        };
+        let template = "<mjml><mj-body><mj-include path=\"a.mjml\" /></mj-body></mjml>";
+        let message = to_html(template, opts).unwrap_err().to_string();
+        assert!(
+            message.contains("unable to include \"c.mjml\": maximum include depth of 2 reached")
+        );
+    }
+
+    #[test]
+    fn error_in_included_template() {
+        let mut loader = MemoryIncludeLoader::default();
+        loader.insert(
+            "partials/a.mjml",
+            "<mj-section>\n  <mj-unknown />\n</mj-section>",
+        );
+        let opts = Options {
+            include_loader: Arc::new(loader),
+            ..Options::default()
+        };
+        let template = "<mjml><mj-head><mj-include path=\"partials/a.mjml\" /></mj-head></mjml>";
+        let error = to_html(template, opts).unwrap_err();
+        let message = error.to_string();
+        assert!(message.starts_with("unable to include \"partials/a.mjml\": unexpected element <mj-section> at line 1, column 1"));
+        assert_eq!(error.location().path, "mjml > mj-head > mj-include");
+    }
+}
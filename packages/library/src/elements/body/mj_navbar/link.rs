@@ -7,8 +7,11 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -21,29 +24,47 @@ lazy_static! {
         .add("target", "_blank")
         .add("text-decoration", "none")
         .add("text-transform", "uppercase");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(FONT)
+        .extend(LINK)
+        .extend(PADDING)
+        .add("letter-spacing", AttributeType::String)
+        .add("text-transform", AttributeType::String);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJNavbarLink {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     content: Option<String>,
 }
 
 impl MJNavbarLink {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-navbar-link",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse_link<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJNavbarLink, Error> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJNavbarLink, Error> {
         if node.name.as_str() != "mj-navbar-link" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
         let content = node
             .children
@@ -51,35 +72,22 @@ impl MJNavbarLink {
             .filter_map(|child| child.as_text())
             .map(|child| child.as_str())
             .collect::<String>();
-        let mut attributes = Self::default_attributes(node, header);
-        if let Some(extra) = extra {
-            attributes.merge(extra);
-        }
-        attributes.merge(node);
         Ok(MJNavbarLink {
-            attributes,
-            context: None,
-            content: if content.is_empty() {
-                None
-            } else {
-                Some(content)
-            },
-        })
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..MJNavbarLink::new()
+        }
+        .content(content))
     }
 
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJNavbarLink, Error> {
-        let mut attrs = match extra {
-            Some(value) => value.into(),
-            None => Attributes::default(),
+    /// set the text of the link
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        let content = content.to_string();
+        self.content = if content.is_empty() {
+            None
+        } else {
+            Some(content)
         };
-        if attrs.get("text-padding").is_none() {
-            attrs.set("text-padding", "4px 4px 4px 0");
-        }
-        Self::parse_link(node, header, Some(&attrs))
+        self
     }
 
     fn set_style_a(&self, tag: Tag) -> Tag {
@@ -116,12 +124,16 @@ impl MJNavbarLink {
         })
     }
 
-    fn render_content(&self, _header: &Header) -> Result<String, Error> {
+    fn render_content(&self, header: &Header) -> Result<String, Error> {
         let link = self
             .set_style_a(Tag::new("a"))
             .set_class("mj-link")
             .maybe_set_class(self.get_attribute("css-class"))
-            .maybe_set_attribute("href", self.get_link())
+            .maybe_set_attribute(
+                "href",
+                self.get_link()
+                    .map(|href| header.rewrite_link("mj-navbar-link", &href)),
+            )
             .maybe_set_attribute("rel", self.get_attribute("rel"))
             .maybe_set_attribute("target", self.get_attribute("target"))
             .maybe_set_attribute("name", self.get_attribute("name"));
@@ -157,15 +169,33 @@ impl Component for MJNavbarLink {
     }
 }
 
+impl Builder for MJNavbarLink {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJNavbarLink {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+    }
+
     fn get_current_width(&self) -> Option<Size> {
         None
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -176,4 +206,24 @@ impl BodyComponent for MJNavbarLink {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.list_item();
+        writer.link(
+            "mj-navbar-link",
+            self.content.as_deref().unwrap_or_default(),
+            self.get_link().as_deref(),
+        );
+        writer.line_break();
+    }
+}
+
+impl Print for MJNavbarLink {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content(
+            "mj-navbar-link",
+            &self.source_attributes,
+            self.content.as_deref().unwrap_or_default(),
+        );
+    }
 }
@@ -1,16 +1,19 @@
 use super::link::MJNavbarLink;
+use crate::elements::body::mj_include::MJInclude;
 use crate::elements::body::prelude::*;
 use crate::elements::body::BodyElement;
 use crate::elements::error::Error;
 use crate::elements::prelude::*;
-use crate::parser::Node;
+use crate::parser::{Element, Node};
 use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
-use crate::util::id::generate as generate_id;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -25,53 +28,99 @@ lazy_static! {
         .add("ico-padding", "10px")
         .add("ico-text-decoration", "none")
         .add("ico-line-height", "30px");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-navbar-link", "mj-raw"])
+        .extend(BODY_COMMON)
+        .add("align", ALIGN)
+        .add("base-url", AttributeType::Url)
+        .add("hamburger", AttributeType::Enum(&["hamburger"]))
+        .add("ico-align", ALIGN)
+        .add("ico-close", AttributeType::String)
+        .add("ico-color", AttributeType::Color)
+        .add("ico-font-family", AttributeType::String)
+        .add("ico-font-size", AttributeType::Size)
+        .add("ico-line-height", AttributeType::String)
+        .add("ico-open", AttributeType::String)
+        .add("ico-padding", AttributeType::Spacing)
+        .add("ico-padding-bottom", AttributeType::Size)
+        .add("ico-padding-left", AttributeType::Size)
+        .add("ico-padding-right", AttributeType::Size)
+        .add("ico-padding-top", AttributeType::Size)
+        .add("ico-text-decoration", AttributeType::String)
+        .add("ico-text-transform", AttributeType::String);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJNavbar {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
     id: String,
 }
 
-fn create_id() -> String {
-    if cfg!(test) {
-        "navbar_toggle".into()
-    } else {
-        generate_id(8)
+impl MJNavbar {
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-navbar",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
-}
 
-impl MJNavbar {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJNavbar, Error> {
-        let mut result = MJNavbar {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children: vec![],
-            id: create_id(),
-        };
-        let attrs = result.get_children_attributes();
-        for child in node.children.iter() {
+        Ok(MJNavbar {
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: Self::parse_children(&node.children, header)?,
+            ..MJNavbar::new()
+        })
+    }
+
+    fn parse_children<'a>(
+        elements: &[Element<'a>],
+        header: &Header,
+    ) -> Result<Vec<BodyElement>, Error> {
+        let mut result = vec![];
+        for child in elements.iter() {
             if let Some(child_node) = child.as_node() {
-                let tag_name = child_node.name.as_str();
-                if tag_name != "mj-navbar-link" {
-                    return Err(Error::UnexpectedElement(tag_name.into()));
-                } else {
-                    let element = MJNavbarLink::parse_link(&child_node, header, Some(&attrs))?;
-                    result.children.push(BodyElement::MJNavbarLink(element));
+                match child_node.name.as_str() {
+                    "mj-navbar-link" => {
+                        let element = MJNavbarLink::parse(child_node, header)?;
+                        result.push(BodyElement::MJNavbarLink(element));
+                    }
+                    "mj-include" => {
+                        result.extend(MJInclude::parse_with(
+                            child_node,
+                            header,
+                            Self::parse_children,
+                        )?);
+                    }
+                    tag_name => {
+                        return Err(Error::UnexpectedElement(
+                            tag_name.into(),
+                            child_node.span.into(),
+                        ))
+                    }
                 }
             }
         }
         Ok(result)
     }
 
+    pub fn link(mut self, link: MJNavbarLink) -> Self {
+        self.children.push(BodyElement::MJNavbarLink(link));
+        self
+    }
+
     fn get_children_attributes(&self) -> Attributes {
         let mut attrs = Attributes::default();
         attrs.maybe_set("navbar-base-url", self.get_attribute("base-url"));
@@ -225,11 +274,36 @@ impl Component for MJNavbar {
     }
 }
 
+impl Builder for MJNavbar {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJNavbar {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.id = header.generate_id(8);
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let attrs = self.get_children_attributes();
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, Some(&attrs));
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -241,11 +315,50 @@ impl BodyComponent for MJNavbar {
     fn set_style(&self, _name: &str, tag: Tag) -> Tag {
         tag
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJNavbar {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-navbar",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
+    use crate::{to_html, Options};
+
+    #[test]
+    fn ids_from_seed() {
+        let template = include_str!("../../../../test/mj-navbar-ico.mjml");
+        let render = |id_seed| {
+            to_html(
+                template,
+                Options {
+                    id_seed,
+                    ..Options::default()
+                },
+            )
+            .unwrap()
+        };
+        assert_eq!(render(42), render(42));
+        assert_ne!(render(42), render(43));
+    }
 
     #[test]
     fn base() {
@@ -7,15 +7,31 @@ use crate::parser::Node;
 use crate::util::attributes::Attributes;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
+use crate::util::text::TextWriter;
 
-#[derive(Clone, Debug)]
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new().extend(BODY_COMMON);
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct MJRaw {
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJRaw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJRaw, Error> {
         let mut children = vec![];
         for child in node.children.iter() {
@@ -28,6 +44,13 @@ impl MJRaw {
             children,
         })
     }
+
+    /// add some html content to the element
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.children
+            .push(BodyElement::Raw(RawElement::Text(content.to_string())));
+        self
+    }
 }
 
 impl Component for MJRaw {
@@ -53,6 +76,12 @@ impl BodyComponent for MJRaw {
         None
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -60,6 +89,29 @@ impl BodyComponent for MJRaw {
     fn get_current_width(&self) -> Option<Size> {
         None
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJRaw {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                "mj-raw",
+                &Attributes::default(),
+                self.children.is_empty(),
+                |printer| {
+                    BodyElement::print_all(&self.children, printer);
+                },
+            )
+        });
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+use super::mj_column::MJColumn;
+use super::mj_group::MJGroup;
 use super::BodyElement;
 use crate::elements::body::prelude::*;
 use crate::elements::error::Error;
@@ -7,6 +9,8 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 
@@ -18,34 +22,71 @@ lazy_static! {
         .add("padding", "20px 0")
         .add("text-align", "center")
         .add("text-padding", "4px 4px 4px 0");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-column", "mj-group", "mj-raw"])
+        .extend(BODY_COMMON)
+        .extend(BORDER)
+        .extend(PADDING)
+        .add("background-color", AttributeType::Color)
+        .add(
+            "background-repeat",
+            AttributeType::Enum(&["repeat", "no-repeat"]),
+        )
+        .add("background-size", AttributeType::String)
+        .add("background-url", AttributeType::Url)
+        .add("direction", DIRECTION)
+        .add("full-width", AttributeType::Enum(&["full-width", "false"]))
+        .add("text-align", ALIGN)
+        .add("text-padding", AttributeType::Spacing);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJSection {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJSection {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-section",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJSection, Error> {
-        let mut children = vec![];
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
-        }
         Ok(MJSection {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..Self::default()
         })
     }
 
+    pub fn column(self, column: MJColumn) -> Self {
+        self.add_child(column)
+    }
+
+    pub fn group(self, group: MJGroup) -> Self {
+        self.add_child(group)
+    }
+
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn get_background(&self) -> Option<String> {
         let mut res: Vec<String> = vec![];
         if let Some(color) = self.get_attribute("background-color") {
@@ -187,7 +228,7 @@ impl MJSection {
         res.push(conditional_tag(tr.open()));
         for child in self.children.iter() {
             match child {
-                BodyElement::Raw(element) => res.push(element.render(header)?),
+                _ if child.is_raw() => res.push(child.render(header)?),
                 _ => {
                     let td = Tag::td()
                         .maybe_set_attribute("align", child.get_attribute("align"))
@@ -340,11 +381,34 @@ impl Component for MJSection {
     }
 }
 
+impl Builder for MJSection {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJSection {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -365,6 +429,19 @@ impl BodyComponent for MJSection {
     }
 }
 
+impl Print for MJSection {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-section",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
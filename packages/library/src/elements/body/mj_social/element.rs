@@ -6,8 +6,11 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -111,30 +114,55 @@ lazy_static! {
         .add("padding", "4px")
         .add("target", "_blank")
         .add("text-decoration", "none");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(FONT)
+        .extend(LINK)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("alt", AttributeType::String)
+        .add("background-color", AttributeType::Color)
+        .add("border-radius", AttributeType::String)
+        .add("icon-height", AttributeType::Size)
+        .add("icon-padding", AttributeType::Spacing)
+        .add("icon-size", AttributeType::Size)
+        .add("src", AttributeType::Url)
+        .add("text-padding", AttributeType::Spacing);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJSocialElement {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     content: Option<String>,
     social_network: Option<SocialNetwork>,
 }
 
 impl MJSocialElement {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-social-element",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse_social_child<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJSocialElement, Error> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJSocialElement, Error> {
         if node.name.as_str() != "mj-social-element" {
-            return Err(Error::UnexpectedElement(node.name.as_str().into()));
+            return Err(Error::UnexpectedElement(
+                node.name.as_str().into(),
+                node.span.into(),
+            ));
         }
         let content = node
             .children
@@ -142,40 +170,22 @@ impl MJSocialElement {
             .filter_map(|child| child.as_text())
             .map(|child| child.as_str())
             .collect::<String>();
-        let social_network = node
-            .attributes
-            .iter()
-            .find(|(key, _value)| key.as_str() == "name")
-            .and_then(|(_key, value)| SocialNetwork::from_str(value.as_str()).ok());
-        let mut attributes = Self::default_attributes(node, header);
-        if let Some(extra) = extra {
-            attributes.merge(extra);
-        }
         Ok(MJSocialElement {
-            attributes: attributes.concat(node),
-            context: None,
-            content: if content.is_empty() {
-                None
-            } else {
-                Some(content)
-            },
-            social_network,
-        })
-    }
-
-    pub fn parse<'a>(
-        node: &Node<'a>,
-        header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<MJSocialElement, Error> {
-        let mut attrs = match extra {
-            Some(value) => value.into(),
-            None => Attributes::default(),
-        };
-        if attrs.get("text-padding").is_none() {
-            attrs.set("text-padding", "4px 4px 4px 0");
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..MJSocialElement::new()
         }
-        Self::parse_social_child(node, header, Some(&attrs))
+        .content(content))
+    }
+
+    /// set the text displayed next to the icon
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        let content = content.to_string();
+        self.content = if content.is_empty() {
+            None
+        } else {
+            Some(content)
+        };
+        self
     }
 
     fn get_background_color(&self) -> Option<String> {
@@ -311,8 +321,10 @@ impl Component for MJSocialElement {
         self.context = Some(ctx);
     }
 
-    fn render(&self, _header: &Header) -> Result<String, Error> {
-        let href = self.get_href();
+    fn render(&self, header: &Header) -> Result<String, Error> {
+        let href = self
+            .get_href()
+            .map(|href| header.rewrite_link("mj-social-element", &href));
         let tr = Tag::tr().maybe_set_class(self.get_attribute("css-class"));
         let td = self.set_style_td(Tag::td());
 
@@ -327,11 +339,33 @@ impl Component for MJSocialElement {
     }
 }
 
+impl Builder for MJSocialElement {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJSocialElement {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        let mut attributes = self.default_attributes(header);
+        if let Some(extra) = extra {
+            attributes.merge(extra);
+        }
+        self.attributes = attributes.concat(&self.source_attributes);
+        self.social_network = self
+            .source_attributes
+            .get("name")
+            .and_then(|value| SocialNetwork::from_str(value.as_str()).ok());
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -351,4 +385,26 @@ impl BodyComponent for MJSocialElement {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        let label = self
+            .content
+            .as_ref()
+            .or_else(|| self.get_attribute("name"))
+            .map(|value| value.as_str())
+            .unwrap_or_default();
+        writer.list_item();
+        writer.link("mj-social-element", label, self.get_href().as_deref());
+        writer.line_break();
+    }
+}
+
+impl Print for MJSocialElement {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content(
+            "mj-social-element",
+            &self.source_attributes,
+            self.content.as_deref().unwrap_or_default(),
+        );
+    }
 }
@@ -1,15 +1,19 @@
 use super::element::MJSocialElement;
+use crate::elements::body::mj_include::MJInclude;
 use crate::elements::body::prelude::*;
 use crate::elements::body::BodyElement;
 use crate::elements::error::Error;
 use crate::elements::prelude::*;
-use crate::parser::Node;
+use crate::parser::{Element, Node};
 use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -23,44 +27,92 @@ lazy_static! {
         .add("mode", "horizontal")
         .add("padding", "10px 25px")
         .add("text-decoration", "none");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-raw", "mj-social-element"])
+        .extend(BODY_COMMON)
+        .extend(FONT)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("border-radius", AttributeType::String)
+        .add("container-background-color", AttributeType::Color)
+        .add("icon-height", AttributeType::Size)
+        .add("icon-padding", AttributeType::Spacing)
+        .add("icon-size", AttributeType::Size)
+        .add("inner-padding", AttributeType::Spacing)
+        .add("mode", AttributeType::Enum(&["horizontal", "vertical"]))
+        .add("text-padding", AttributeType::Spacing);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJSocial {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJSocial {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-social",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJSocial, Error> {
-        let mut result = MJSocial {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
-            children: vec![],
-        };
-        let attrs = result.get_children_attributes();
-        for child in node.children.iter() {
+        Ok(MJSocial {
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: Self::parse_children(&node.children, header)?,
+            ..MJSocial::new()
+        })
+    }
+
+    fn parse_children<'a>(
+        elements: &[Element<'a>],
+        header: &Header,
+    ) -> Result<Vec<BodyElement>, Error> {
+        let mut result = vec![];
+        for child in elements.iter() {
             if let Some(child_node) = child.as_node() {
-                let tag_name = child_node.name.as_str();
-                if tag_name != "mj-social-element" {
-                    return Err(Error::UnexpectedElement(tag_name.into()));
-                } else {
-                    let element =
-                        MJSocialElement::parse_social_child(&child_node, header, Some(&attrs))?;
-                    result.children.push(BodyElement::MJSocialElement(element));
+                match child_node.name.as_str() {
+                    "mj-social-element" => {
+                        let element = MJSocialElement::parse(child_node, header)?;
+                        result.push(BodyElement::MJSocialElement(element));
+                    }
+                    "mj-include" => {
+                        result.extend(MJInclude::parse_with(
+                            child_node,
+                            header,
+                            Self::parse_children,
+                        )?);
+                    }
+                    tag_name => {
+                        return Err(Error::UnexpectedElement(
+                            tag_name.into(),
+                            child_node.span.into(),
+                        ))
+                    }
                 }
             }
         }
         Ok(result)
     }
 
+    pub fn element(mut self, element: MJSocialElement) -> Self {
+        self.children.push(BodyElement::MJSocialElement(element));
+        self
+    }
+
     fn set_style_table_vertical(&self, tag: Tag) -> Tag {
         tag.set_style("margin", "0px")
     }
@@ -104,6 +156,10 @@ impl MJSocial {
         res.push(tr.open());
         res.push(END_CONDITIONAL_TAG.into());
         for child in self.children.iter() {
+            if child.is_raw() {
+                res.push(child.render(header)?);
+                continue;
+            }
             res.push(conditional_tag(td.open()));
             res.push(inner_table.render(child.render(header)?));
             res.push(conditional_tag(td.close()));
@@ -159,7 +215,20 @@ impl Component for MJSocial {
     }
 }
 
+impl Builder for MJSocial {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJSocial {
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -172,12 +241,44 @@ impl BodyComponent for MJSocial {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let attrs = self.get_children_attributes();
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, Some(&attrs));
+        }
+    }
+
     fn set_style(&self, name: &str, tag: Tag) -> Tag {
         match name {
             "table-vertical" => self.set_style_table_vertical(tag),
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJSocial {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-social",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
 }
 
 #[cfg(test)]
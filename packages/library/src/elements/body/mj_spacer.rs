@@ -7,30 +7,53 @@ use crate::util::attributes::*;
 use crate::util::condition::{END_CONDITIONAL_TAG, START_CONDITIONAL_TAG};
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default().add("height", "20px");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .extend(BODY_COMMON)
+        .extend(PADDING)
+        .add("container-background-color", AttributeType::Color)
+        .add("height", AttributeType::Size)
+        .add("vertical-align", VERTICAL_ALIGN)
+        .add("width", AttributeType::Size);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJSpacer {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
 }
 
 impl MJSpacer {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-spacer",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
     }
 
-    pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJSpacer, Error> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>, _header: &Header) -> Result<MJSpacer, Error> {
         Ok(MJSpacer {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            ..Self::default()
         })
     }
 
@@ -73,11 +96,27 @@ impl Component for MJSpacer {
     }
 }
 
+impl Builder for MJSpacer {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJSpacer {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -92,6 +131,16 @@ impl BodyComponent for MJSpacer {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+    }
+}
+
+impl Print for MJSpacer {
+    fn print(&self, printer: &mut Printer) {
+        printer.empty("mj-spacer", &self.source_attributes);
+    }
 }
 
 #[cfg(test)]
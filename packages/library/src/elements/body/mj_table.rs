@@ -7,8 +7,11 @@ use crate::parser::Node;
 use crate::util::attributes::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -23,20 +26,46 @@ lazy_static! {
         .add("padding", "10px 25px")
         .add("table-layout", "auto")
         .add("width", "100%");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(FONT)
+        .extend(PADDING)
+        .add("align", ALIGN)
+        .add("border", AttributeType::String)
+        .add("cellpadding", AttributeType::Integer)
+        .add("cellspacing", AttributeType::Integer)
+        .add("container-background-color", AttributeType::Color)
+        .add(
+            "table-layout",
+            AttributeType::Enum(&["auto", "fixed", "initial", "inherit"]),
+        )
+        .add("width", AttributeType::String);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJTable {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJTable {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-table",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJTable, Error> {
@@ -47,12 +76,19 @@ impl MJTable {
             )?));
         }
         Ok(MJTable {
-            attributes: Self::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
             children,
+            ..Self::default()
         })
     }
 
+    /// add some html content to the element
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.children
+            .push(BodyElement::Raw(RawElement::Text(content.to_string())));
+        self
+    }
+
     fn set_style_table(&self, tag: Tag) -> Tag {
         tag.maybe_set_style("border", self.get_attribute("border"))
             .maybe_set_style("color", self.get_attribute("color"))
@@ -92,18 +128,63 @@ impl Component for MJTable {
     }
 }
 
+impl Builder for MJTable {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJTable {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        for child in self.children.iter_mut() {
+            child.resolve(header, None);
+        }
+    }
+
     fn get_current_width(&self) -> Option<Size> {
         None
     }
 
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJTable {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                "mj-table",
+                &self.source_attributes,
+                self.children.is_empty(),
+                |printer| {
+                    BodyElement::print_all(&self.children, printer);
+                },
+            )
+        });
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use super::raw::RawElement;
 use super::BodyElement;
 use crate::elements::body::prelude::*;
 use crate::elements::error::Error;
@@ -7,8 +8,11 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 lazy_static! {
     static ref DEFAULT_ATTRIBUTES: Attributes = Attributes::default()
@@ -18,34 +22,66 @@ lazy_static! {
         .add("font-size", "13px")
         .add("line-height", "1")
         .add("padding", "10px 25px");
+    static ref SCHEMA: Schema = Schema::new()
+        .extend(BODY_COMMON)
+        .extend(FONT)
+        .extend(PADDING)
+        .add(
+            "align",
+            AttributeType::Enum(&["left", "right", "center", "justify"]),
+        )
+        .add("container-background-color", AttributeType::Color)
+        .add("height", AttributeType::Size)
+        .add("letter-spacing", AttributeType::String)
+        .add("text-transform", AttributeType::String)
+        .add("vertical-align", VERTICAL_ALIGN);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJText {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJText {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-text",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJText, Error> {
         let mut children = vec![];
         for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
+            children.push(BodyElement::parse(child, header)?);
         }
         Ok(MJText {
-            attributes: MJText::default_attributes(node, header).concat(node),
-            context: None,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
             children,
+            ..Self::default()
         })
     }
 
+    /// add some html content to the element
+    pub fn content<C: ToString>(mut self, content: C) -> Self {
+        self.children
+            .push(BodyElement::Raw(RawElement::Text(content.to_string())));
+        self
+    }
+
     fn set_style_text(&self, tag: Tag) -> Tag {
         tag.maybe_set_style("font-family", self.get_attribute("font-family"))
             .maybe_set_style("font-size", self.get_attribute("font-size"))
@@ -102,11 +138,33 @@ impl Component for MJText {
     }
 }
 
+impl Builder for MJText {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJText {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        for child in self.children.iter_mut() {
+            child.resolve(header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &EMPTY_CHILDREN
     }
@@ -121,6 +179,29 @@ impl BodyComponent for MJText {
             _ => tag,
         }
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.block();
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+        writer.block();
+    }
+}
+
+impl Print for MJText {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                "mj-text",
+                &self.source_attributes,
+                self.children.is_empty(),
+                |printer| {
+                    BodyElement::print_all(&self.children, printer);
+                },
+            )
+        });
+    }
 }
 
 #[cfg(test)]
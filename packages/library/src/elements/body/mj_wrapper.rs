@@ -1,3 +1,4 @@
+use super::mj_section::MJSection;
 use super::BodyElement;
 use crate::elements::body::prelude::*;
 use crate::elements::error::Error;
@@ -7,6 +8,8 @@ use crate::util::attributes::*;
 use crate::util::condition::*;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
 
@@ -18,34 +21,67 @@ lazy_static! {
         .add("padding", "20px 0")
         .add("text-align", "center")
         .add("text-padding", "4px 4px 4px 0");
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-hero", "mj-raw", "mj-section"])
+        .extend(BODY_COMMON)
+        .extend(BORDER)
+        .extend(PADDING)
+        .add("background-color", AttributeType::Color)
+        .add(
+            "background-repeat",
+            AttributeType::Enum(&["repeat", "no-repeat"]),
+        )
+        .add("background-size", AttributeType::String)
+        .add("background-url", AttributeType::Url)
+        .add("direction", DIRECTION)
+        .add("full-width", AttributeType::Enum(&["full-width", "false"]))
+        .add("text-align", ALIGN)
+        .add("text-padding", AttributeType::Spacing);
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MJWrapper {
     attributes: Attributes,
+    source_attributes: Attributes,
     context: Option<Context>,
     children: Vec<BodyElement>,
 }
 
 impl MJWrapper {
-    fn default_attributes<'a>(node: &Node<'a>, header: &Header) -> Attributes {
-        header
-            .default_attributes()
-            .get_attributes(node, DEFAULT_ATTRIBUTES.clone())
+    fn default_attributes(&self, header: &Header) -> Attributes {
+        header.default_attributes().get_attributes(
+            "mj-wrapper",
+            &self.source_attributes,
+            DEFAULT_ATTRIBUTES.clone(),
+        )
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
     }
 
     pub fn parse<'a>(node: &Node<'a>, header: &Header) -> Result<MJWrapper, Error> {
-        let mut children = vec![];
-        for child in node.children.iter() {
-            children.push(BodyElement::parse(&child, header, None::<&Attributes>)?);
-        }
         Ok(MJWrapper {
-            attributes: MJWrapper::default_attributes(node, header).concat(node),
-            context: None,
-            children,
+            source_attributes: SCHEMA.normalize(Attributes::from(node)),
+            children: BodyElement::parse_all(&node.children, header)?,
+            ..Self::default()
         })
     }
 
+    pub fn section(self, section: MJSection) -> Self {
+        self.add_child(section)
+    }
+
+    pub fn add_child<C: Into<BodyElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
     fn get_background(&self) -> Option<String> {
         let mut res: Vec<String> = vec![];
         if let Some(color) = self.get_attribute("background-color") {
@@ -187,7 +223,7 @@ impl MJWrapper {
         let mut res = vec![];
         for child in self.children.iter() {
             match child {
-                BodyElement::Raw(element) => res.push(element.render(header)?),
+                _ if child.is_raw() => res.push(child.render(header)?),
                 _ => {
                     let td = Tag::td()
                         .maybe_set_attribute("align", child.get_attribute("align"))
@@ -344,11 +380,34 @@ impl Component for MJWrapper {
     }
 }
 
+impl Builder for MJWrapper {
+    fn source_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.source_attributes
+    }
+}
+
 impl BodyComponent for MJWrapper {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
 
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        self.attributes = self
+            .default_attributes(header)
+            .concat(&self.source_attributes);
+        let header = header.scoped(self.get_attribute("mj-class"));
+        for child in self.children.iter_mut() {
+            child.resolve(&header, None);
+        }
+    }
+
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -369,6 +428,19 @@ impl BodyComponent for MJWrapper {
     }
 }
 
+impl Print for MJWrapper {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-wrapper",
+            &self.source_attributes,
+            self.children.is_empty(),
+            |printer| {
+                BodyElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
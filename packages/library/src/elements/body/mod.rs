@@ -4,9 +4,12 @@ use crate::parser::Element;
 use crate::util::attributes::Attributes;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 use prelude::BodyComponent;
+use std::fmt;
 
 pub mod mj_accordion;
 pub mod mj_body;
@@ -17,6 +20,7 @@ pub mod mj_divider;
 pub mod mj_group;
 pub mod mj_hero;
 pub mod mj_image;
+pub mod mj_include;
 pub mod mj_navbar;
 pub mod mj_raw;
 pub mod mj_section;
@@ -40,6 +44,7 @@ pub enum BodyElement {
     MJGroup(mj_group::MJGroup),
     MJHero(mj_hero::MJHero),
     MJImage(mj_image::MJImage),
+    MJInclude(mj_include::MJInclude),
     MJNavbar(mj_navbar::MJNavbar),
     MJNavbarLink(mj_navbar::MJNavbarLink),
     MJRaw(mj_raw::MJRaw),
@@ -66,6 +71,7 @@ macro_rules! inner_element {
             BodyElement::MJGroup(item) => item,
             BodyElement::MJHero(item) => item,
             BodyElement::MJImage(item) => item,
+            BodyElement::MJInclude(item) => item,
             BodyElement::MJNavbar(item) => item,
             BodyElement::MJNavbarLink(item) => item,
             BodyElement::MJRaw(item) => item,
@@ -81,6 +87,38 @@ macro_rules! inner_element {
     };
 }
 
+macro_rules! from_element {
+    ($variant:ident, $kind:ty) => {
+        impl From<$kind> for BodyElement {
+            fn from(value: $kind) -> Self {
+                BodyElement::$variant(value)
+            }
+        }
+    };
+}
+
+from_element!(MJAccordion, mj_accordion::MJAccordion);
+from_element!(MJAccordionElement, mj_accordion::MJAccordionElement);
+from_element!(MJButton, mj_button::MJButton);
+from_element!(MJCarousel, mj_carousel::MJCarousel);
+from_element!(MJCarouselImage, mj_carousel::MJCarouselImage);
+from_element!(MJColumn, mj_column::MJColumn);
+from_element!(MJDivider, mj_divider::MJDivider);
+from_element!(MJGroup, mj_group::MJGroup);
+from_element!(MJHero, mj_hero::MJHero);
+from_element!(MJImage, mj_image::MJImage);
+from_element!(MJNavbar, mj_navbar::MJNavbar);
+from_element!(MJNavbarLink, mj_navbar::MJNavbarLink);
+from_element!(MJRaw, mj_raw::MJRaw);
+from_element!(MJSection, mj_section::MJSection);
+from_element!(MJSocial, mj_social::MJSocial);
+from_element!(MJSocialElement, mj_social::MJSocialElement);
+from_element!(MJSpacer, mj_spacer::MJSpacer);
+from_element!(MJTable, mj_table::MJTable);
+from_element!(MJText, mj_text::MJText);
+from_element!(MJWrapper, mj_wrapper::MJWrapper);
+from_element!(Raw, raw::RawElement);
+
 impl Component for BodyElement {
     fn update_header(&self, header: &mut Header) {
         self.inner().update_header(header)
@@ -109,6 +147,15 @@ impl BodyComponent for BodyElement {
     fn attributes(&self) -> Option<&Attributes> {
         self.inner().attributes()
     }
+    fn write_text(&self, writer: &mut TextWriter) {
+        self.inner().write_text(writer)
+    }
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        self.inner_mut().resolve(header, extra)
+    }
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        self.inner_mut().update_attributes(update)
+    }
     fn set_style(&self, key: &str, tag: Tag) -> Tag {
         self.inner().set_style(key, tag)
     }
@@ -118,6 +165,18 @@ impl BodyComponent for BodyElement {
     }
 }
 
+impl Print for BodyElement {
+    fn print(&self, printer: &mut Printer) {
+        self.inner().print(printer)
+    }
+}
+
+impl fmt::Display for BodyElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_mjml())
+    }
+}
+
 impl BodyElement {
     pub fn inner_mut(&mut self) -> &mut dyn BodyComponent {
         inner_element!(self)
@@ -127,11 +186,39 @@ impl BodyElement {
         inner_element!(self)
     }
 
-    pub fn parse<'a>(
-        element: &Element<'a>,
+    /// parse a list of elements, replacing the `mj-include` elements by their content
+    pub fn parse_all<'a>(
+        elements: &[Element<'a>],
         header: &Header,
-        extra: Option<&Attributes>,
-    ) -> Result<BodyElement, Error> {
+    ) -> Result<Vec<BodyElement>, Error> {
+        let mut res = vec![];
+        for element in elements {
+            match element {
+                Element::Node(node) if node.name.as_str() == "mj-include" => {
+                    res.extend(mj_include::MJInclude::parse(node, header)?);
+                }
+                _ => res.push(BodyElement::parse(element, header)?),
+            };
+        }
+        Ok(res)
+    }
+
+    /// print a list of elements, the `mj-include` being printed instead of their content
+    pub fn print_all(elements: &[BodyElement], printer: &mut Printer) {
+        let mut included = 0;
+        for element in elements.iter() {
+            if included > 0 {
+                included -= 1;
+                continue;
+            }
+            if let BodyElement::MJInclude(include) = element {
+                included = include.expanded();
+            }
+            element.print(printer);
+        }
+    }
+
+    pub fn parse<'a>(element: &Element<'a>, header: &Header) -> Result<BodyElement, Error> {
         let res = match element {
             Element::Node(node) => match node.name.as_str() {
                 "mj-accordion" => {
@@ -141,9 +228,7 @@ impl BodyElement {
                 "mj-carousel" => {
                     BodyElement::MJCarousel(mj_carousel::MJCarousel::parse(node, header)?)
                 }
-                "mj-column" => {
-                    BodyElement::MJColumn(mj_column::MJColumn::parse(node, header, extra)?)
-                }
+                "mj-column" => BodyElement::MJColumn(mj_column::MJColumn::parse(node, header)?),
                 "mj-divider" => BodyElement::MJDivider(mj_divider::MJDivider::parse(node, header)?),
                 "mj-group" => BodyElement::MJGroup(mj_group::MJGroup::parse(node, header)?),
                 "mj-hero" => BodyElement::MJHero(mj_hero::MJHero::parse(node, header)?),
@@ -167,6 +252,6 @@ impl BodyElement {
     }
 
     pub fn is_raw(&self) -> bool {
-        matches!(self, BodyElement::Raw(_))
+        matches!(self, BodyElement::Raw(_) | BodyElement::MJInclude(_))
     }
 }
@@ -1,18 +1,27 @@
 use super::BodyElement;
 use crate::elements::prelude::*;
 use crate::util::attributes::Attributes;
+use crate::util::header::Header;
 use crate::util::size::Size;
 use crate::util::spacing::Spacing;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 use regex::Regex;
 
 lazy_static! {
     pub static ref EMPTY_CHILDREN: Vec<BodyElement> = vec![];
 }
 
-pub trait BodyComponent: Component {
+pub trait BodyComponent: Component + Print {
     fn attributes(&self) -> Option<&Attributes>;
 
+    /// compute the attributes of the element and its children from the ones it has been
+    /// created with, the default ones from the header and the ones given by its parent
+    fn resolve(&mut self, _header: &Header, _extra: Option<&Attributes>) {}
+
+    /// update the resolved attributes of the element and its children, before rendering
+    fn update_attributes(&mut self, _update: &dyn Fn(&mut Attributes)) {}
+
     fn get_attribute(&self, key: &str) -> Option<&String> {
         self.attributes().and_then(|src| src.get(&key.to_string()))
     }
@@ -23,6 +32,13 @@ pub trait BodyComponent: Component {
     fn get_children(&self) -> &Vec<BodyElement>;
     fn get_current_width(&self) -> Option<Size>;
 
+    /// write the element as plain text, the children being written by default
+    fn write_text(&self, writer: &mut TextWriter) {
+        for child in self.get_children().iter() {
+            child.write_text(writer);
+        }
+    }
+
     fn get_siblings(&self) -> usize {
         self.get_children().len()
     }
@@ -233,6 +249,7 @@ pub mod tests {
     use crate::util::attributes::Attributes;
     use crate::util::context::Context;
     use crate::util::header::Header;
+    use crate::util::printer::Printer;
     use crate::util::tag::Tag;
 
     struct TestComponent {
@@ -253,6 +270,12 @@ pub mod tests {
         }
     }
 
+    impl Print for TestComponent {
+        fn print(&self, _printer: &mut Printer) {
+            // noop
+        }
+    }
+
     impl BodyComponent for TestComponent {
         fn get_current_width(&self) -> Option<Size> {
             None
@@ -1,12 +1,15 @@
 use crate::elements::body::prelude::*;
 use crate::elements::body::BodyElement;
+use crate::elements::prelude::Print;
 use crate::elements::{Component, Error};
 use crate::parser::{Element, Node};
 use crate::util::attributes::Attributes;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
 use crate::util::size::Size;
 use crate::util::tag::Tag;
+use crate::util::text::TextWriter;
 
 #[derive(Clone, Debug)]
 pub struct NodeElement {
@@ -24,18 +27,19 @@ impl NodeElement {
     ) -> Result<NodeElement, Error> {
         let tag = node.name.as_str();
         if only_raw && tag.starts_with("mj-") {
-            return Err(Error::UnexpectedElement(tag.into()));
+            return Err(Error::UnexpectedElement(tag.into(), node.span.into()));
         }
-        let mut children = vec![];
-        for child in node.children.iter() {
-            if only_raw {
+        let children = if only_raw {
+            let mut children = vec![];
+            for child in node.children.iter() {
                 children.push(BodyElement::Raw(RawElement::conditional_parse(
                     child, header, true,
-                )?))
-            } else {
-                children.push(BodyElement::parse(&child, header, None)?);
+                )?));
             }
-        }
+            children
+        } else {
+            BodyElement::parse_all(&node.children, header)?
+        };
         Ok(NodeElement {
             attributes: Attributes::from(node),
             context: None,
@@ -47,6 +51,36 @@ impl NodeElement {
     fn closed_element(&self) -> bool {
         self.children.is_empty() && ["img"].contains(&self.tag.as_str())
     }
+
+    pub fn tag(&self) -> &str {
+        self.tag.as_str()
+    }
+
+    fn write_children_text(&self, writer: &mut TextWriter) {
+        for child in self.children.iter() {
+            child.write_text(writer);
+        }
+    }
+
+    /// cells of a table row, separated by a pipe
+    fn write_row_text(&self, writer: &mut TextWriter) {
+        let cells = self
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                BodyElement::Raw(RawElement::Node(node)) => Some(node),
+                _ => None,
+            })
+            .filter(|node| matches!(node.tag(), "td" | "th"))
+            .map(|node| TextWriter::capture(|writer| node.write_children_text(writer)))
+            .filter(|cell| !cell.is_empty())
+            .collect::<Vec<_>>();
+        if !cells.is_empty() {
+            writer.line_break();
+            writer.text(&cells.join(" | "));
+            writer.line_break();
+        }
+    }
 }
 
 impl Component for NodeElement {
@@ -59,7 +93,12 @@ impl Component for NodeElement {
     }
 
     fn render(&self, header: &Header) -> Result<String, Error> {
-        let tag = Tag::new(self.tag.as_str()).insert_attributes(self.attributes.inner());
+        let mut tag = Tag::new(self.tag.as_str()).insert_attributes(self.attributes.inner());
+        if self.tag.eq_ignore_ascii_case("a") {
+            if let Some(href) = self.attributes.get("href") {
+                tag = tag.set_attribute("href", header.rewrite_link("a", href));
+            }
+        }
         if self.closed_element() {
             Ok(tag.closed())
         } else {
@@ -76,6 +115,19 @@ impl BodyComponent for NodeElement {
     fn attributes(&self) -> Option<&Attributes> {
         Some(&self.attributes)
     }
+
+    fn resolve(&mut self, header: &Header, _extra: Option<&Attributes>) {
+        for child in self.children.iter_mut() {
+            child.resolve(header, None);
+        }
+    }
+    fn update_attributes(&mut self, update: &dyn Fn(&mut Attributes)) {
+        update(&mut self.attributes);
+        for child in self.children.iter_mut() {
+            child.update_attributes(update);
+        }
+    }
+
     fn get_children(&self) -> &Vec<BodyElement> {
         &self.children
     }
@@ -83,6 +135,54 @@ impl BodyComponent for NodeElement {
     fn get_current_width(&self) -> Option<Size> {
         None
     }
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        let tag = self.tag.to_lowercase();
+        match tag.as_str() {
+            "head" | "script" | "style" | "title" => (),
+            "br" => writer.line_break(),
+            "hr" => writer.rule(),
+            "img" => writer.image("img", self.get_attribute("alt").map(|v| v.as_str()), None),
+            "a" => {
+                let label = TextWriter::capture(|writer| self.write_children_text(writer));
+                writer.link("a", &label, self.get_attribute("href").map(|v| v.as_str()));
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                writer.block();
+                writer.text(&"#".repeat(tag[1..].parse::<usize>().unwrap_or(1)));
+                writer.text(" ");
+                self.write_children_text(writer);
+                writer.block();
+            }
+            "li" => {
+                writer.list_item();
+                self.write_children_text(writer);
+                writer.line_break();
+            }
+            "tr" => self.write_row_text(writer),
+            "blockquote" | "center" | "div" | "ol" | "p" | "table" | "ul" => {
+                writer.block();
+                self.write_children_text(writer);
+                writer.block();
+            }
+            _ => self.write_children_text(writer),
+        };
+    }
+}
+
+impl Print for NodeElement {
+    fn print(&self, printer: &mut Printer) {
+        printer.compact(|printer| {
+            printer.element(
+                self.tag.as_str(),
+                &self.attributes,
+                self.children.is_empty(),
+                |printer| {
+                    BodyElement::print_all(&self.children, printer);
+                },
+            )
+        });
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -159,4 +259,27 @@ impl BodyComponent for RawElement {
     fn attributes(&self) -> Option<&Attributes> {
         self.as_node().and_then(|node| node.attributes())
     }
+
+    fn resolve(&mut self, header: &Header, extra: Option<&Attributes>) {
+        if let RawElement::Node(node) = self {
+            node.resolve(header, extra);
+        }
+    }
+    fn write_text(&self, writer: &mut TextWriter) {
+        match self {
+            RawElement::Comment(_) => (),
+            RawElement::Node(node) => node.write_text(writer),
+            RawElement::Text(value) => writer.text(value),
+        };
+    }
+}
+
+impl Print for RawElement {
+    fn print(&self, printer: &mut Printer) {
+        match self {
+            RawElement::Comment(value) => printer.comment(value),
+            RawElement::Node(node) => node.print(printer),
+            RawElement::Text(value) => printer.text(value),
+        }
+    }
 }
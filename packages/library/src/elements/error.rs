@@ -1,9 +1,85 @@
+use crate::parser::Span;
+use crate::util::asset::AssetResolverError;
+use crate::util::include::IncludeLoaderError;
+use crate::util::template::TemplateError;
+use std::fmt;
+
+/// reason why an `mj-include` couldn't be resolved
+#[derive(Debug)]
+pub enum IncludeError {
+    /// the loader couldn't provide the template
+    Loader(IncludeLoaderError),
+    /// the template is already being included
+    Cycle,
+    /// the maximum depth of nested includes has been reached
+    MaxDepth(usize),
+    /// the included template is invalid, the error is located in the included template
+    Template(Box<crate::Error>),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IncludeError::Loader(err) => write!(f, "{}", err),
+            IncludeError::Cycle => write!(f, "include cycle detected"),
+            IncludeError::MaxDepth(depth) => {
+                write!(f, "maximum include depth of {} reached", depth)
+            }
+            IncludeError::Template(err) => write!(f, "{}", err),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    InvalidChild,
-    MissingAttribute(String),
-    ParseError(String),
-    UnexpectedText,
-    UnexpectedElement(String),
-    UnexpectedAttribute(String),
+    /// the image at the given url couldn't be embedded, after rendering
+    Asset(String, AssetResolverError),
+    Include(String, IncludeError, Span),
+    InvalidChild(Span),
+    MissingAttribute(String, Span),
+    ParseError(String, Span),
+    UnexpectedText(Span),
+    UnexpectedElement(String, Span),
+    UnexpectedAttribute(String, Span),
+    /// the variables couldn't be interpolated in the generated html
+    Template(TemplateError),
+}
+
+impl Error {
+    /// byte range of the element responsible for the error
+    pub fn span(&self) -> Span {
+        match self {
+            Error::Asset(_, _) => Span::default(),
+            Error::Include(_, _, span) => *span,
+            Error::InvalidChild(span) => *span,
+            Error::MissingAttribute(_, span) => *span,
+            Error::ParseError(_, span) => *span,
+            Error::UnexpectedText(span) => *span,
+            Error::UnexpectedElement(_, span) => *span,
+            Error::UnexpectedAttribute(_, span) => *span,
+            Error::Template(_) => Span::default(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Asset(url, reason) => write!(f, "unable to embed \"{}\": {}", url, reason),
+            Error::Include(path, reason, _) => {
+                write!(f, "unable to include \"{}\": {}", path, reason)
+            }
+            Error::InvalidChild(_) => write!(f, "invalid child"),
+            Error::MissingAttribute(name, _) => write!(f, "missing attribute \"{}\"", name),
+            Error::ParseError(message, _) => write!(f, "unable to parse: {}", message),
+            Error::UnexpectedText(_) => write!(f, "unexpected text"),
+            Error::UnexpectedElement(name, _) => write!(f, "unexpected element <{}>", name),
+            Error::UnexpectedAttribute(name, _) => {
+                write!(f, "unexpected attribute \"{}\"", name)
+            }
+            Error::Template(reason) => write!(f, "unable to render the template: {}", reason),
+        }
+    }
 }
+
+impl std::error::Error for Error {}
@@ -1,14 +1,52 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
 use crate::util::header::{DefaultAttributes, Header};
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 
-#[derive(Clone, Debug)]
-pub struct MJAttributes(DefaultAttributes);
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new();
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MJAttributes(Box<DefaultAttributes>);
 
 impl MJAttributes {
-    fn new() -> Self {
-        Self(DefaultAttributes::default())
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    /// default attributes of every element, like `<mj-all />`
+    pub fn all(mut self, attributes: Attributes) -> Self {
+        self.0.add_all_content(attributes.inner().iter());
+        self
+    }
+
+    /// default attributes of the elements with the given tag name
+    pub fn element(mut self, name: &str, attributes: Attributes) -> Self {
+        self.0.add_element_content(name, attributes.inner().iter());
+        self
+    }
+
+    /// attributes applied to the elements having the given name in their `mj-class` attribute
+    pub fn class(mut self, name: &str, attributes: Attributes) -> Self {
+        self.0.add_class_content(name, attributes.inner().iter());
+        self
+    }
+
+    /// default attributes of the elements with the given tag name, nested in an element
+    /// having the given class in its `mj-class` attribute
+    pub fn class_element(mut self, name: &str, tag: &str, attributes: Attributes) -> Self {
+        self.0
+            .add_class_element_content(name, tag, attributes.inner().iter());
+        self
     }
 
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
@@ -28,7 +66,7 @@ impl MJAttributes {
             },
             // TODO handle comments
             Element::Comment(_) => (),
-            Element::Text(_) => return Err(Error::UnexpectedText),
+            Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
         };
         Ok(())
     }
@@ -58,29 +96,81 @@ impl MJAttributes {
                         Some((key, value.as_str()))
                     }
                 }),
-            )
+            );
+            for child in node.children.iter() {
+                if let Element::Node(child) = child {
+                    let attributes = normalize(child);
+                    self.0.add_class_element_content(
+                        name,
+                        child.name.as_str(),
+                        attributes.inner().iter(),
+                    );
+                }
+            }
         }
     }
 
     fn parse_element<'a>(&mut self, node: &Node<'a>) {
-        self.0.add_element_content(
-            node.name.as_str(),
-            node.attributes
-                .iter()
-                .map(|(key, value)| (key.as_str(), value.as_str())),
-        );
+        let attributes = normalize(node);
+        self.0
+            .add_element_content(node.name.as_str(), attributes.inner().iter());
+    }
+}
+
+/// attributes of the node, normalized with the schema of the element they apply to
+fn normalize(node: &Node) -> Attributes {
+    let attributes = Attributes::from(node);
+    match crate::elements::schema::get(node.name.as_str()) {
+        Some(schema) => schema.normalize(attributes),
+        None => attributes,
     }
 }
 
 impl HeadComponent for MJAttributes {
     fn update_header(&self, header: &mut Header) {
-        header.set_default_attributes(self.0.clone());
+        header.add_default_attributes(&self.0);
+    }
+}
+
+impl Print for MJAttributes {
+    fn print(&self, printer: &mut Printer) {
+        let all = self.0.all();
+        let mut elements: Vec<(&String, &Attributes)> = self.0.elements().iter().collect();
+        elements.sort_by_key(|(name, _)| *name);
+        let mut classes: Vec<(&String, &Attributes)> = self.0.classes().iter().collect();
+        classes.sort_by_key(|(name, _)| *name);
+        let empty = all.is_empty() && elements.is_empty() && classes.is_empty();
+        printer.element("mj-attributes", &Attributes::default(), empty, |printer| {
+            if !all.is_empty() {
+                printer.empty("mj-all", all);
+            }
+            for (name, attributes) in elements {
+                printer.empty(name, attributes);
+            }
+            for (name, attributes) in classes {
+                let attributes = attributes.clone().add("name", name);
+                let mut nested: Vec<(&String, &Attributes)> = self
+                    .0
+                    .class_elements()
+                    .get(name)
+                    .map(|items| items.iter().collect())
+                    .unwrap_or_default();
+                nested.sort_by_key(|(tag, _)| *tag);
+                printer.element("mj-class", &attributes, nested.is_empty(), |printer| {
+                    for (tag, attributes) in nested {
+                        printer.empty(tag, attributes);
+                    }
+                });
+            }
+        });
     }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use crate::elements::prelude::Print;
     use crate::tests::compare_render;
+    use crate::{parse, Options};
 
     #[test]
     fn base() {
@@ -89,4 +179,35 @@ pub mod tests {
             include_str!("../../../test/mj-attributes.html"),
         );
     }
+
+    #[test]
+    fn multiple_blocks() {
+        compare_render(
+            include_str!("../../../test/mj-attributes-multiple.mjml"),
+            include_str!("../../../test/mj-attributes-multiple.html"),
+        );
+    }
+
+    #[test]
+    fn class_css_classes() {
+        compare_render(
+            include_str!("../../../test/mj-attributes-class-css-class.mjml"),
+            include_str!("../../../test/mj-attributes-class-css-class.html"),
+        );
+    }
+
+    #[test]
+    fn nested_class_elements() {
+        let template = include_str!("../../../test/mj-attributes-nested-class.mjml");
+        compare_render(
+            template,
+            include_str!("../../../test/mj-attributes-nested-class.html"),
+        );
+        let printed = parse(template, Options::default()).unwrap().to_mjml();
+        assert!(printed.contains("<mj-class background-color=\"#eeeeee\" name=\"blue\">"));
+        assert_eq!(
+            parse(&printed, Options::default()).unwrap().to_mjml(),
+            printed
+        );
+    }
 }
@@ -1,15 +1,33 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::Node;
+use crate::util::attributes::Attributes;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
 use crate::util::size::Size;
 
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .add("width", AttributeType::Size);
+}
+
 #[derive(Clone, Debug)]
 pub struct MJBreakpoint {
     value: Option<Size>,
 }
 
 impl MJBreakpoint {
+    pub fn new(value: Size) -> Self {
+        Self { value: Some(value) }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
         let value = node
             .attributes
@@ -28,6 +46,14 @@ impl HeadComponent for MJBreakpoint {
     }
 }
 
+impl Print for MJBreakpoint {
+    fn print(&self, printer: &mut Printer) {
+        let attributes =
+            Attributes::default().maybe_add("width", self.value.as_ref().map(Size::to_string));
+        printer.empty("mj-breakpoint", &attributes);
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::{compare_render, compare_render_with_options};
@@ -60,4 +86,13 @@ pub mod tests {
             opts,
         );
     }
+
+    #[test]
+    fn get_breakpoint() {
+        let template = include_str!("../../../test/mj-breakpoint.mjml");
+        let element = crate::parse(template, Options::default()).unwrap();
+        assert_eq!(element.get_breakpoint().to_string(), "320px");
+        let element = crate::parse("<mjml></mjml>", Options::default()).unwrap();
+        assert_eq!(element.get_breakpoint().to_string(), "480px");
+    }
 }
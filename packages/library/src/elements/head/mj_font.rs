@@ -1,7 +1,17 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::Node;
+use crate::util::attributes::Attributes;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .add("href", AttributeType::Url)
+        .add("name", AttributeType::String);
+}
 
 #[derive(Clone, Debug)]
 pub struct MJFont {
@@ -10,11 +20,23 @@ pub struct MJFont {
 }
 
 impl MJFont {
+    pub fn new<N: ToString, H: ToString>(name: N, href: H) -> Self {
+        Self {
+            name: name.to_string(),
+            href: href.to_string(),
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
         let mut name: Option<String> = None;
         let mut href: Option<String> = None;
-        for (key, value) in node.attributes.iter() {
-            let key = key.as_str();
+        for (key_span, value) in node.attributes.iter() {
+            let key = key_span.as_str();
             match key {
                 "name" => {
                     name = Some(value.as_str().into());
@@ -22,14 +44,14 @@ impl MJFont {
                 "href" => {
                     href = Some(value.as_str().into());
                 }
-                _ => return Err(Error::UnexpectedAttribute(key.into())),
+                _ => return Err(Error::UnexpectedAttribute(key.into(), key_span.into())),
             };
         }
         if name.is_none() {
-            return Err(Error::MissingAttribute("name".into()));
+            return Err(Error::MissingAttribute("name".into(), node.span.into()));
         }
         if href.is_none() {
-            return Err(Error::MissingAttribute("href".into()));
+            return Err(Error::MissingAttribute("href".into(), node.span.into()));
         }
         let name = name.unwrap();
         let href = href.unwrap();
@@ -43,6 +65,15 @@ impl HeadComponent for MJFont {
     }
 }
 
+impl Print for MJFont {
+    fn print(&self, printer: &mut Printer) {
+        let attributes = Attributes::default()
+            .add("name", self.name.as_str())
+            .add("href", self.href.as_str());
+        printer.empty("mj-font", &attributes);
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
@@ -3,13 +3,29 @@ use crate::elements::head::prelude::HeadComponent;
 use crate::elements::prelude::*;
 use crate::elements::Error;
 use crate::parser::Node;
+use crate::util::attributes::Attributes;
 use crate::util::context::Context;
 use crate::util::fonts::{url_to_import, url_to_link};
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+use crate::util::size::Size;
 use crate::util::tag::Tag;
 use crate::Options;
 use log::debug;
-use std::collections::HashMap;
+use std::fmt;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new().children(&[
+        "mj-attributes",
+        "mj-breakpoint",
+        "mj-font",
+        "mj-html-attributes",
+        "mj-preview",
+        "mj-style",
+        "mj-title",
+    ]);
+}
 
 const STYLE_BASE: &str = r#"
 <style type="text/css">
@@ -56,44 +72,71 @@ p {
 "#;
 
 #[derive(Debug, Clone)]
-pub struct MJHead<'a> {
-    attributes: HashMap<&'a str, &'a str>,
+pub struct MJHead {
+    attributes: Attributes,
     context: Option<Context>,
     children: Vec<HeadElement>,
     header: Header,
 }
 
-impl<'a> MJHead<'a> {
-    pub fn empty(opts: Options) -> MJHead<'a> {
+impl Default for MJHead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MJHead {
+    pub fn empty(opts: Options) -> MJHead {
         debug!("create empty");
         MJHead {
-            attributes: HashMap::new(),
+            attributes: Attributes::default(),
             context: None,
             children: vec![],
             header: Header::from(opts),
         }
     }
 
-    pub fn parse(node: &Node<'a>, opts: Options) -> Result<MJHead<'a>, Error> {
-        let children = HeadElement::parse_all(&node.children)?;
+    pub fn parse(node: &Node, opts: Options) -> Result<MJHead, Error> {
         let mut header = Header::from(opts);
+        let children = HeadElement::parse_all(&node.children, &header)?;
         for child in children.iter() {
             child.update_header(&mut header);
         }
         Ok(MJHead {
-            attributes: node
-                .attributes
-                .iter()
-                .fold(HashMap::new(), |mut res, (key, value)| {
-                    res.insert(key.as_str(), value.as_str());
-                    res
-                }),
+            attributes: Attributes::from(node),
             context: None,
             children,
             header,
         })
     }
 
+    pub fn new() -> MJHead {
+        MJHead::empty(Options::default())
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn add_child<C: Into<HeadElement>>(mut self, child: C) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// build the header again with the given options, used when the element hasn't been parsed
+    pub fn set_options(&mut self, opts: Options) {
+        let mut header = Header::from(opts);
+        for child in self.children.iter() {
+            child.update_header(&mut header);
+        }
+        self.header = header;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.children.is_empty()
+    }
+
     pub fn get_header(&self) -> &Header {
         &self.header
     }
@@ -110,47 +153,53 @@ impl<'a> MJHead<'a> {
     }
 
     pub fn get_preview(&self) -> String {
-        for child in self.children.iter() {
-            if let HeadElement::MJPreview(element) = child {
-                return element.content.clone();
-            }
+        match self.header.preview() {
+            Some(value) => value.clone(),
+            None => "".into(),
         }
-        String::new()
     }
 
-    fn get_media_queries(&self) -> String {
-        if !self.header.has_media_queries() {
+    fn get_media_queries(&self, header: &Header) -> String {
+        if !header.has_media_queries() {
             return "".into();
         }
         let mut res = vec![];
         res.push(format!(
             "@media only screen and (min-width:{}) {{ ",
-            self.header.breakpoint().to_string()
+            header.breakpoint().to_string()
         ));
-        let mut classnames: Vec<&String> = self.header.get_media_queries().keys().collect();
-        classnames.sort();
-        for classname in classnames.iter() {
-            let size = self
-                .header
-                .get_media_queries()
-                .get(&(*classname).clone())
-                .unwrap();
-            res.push(format!(
-                ".{} {{ width:{} !important; max-width: {}; }}",
-                classname,
-                size.to_string(),
-                size.to_string()
-            ));
-        }
+        // sorted by class name, like the output of mjml
+        let mut media_queries: Vec<&(String, Size)> = header.get_media_queries().iter().collect();
+        media_queries.sort_by(|a, b| a.0.cmp(&b.0));
+        let queries: Vec<String> = media_queries
+            .iter()
+            .map(|(classname, size)| {
+                format!(
+                    ".{} {{ width:{} !important; max-width: {}; }}",
+                    classname,
+                    size.to_string(),
+                    size.to_string()
+                )
+            })
+            .collect();
+        res.extend(queries.iter().cloned());
         res.push("}".into());
-        Tag::new("style")
-            .set_attribute("type", "text/css")
-            .render(res.join("\n"))
+        let tag = Tag::new("style").set_attribute("type", "text/css");
+        let mut result = tag.render(res.join("\n"));
+        // outlook web app ignores the media queries, the desktop layout is forced with its attribute
+        if header.force_owa_desktop() {
+            let owa: Vec<String> = queries
+                .iter()
+                .map(|query| format!("[owa] {}", query))
+                .collect();
+            result.push_str(&tag.render(owa.join("\n")));
+        }
+        result
     }
 
-    fn get_font_families(&self) -> String {
+    fn get_font_families(&self, header: &Header) -> String {
         let tag = Tag::new("style").set_attribute("type", "text/css");
-        let font_urls = self.header.get_used_font_families();
+        let font_urls = header.get_used_font_families();
         if font_urls.is_empty() {
             return "".into();
         }
@@ -168,8 +217,8 @@ impl<'a> MJHead<'a> {
         res.join("")
     }
 
-    fn get_styles(&self) -> String {
-        let styles = self.header.get_styles();
+    fn get_styles(&self, header: &Header) -> String {
+        let styles = header.get_styles();
         if styles.is_empty() {
             "".into()
         } else {
@@ -180,7 +229,26 @@ impl<'a> MJHead<'a> {
     }
 }
 
-impl<'a> Component for MJHead<'a> {
+impl Print for MJHead {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-head",
+            &self.attributes,
+            self.children.is_empty(),
+            |printer| {
+                HeadElement::print_all(&self.children, printer);
+            },
+        );
+    }
+}
+
+impl fmt::Display for MJHead {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_mjml())
+    }
+}
+
+impl Component for MJHead {
     fn context(&self) -> Option<&Context> {
         self.context.as_ref()
     }
@@ -189,7 +257,7 @@ impl<'a> Component for MJHead<'a> {
         self.context = Some(ctx);
     }
 
-    fn render(&self, _header: &Header) -> Result<String, Error> {
+    fn render(&self, header: &Header) -> Result<String, Error> {
         debug!("render");
         let head = Tag::new("head");
         let mut res: Vec<String> = vec![];
@@ -216,9 +284,9 @@ impl<'a> Component for MJHead<'a> {
                 .open(),
         );
         res.push(STYLE_BASE.into());
-        res.push(self.get_font_families());
-        res.push(self.get_media_queries());
-        res.push(self.get_styles());
+        res.push(self.get_font_families(header));
+        res.push(self.get_media_queries(header));
+        res.push(self.get_styles(header));
         res.push(head.close());
         Ok(res.join(""))
     }
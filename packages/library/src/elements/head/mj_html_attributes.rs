@@ -0,0 +1,245 @@
+use super::prelude::*;
+use crate::elements::error::Error;
+use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
+use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new().children(&["mj-selector"]);
+    static ref SELECTOR_SCHEMA: Schema = Schema::new()
+        .children(&["mj-html-attribute"])
+        .add("path", AttributeType::String);
+    static ref ATTRIBUTE_SCHEMA: Schema = Schema::new().add("name", AttributeType::String);
+}
+
+/// attribute set by `<mj-html-attribute>` on the elements matching its selector
+#[derive(Clone, Debug)]
+pub struct MJHtmlAttribute {
+    name: String,
+    value: String,
+}
+
+impl MJHtmlAttribute {
+    pub fn new<N: ToString, V: ToString>(name: N, value: V) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &ATTRIBUTE_SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
+        let mut name = None;
+        for (key, value) in node.attributes.iter() {
+            match key.as_str() {
+                "name" => name = Some(value.as_str().to_string()),
+                other => return Err(Error::UnexpectedAttribute(other.into(), key.into())),
+            };
+        }
+        let name = name.ok_or_else(|| Error::MissingAttribute("name".into(), node.span.into()))?;
+        let mut value = String::new();
+        for child in node.children.iter() {
+            match child {
+                Element::Text(text) => value.push_str(text.as_str()),
+                Element::Comment(_) => (),
+                other => return Err(Error::InvalidChild(other.span())),
+            };
+        }
+        Ok(Self::new(name, value.trim()))
+    }
+}
+
+/// css selector of the elements of the generated html receiving the attributes
+#[derive(Clone, Debug)]
+pub struct MJSelector {
+    path: String,
+    attributes: Vec<MJHtmlAttribute>,
+}
+
+impl MJSelector {
+    pub fn new<P: ToString>(path: P) -> Self {
+        Self {
+            path: path.to_string(),
+            attributes: vec![],
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SELECTOR_SCHEMA
+    }
+
+    pub fn attribute<N: ToString, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.attributes.push(MJHtmlAttribute::new(name, value));
+        self
+    }
+
+    pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
+        let mut path = None;
+        for (key, value) in node.attributes.iter() {
+            match key.as_str() {
+                "path" => path = Some(value.as_str()),
+                other => return Err(Error::UnexpectedAttribute(other.into(), key.into())),
+            };
+        }
+        let path = path.ok_or_else(|| Error::MissingAttribute("path".into(), node.span.into()))?;
+        let mut element = Self::new(path);
+        for child in node.children.iter() {
+            match child {
+                Element::Node(child) if child.name.as_str() == "mj-html-attribute" => {
+                    element.attributes.push(MJHtmlAttribute::parse(child)?);
+                }
+                Element::Node(child) => {
+                    return Err(Error::UnexpectedElement(
+                        child.name.as_str().into(),
+                        child.span.into(),
+                    ))
+                }
+                Element::Comment(_) => (),
+                Element::Text(text) if text.as_str().trim().is_empty() => (),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
+            };
+        }
+        Ok(element)
+    }
+}
+
+/// attributes added to the generated html, like `data-*` attributes used for tracking
+#[derive(Clone, Debug, Default)]
+pub struct MJHtmlAttributes(Vec<MJSelector>);
+
+impl MJHtmlAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn selector(mut self, selector: MJSelector) -> Self {
+        self.0.push(selector);
+        self
+    }
+
+    pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
+        let mut element = Self::new();
+        for child in node.children.iter() {
+            match child {
+                Element::Node(child) if child.name.as_str() == "mj-selector" => {
+                    element.0.push(MJSelector::parse(child)?);
+                }
+                Element::Node(child) => {
+                    return Err(Error::UnexpectedElement(
+                        child.name.as_str().into(),
+                        child.span.into(),
+                    ))
+                }
+                Element::Comment(_) => (),
+                Element::Text(text) if text.as_str().trim().is_empty() => (),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
+            };
+        }
+        Ok(element)
+    }
+}
+
+impl HeadComponent for MJHtmlAttributes {
+    fn update_header(&self, header: &mut Header) {
+        for selector in self.0.iter() {
+            header.add_html_attributes(
+                selector.path.clone(),
+                selector
+                    .attributes
+                    .iter()
+                    .map(|item| (item.name.clone(), item.value.clone())),
+            );
+        }
+    }
+}
+
+impl Print for MJHtmlAttributes {
+    fn print(&self, printer: &mut Printer) {
+        printer.element(
+            "mj-html-attributes",
+            &Attributes::default(),
+            self.0.is_empty(),
+            |printer| {
+                for selector in self.0.iter() {
+                    printer.element(
+                        "mj-selector",
+                        &Attributes::default().add("path", &selector.path),
+                        selector.attributes.is_empty(),
+                        |printer| {
+                            for item in selector.attributes.iter() {
+                                printer.element_with_content(
+                                    "mj-html-attribute",
+                                    &Attributes::default().add("name", &item.name),
+                                    &item.value,
+                                );
+                            }
+                        },
+                    );
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::elements::prelude::Print;
+    use crate::{parse, to_html, Options};
+
+    const TEMPLATE: &str = r#"<mjml>
+  <mj-head>
+    <mj-html-attributes>
+      <mj-selector path=".custom div">
+        <mj-html-attribute name="data-id">42</mj-html-attribute>
+      </mj-selector>
+      <mj-selector path=".custom td, .other">
+        <mj-html-attribute name="data-name">"quoted"</mj-html-attribute>
+      </mj-selector>
+    </mj-html-attributes>
+  </mj-head>
+  <mj-body>
+    <mj-section css-class="custom"><mj-column><mj-text>Hello</mj-text></mj-column></mj-section>
+  </mj-body>
+</mjml>"#;
+
+    #[test]
+    fn base() {
+        let result = to_html(TEMPLATE, Options::default()).unwrap();
+        assert_eq!(result.matches(" data-id=\"42\">").count(), 2);
+        assert!(!result.contains("class=\"custom\" data-id"));
+        assert_eq!(
+            result.matches(" data-name=\"&quot;quoted&quot;\">").count(),
+            2
+        );
+        assert!(!result.contains("mj-html-attribute"));
+    }
+
+    #[test]
+    fn print() {
+        let element = parse(TEMPLATE, Options::default()).unwrap();
+        let printed = element.to_mjml();
+        assert!(printed.contains("<mj-selector path=\".custom div\">"));
+        assert_eq!(
+            parse(&printed, Options::default()).unwrap().to_mjml(),
+            printed
+        );
+    }
+
+    #[test]
+    fn missing_path() {
+        let template = "<mjml><mj-head><mj-html-attributes><mj-selector /></mj-html-attributes></mj-head></mjml>";
+        assert!(to_html(template, Options::default()).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use super::prelude::*;
+use crate::elements::include::Include;
+use crate::util::header::Header;
+use crate::util::printer::Printer;
+
+/// `<mj-include />` of the head, kept to print the template back
+///
+/// The included elements follow it in the children of the head.
+#[derive(Clone, Debug)]
+pub struct MJInclude {
+    include: Include,
+    expanded: usize,
+}
+
+impl MJInclude {
+    pub fn new(include: Include, expanded: usize) -> Self {
+        Self { include, expanded }
+    }
+
+    /// number of elements following the include in the head that come from it
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+}
+
+impl HeadComponent for MJInclude {
+    fn update_header(&self, _header: &mut Header) {}
+}
+
+impl Print for MJInclude {
+    fn print(&self, printer: &mut Printer) {
+        self.include.print(printer);
+    }
+}
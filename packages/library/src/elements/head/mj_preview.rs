@@ -1,7 +1,14 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new();
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct MJPreview {
@@ -9,13 +16,24 @@ pub struct MJPreview {
 }
 
 impl MJPreview {
+    pub fn new<C: ToString>(content: C) -> Self {
+        Self {
+            content: content.to_string(),
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
         match node.children.first() {
             Some(element) => match element {
                 Element::Text(value) => Ok(Self {
                     content: value.as_str().into(),
                 }),
-                _ => Err(Error::InvalidChild),
+                other => Err(Error::InvalidChild(other.span())),
             },
             None => Ok(Self::default()),
         }
@@ -28,6 +46,12 @@ impl HeadComponent for MJPreview {
     }
 }
 
+impl Print for MJPreview {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content("mj-preview", &Attributes::default(), &self.content);
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::{compare_preview, compare_render};
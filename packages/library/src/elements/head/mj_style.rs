@@ -1,7 +1,15 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
+use crate::util::css::Stylesheet;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new().add("inline", AttributeType::Enum(&["inline"]));
+}
 
 #[derive(Clone, Debug)]
 pub struct MJStyle {
@@ -10,21 +18,30 @@ pub struct MJStyle {
 }
 
 impl MJStyle {
+    pub fn new(content: String, inline: bool) -> Self {
+        Self { content, inline }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
         let mut content = String::new();
         let mut inline = false;
-        for (key, _value) in node.attributes.iter() {
+        for (key, value) in node.attributes.iter() {
             match key.as_str() {
                 "inline" => {
-                    inline = true;
+                    inline = value.as_str() == "inline";
                 }
-                name => return Err(Error::UnexpectedAttribute(name.into())),
+                name => return Err(Error::UnexpectedAttribute(name.into(), key.into())),
             };
         }
         for child in node.children.iter() {
             match child {
                 Element::Text(value) => content.push_str(value.as_str()),
-                _ => return Err(Error::InvalidChild),
+                other => return Err(Error::InvalidChild(other.span())),
             };
         }
         Ok(Self { inline, content })
@@ -34,13 +51,25 @@ impl MJStyle {
 impl HeadComponent for MJStyle {
     fn update_header(&self, header: &mut Header) {
         if self.inline {
-            // TODO
+            let stylesheet = Stylesheet::parse(self.content.as_str());
+            header.add_inline_rules(stylesheet.rules);
+            if !stylesheet.remaining.is_empty() {
+                header.add_style(stylesheet.remaining.join("\n"));
+            }
         } else {
             header.add_style(self.content.clone());
         }
     }
 }
 
+impl Print for MJStyle {
+    fn print(&self, printer: &mut Printer) {
+        let attributes =
+            Attributes::default().maybe_add("inline", Some("inline").filter(|_| self.inline));
+        printer.element_with_content("mj-style", &attributes, &self.content);
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::compare_render;
@@ -52,4 +81,20 @@ pub mod tests {
             include_str!("../../../test/mj-style.html"),
         );
     }
+
+    #[test]
+    fn inline() {
+        compare_render(
+            include_str!("../../../test/mj-style-inline.mjml"),
+            include_str!("../../../test/mj-style-inline.html"),
+        );
+    }
+
+    #[test]
+    fn inline_only_when_asked() {
+        let template = r#"<mjml><mj-head><mj-style inline="false">.red { color: red; }</mj-style></mj-head><mj-body><mj-section><mj-column><mj-text css-class="red">Hi</mj-text></mj-column></mj-section></mj-body></mjml>"#;
+        let html = crate::to_html(template, crate::Options::default()).unwrap();
+        assert!(html.contains(".red { color: red; }"));
+        assert!(!html.contains("color:red"));
+    }
 }
@@ -1,7 +1,14 @@
 use super::prelude::*;
 use crate::elements::error::Error;
 use crate::parser::{Element, Node};
+use crate::util::attributes::Attributes;
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new();
+}
 
 #[derive(Clone, Debug)]
 pub struct MJTitle {
@@ -9,12 +16,23 @@ pub struct MJTitle {
 }
 
 impl MJTitle {
+    pub fn new<C: ToString>(content: C) -> Self {
+        Self {
+            content: content.to_string(),
+        }
+    }
+
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
         let mut content = String::new();
         for child in node.children.iter() {
             match child {
                 Element::Text(value) => content.push_str(value.as_str()),
-                _ => return Err(Error::InvalidChild),
+                other => return Err(Error::InvalidChild(other.span())),
             }
         }
         Ok(Self { content })
@@ -31,6 +49,12 @@ impl HeadComponent for MJTitle {
     }
 }
 
+impl Print for MJTitle {
+    fn print(&self, printer: &mut Printer) {
+        printer.element_with_content("mj-title", &Attributes::default(), &self.content);
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::tests::{compare_render, compare_title};
@@ -1,11 +1,17 @@
 use crate::elements::error::Error;
+use crate::elements::include::{Include, IncludeType};
+use crate::elements::prelude::Print;
 use crate::parser::{Element, Node};
 use crate::util::header::Header;
+use crate::util::printer::Printer;
+use std::fmt;
 
 pub mod mj_attributes;
 pub mod mj_breakpoint;
 pub mod mj_font;
 pub mod mj_head;
+pub mod mj_html_attributes;
+pub mod mj_include;
 pub mod mj_preview;
 pub mod mj_style;
 pub mod mj_title;
@@ -16,9 +22,12 @@ pub enum HeadElement {
     MJAttributes(mj_attributes::MJAttributes),
     MJBreakpoint(mj_breakpoint::MJBreakpoint),
     MJFont(mj_font::MJFont),
+    MJHtmlAttributes(mj_html_attributes::MJHtmlAttributes),
+    MJInclude(mj_include::MJInclude),
     MJPreview(mj_preview::MJPreview),
     MJStyle(mj_style::MJStyle),
     MJTitle(mj_title::MJTitle),
+    Comment(String),
 }
 
 macro_rules! apply_fn {
@@ -27,38 +36,109 @@ macro_rules! apply_fn {
             HeadElement::MJAttributes(item) => item.$func($($args)*),
             HeadElement::MJBreakpoint(item) => item.$func($($args)*),
             HeadElement::MJFont(item) => item.$func($($args)*),
+            HeadElement::MJHtmlAttributes(item) => item.$func($($args)*),
+            HeadElement::MJInclude(item) => item.$func($($args)*),
             HeadElement::MJPreview(item) => item.$func($($args)*),
             HeadElement::MJStyle(item) => item.$func($($args)*),
             HeadElement::MJTitle(item) => item.$func($($args)*),
+            HeadElement::Comment(_) => (),
         }
     };
 }
 
+macro_rules! from_element {
+    ($variant:ident, $kind:ty) => {
+        impl From<$kind> for HeadElement {
+            fn from(value: $kind) -> Self {
+                HeadElement::$variant(value)
+            }
+        }
+    };
+}
+
+from_element!(MJAttributes, mj_attributes::MJAttributes);
+from_element!(MJBreakpoint, mj_breakpoint::MJBreakpoint);
+from_element!(MJFont, mj_font::MJFont);
+from_element!(MJHtmlAttributes, mj_html_attributes::MJHtmlAttributes);
+from_element!(MJPreview, mj_preview::MJPreview);
+from_element!(MJStyle, mj_style::MJStyle);
+from_element!(MJTitle, mj_title::MJTitle);
+
 impl HeadElement {
-    pub fn parse_all<'a>(elements: &[Element<'a>]) -> Result<Vec<HeadElement>, Error> {
+    pub fn parse_all<'a>(
+        elements: &[Element<'a>],
+        header: &Header,
+    ) -> Result<Vec<HeadElement>, Error> {
         let mut res = vec![];
         for elt in elements {
             match elt {
+                Element::Node(node) if node.name.as_str() == "mj-include" => {
+                    res.extend(HeadElement::parse_include(node, header)?);
+                }
                 Element::Node(node) => {
                     res.push(HeadElement::parse(&node)?);
                 }
-                Element::Comment(_) => (),
-                _ => return Err(Error::UnexpectedText),
+                Element::Comment(value) => res.push(HeadElement::Comment(value.as_str().into())),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
             }
         }
         Ok(res)
     }
 
+    fn parse_include<'a>(node: &Node<'a>, header: &Header) -> Result<Vec<HeadElement>, Error> {
+        let include = Include::parse(node)?;
+        let template = include.load(header)?;
+        let children = match include.kind {
+            IncludeType::Css => vec![HeadElement::MJStyle(mj_style::MJStyle::new(
+                template.content,
+                include.css_inline,
+            ))],
+            IncludeType::Html => {
+                return Err(Error::ParseError(
+                    "html includes are only allowed in mj-body".into(),
+                    node.span.into(),
+                ))
+            }
+            IncludeType::Mjml => template.parse_mjml("mj-head", |children| {
+                HeadElement::parse_all(children, &template.header)
+            })?,
+        };
+        let mut res = vec![HeadElement::MJInclude(mj_include::MJInclude::new(
+            include,
+            children.len(),
+        ))];
+        res.extend(children);
+        Ok(res)
+    }
+
+    /// print a list of elements, the `mj-include` being printed instead of their content
+    pub fn print_all(elements: &[HeadElement], printer: &mut Printer) {
+        let mut included = 0;
+        for element in elements.iter() {
+            if included > 0 {
+                included -= 1;
+                continue;
+            }
+            if let HeadElement::MJInclude(include) = element {
+                included = include.expanded();
+            }
+            element.print(printer);
+        }
+    }
+
     pub fn parse<'a>(node: &Node<'a>) -> Result<HeadElement, Error> {
         let tag_name = node.name.as_str();
         let res = match tag_name {
             "mj-attributes" => HeadElement::MJAttributes(mj_attributes::MJAttributes::parse(node)?),
             "mj-breakpoint" => HeadElement::MJBreakpoint(mj_breakpoint::MJBreakpoint::parse(node)?),
             "mj-font" => HeadElement::MJFont(mj_font::MJFont::parse(node)?),
+            "mj-html-attributes" => {
+                HeadElement::MJHtmlAttributes(mj_html_attributes::MJHtmlAttributes::parse(node)?)
+            }
             "mj-preview" => HeadElement::MJPreview(mj_preview::MJPreview::parse(node)?),
             "mj-style" => HeadElement::MJStyle(mj_style::MJStyle::parse(node)?),
             "mj-title" => HeadElement::MJTitle(mj_title::MJTitle::parse(node)?),
-            _ => return Err(Error::UnexpectedElement(tag_name.into())),
+            _ => return Err(Error::UnexpectedElement(tag_name.into(), node.span.into())),
         };
         Ok(res)
     }
@@ -70,9 +150,24 @@ impl prelude::HeadComponent for HeadElement {
     }
 }
 
+impl Print for HeadElement {
+    fn print(&self, printer: &mut Printer) {
+        match self {
+            HeadElement::Comment(value) => printer.comment(value),
+            other => apply_fn!(other, print(printer)),
+        }
+    }
+}
+
+impl fmt::Display for HeadElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_mjml())
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use crate::{to_html, Options};
+    use crate::{to_html, to_mjml, Options};
 
     #[test]
     fn unknown_tag() {
@@ -82,4 +177,10 @@ pub mod tests {
         let error = result.unwrap_err();
         assert_eq!(error.is_mjml_error(), true);
     }
+
+    #[test]
+    fn print_comments() {
+        let content = include_str!("../../../test/mj-head-comment.mjml");
+        assert_eq!(to_mjml(content, Options::default()).unwrap(), content);
+    }
 }
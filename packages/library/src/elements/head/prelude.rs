@@ -1,5 +1,6 @@
+pub use crate::elements::prelude::Print;
 use crate::util::header::Header;
 
-pub trait HeadComponent {
+pub trait HeadComponent: Print {
     fn update_header(&self, header: &mut Header);
 }
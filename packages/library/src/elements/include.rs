@@ -0,0 +1,154 @@
+use super::error::{Error, IncludeError};
+use super::prelude::Print;
+use crate::parser::{self, Element, Node, Span};
+use crate::util::attributes::Attributes;
+use crate::util::header::Header;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&[])
+        .add("css-inline", AttributeType::Enum(&["inline"]))
+        .add("path", AttributeType::String)
+        .add("type", AttributeType::Enum(&["css", "html", "mjml"]));
+}
+
+/// kind of content loaded by an `mj-include`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IncludeType {
+    Mjml,
+    Css,
+    Html,
+}
+
+/// representation of an `<mj-include path="..." />` element
+#[derive(Clone, Debug)]
+pub struct Include {
+    pub path: String,
+    pub kind: IncludeType,
+    pub css_inline: bool,
+    span: Span,
+}
+
+/// content of an included template, ready to be parsed
+pub struct IncludedTemplate {
+    pub path: String,
+    pub content: String,
+    pub header: Header,
+    span: Span,
+}
+
+impl Include {
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse<'a>(node: &Node<'a>) -> Result<Self, Error> {
+        let mut path = None;
+        let mut kind = IncludeType::Mjml;
+        let mut css_inline = false;
+        for (key, value) in node.attributes.iter() {
+            match key.as_str() {
+                "path" => path = Some(value.as_str().to_string()),
+                "type" => {
+                    kind = match value.as_str() {
+                        "mjml" => IncludeType::Mjml,
+                        "css" => IncludeType::Css,
+                        "html" => IncludeType::Html,
+                        other => {
+                            return Err(Error::ParseError(
+                                format!("unknown include type \"{}\"", other),
+                                value.into(),
+                            ))
+                        }
+                    }
+                }
+                "css-inline" => css_inline = value.as_str() == "inline",
+                _ => (),
+            };
+        }
+        match path {
+            Some(path) => Ok(Self {
+                path,
+                kind,
+                css_inline,
+                span: node.span.into(),
+            }),
+            None => Err(Error::MissingAttribute("path".into(), node.span.into())),
+        }
+    }
+
+    pub fn load(&self, header: &Header) -> Result<IncludedTemplate, Error> {
+        let path = header.include_path(self.path.as_str());
+        match header.load_include(path.as_str()) {
+            Ok((content, header)) => Ok(IncludedTemplate {
+                path,
+                content,
+                header,
+                span: self.span,
+            }),
+            Err(reason) => Err(Error::Include(path, reason, self.span)),
+        }
+    }
+}
+
+impl Print for Include {
+    fn print(&self, printer: &mut Printer) {
+        let kind = match self.kind {
+            IncludeType::Mjml => None,
+            IncludeType::Css => Some("css"),
+            IncludeType::Html => Some("html"),
+        };
+        let attributes = Attributes::default()
+            .add("path", self.path.as_str())
+            .maybe_add("type", kind)
+            .maybe_add("css-inline", Some("inline").filter(|_| self.css_inline));
+        printer.empty("mj-include", &attributes);
+    }
+}
+
+impl IncludedTemplate {
+    pub(crate) fn error(&self, reason: IncludeError) -> Error {
+        Error::Include(self.path.clone(), reason, self.span)
+    }
+
+    /// whether the included template is a complete `mjml` document
+    pub fn is_document(&self) -> bool {
+        self.content.trim_start().starts_with("<mjml")
+    }
+
+    /// parse the included template, being either a complete `mjml` document or a list of
+    /// elements. For a complete document, only the children of `section` are kept.
+    pub fn parse_mjml<T, F>(&self, section: &str, parse: F) -> Result<T, Error>
+    where
+        F: FnOnce(&[Element]) -> Result<T, Error>,
+    {
+        let content = self.content.as_str();
+        let parse_error = |err| {
+            self.error(IncludeError::Template(Box::new(crate::Error::from_parser(
+                content, err,
+            ))))
+        };
+        let result = if self.is_document() {
+            let root = parser::parse(content).map_err(parse_error)?;
+            let children = root
+                .children
+                .iter()
+                .filter_map(|item| item.as_node())
+                .find(|item| item.name.as_str() == section)
+                .map(|item| item.children.as_slice())
+                .unwrap_or(&[]);
+            parse(children)
+        } else {
+            let children = parser::parse_fragment(content).map_err(parse_error)?;
+            parse(&children)
+        };
+        result.map_err(|err| {
+            self.error(IncludeError::Template(Box::new(crate::Error::from_mjml(
+                content, err,
+            ))))
+        })
+    }
+}
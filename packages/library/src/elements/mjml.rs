@@ -1,51 +1,131 @@
 use super::body::mj_body::MJBody;
+use super::body::mj_include::MJInclude;
+use super::body::prelude::BodyComponent;
 use super::head::mj_head::MJHead;
 use super::prelude::*;
 use super::Error;
 use crate::parser::{Element, Node};
+use crate::util::asset::{self, AssetEmbedding};
+use crate::util::attributes::Attributes;
+use crate::util::condition::merge_conditionals;
 use crate::util::context::Context;
-use crate::Options;
+use crate::util::header::Header;
+use crate::util::inliner;
+use crate::util::mime::InlineImage;
+use crate::util::minifier;
+use crate::util::printer::Printer;
+use crate::util::schema::*;
+use crate::util::size::Size;
+use crate::util::template::{has_placeholder, Template, TemplateError, Variables};
+use crate::util::text::{TextOptions, TextWriter};
+use crate::{Options, RenderOptions};
 use log::debug;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::sync::OnceLock;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::new()
+        .children(&["mj-body", "mj-head"])
+        .add("dir", DIRECTION)
+        .add("lang", AttributeType::String)
+        .add("owa", AttributeType::Enum(&["desktop"]));
+}
 
 #[derive(Clone, Debug)]
-pub struct MJMLElement<'a> {
+pub struct MJMLElement {
+    attributes: Attributes,
     context: Option<Context>,
-    head: MJHead<'a>,
+    head: MJHead,
     body: MJBody,
+    /// rather some attribute values contain placeholders, replaced before rendering
+    placeholders: bool,
+    /// template rendered with the default options, kept for the next renderings
+    template: OnceLock<(Template, Vec<InlineImage>)>,
 }
 
-impl<'a> MJMLElement<'a> {
-    pub fn parse(node: &Node<'a>, opts: Options) -> Result<MJMLElement<'a>, Error> {
-        let mut head: Option<&Node<'a>> = None;
-        let mut body: Option<&Node<'a>> = None;
+impl MJMLElement {
+    /// allowed children and attributes
+    pub fn schema() -> &'static Schema {
+        &SCHEMA
+    }
+
+    pub fn parse(node: &Node, opts: Options) -> Result<MJMLElement, Error> {
+        let mut head: Option<&Node> = None;
+        let mut body: Option<&Node> = None;
         for item in node.children.iter() {
             match item {
                 Element::Node(node) => match node.name.as_str() {
                     "mj-head" => head = Some(node),
                     "mj-body" => body = Some(node),
-                    name => return Err(Error::UnexpectedElement(name.into())),
+                    name => return Err(Error::UnexpectedElement(name.into(), node.span.into())),
                 },
                 // TODO handle comments in <mjml>
                 Element::Comment(_) => (),
-                Element::Text(_) => return Err(Error::UnexpectedText),
+                Element::Text(text) => return Err(Error::UnexpectedText(text.into())),
             };
         }
+        let attributes = SCHEMA.normalize(Attributes::from(node));
         let mut head = match head {
             Some(node) => MJHead::parse(node, opts)?,
             None => MJHead::empty(opts),
         };
-        let mut body = match body {
+        set_root_attributes(head.get_mut_header(), &attributes);
+        let body = match body {
             Some(node) => MJBody::parse(node, head.get_header())?,
             None => MJBody::empty(),
         };
+        Ok(MJMLElement::new(attributes, head, body))
+    }
+
+    /// create a template without parsing it
+    ///
+    /// ```rust
+    /// use mrml::elements::body::mj_body::MJBody;
+    /// use mrml::elements::body::mj_column::MJColumn;
+    /// use mrml::elements::body::mj_section::MJSection;
+    /// use mrml::elements::body::mj_text::MJText;
+    /// use mrml::elements::head::mj_head::MJHead;
+    /// use mrml::elements::head::mj_title::MJTitle;
+    /// use mrml::elements::prelude::Builder;
+    /// use mrml::elements::MJMLElement;
+    /// let element = MJMLElement::builder()
+    ///     .head(MJHead::new().add_child(MJTitle::new("Hello")))
+    ///     .body(MJBody::new().section(MJSection::new().column(
+    ///         MJColumn::new().add_child(MJText::new().set_attribute("color", "red").content("World")),
+    ///     )))
+    ///     .build();
+    /// assert_eq!(element.get_title(), "Hello");
+    /// assert!(element.get_html().unwrap().contains("World"));
+    /// ```
+    pub fn builder() -> MJMLBuilder {
+        MJMLBuilder::default()
+    }
+
+    fn new(attributes: Attributes, mut head: MJHead, mut body: MJBody) -> MJMLElement {
+        MJInclude::update_header_with_heads(body.get_children(), head.get_mut_header());
+        body.resolve(head.get_header(), None);
         body.set_context(Context::default());
         body.update_header(head.get_mut_header());
-        let element = MJMLElement {
+        let placeholders = Cell::new(false);
+        body.update_attributes(&|attributes: &mut Attributes| {
+            if attributes
+                .inner()
+                .values()
+                .any(|value| has_placeholder(value))
+            {
+                placeholders.set(true);
+            }
+        });
+        MJMLElement {
+            attributes,
             context: None,
             head,
             body,
-        };
-        Ok(element)
+            placeholders: placeholders.get(),
+            template: OnceLock::new(),
+        }
     }
 
     pub fn get_title(&self) -> String {
@@ -58,21 +138,306 @@ impl<'a> MJMLElement<'a> {
         self.head.get_preview()
     }
 
+    /// size between mobile and desktop, from the `mj-breakpoint` or the options
+    pub fn get_breakpoint(&self) -> Size {
+        self.head.get_header().breakpoint().clone()
+    }
+
+    /// plain text version of the body, to be sent along the html
+    pub fn get_text(&self) -> String {
+        self.get_text_with_options(TextOptions::default())
+    }
+
+    /// plain text version of the body, its links going through the link rewriter like the
+    /// ones of the html
+    pub fn get_text_with_options(&self, options: TextOptions) -> String {
+        debug!("get_text");
+        let header = self
+            .head
+            .get_header()
+            .with_render_options(&RenderOptions::default());
+        let mut writer = TextWriter::new(options).with_header(header);
+        self.body.write_text(&mut writer);
+        writer.finish()
+    }
+
     pub fn get_html(&self) -> Result<String, Error> {
+        self.get_html_with_options(&RenderOptions::default())
+    }
+
+    /// generated html, with options overriding the ones given when parsing the template
+    pub fn get_html_with_options(&self, options: &RenderOptions) -> Result<String, Error> {
+        self.render_html(options).map(|(html, _)| html)
+    }
+
+    /// generated html, with the images referenced with `cid:` urls to attach to the email
+    pub fn get_html_with_attachments(&self) -> Result<(String, Vec<InlineImage>), Error> {
+        self.render_html(&RenderOptions::default())
+    }
+
+    fn render_html(&self, options: &RenderOptions) -> Result<(String, Vec<InlineImage>), Error> {
         debug!("get_html");
-        let header = self.head.get_header();
-        Ok(String::from("<!doctype html>")
-            + "<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:v=\"urn:schemas-microsoft-com:vml\" xmlns:o=\"urn:schemas-microsoft-com:office:office\">"
+        let header = self.head.get_header().with_render_options(options);
+        let (body, attachments) = self.embed_assets(&header)?;
+        let lang = header
+            .lang()
+            .map(|value| format!(" lang=\"{}\"", value))
+            .unwrap_or_default();
+        let dir = header
+            .direction()
+            .map(|value| format!(" dir=\"{}\"", value))
+            .unwrap_or_default();
+        let html = String::from("<!doctype html>")
+            + format!("<html{}{} xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:v=\"urn:schemas-microsoft-com:vml\" xmlns:o=\"urn:schemas-microsoft-com:office:office\">", lang, dir).as_str()
             + self.head.render(&header)?.as_str()
-            + self.body.render(&header)?.as_str()
-            + "</html>")
+            + body.render(&header)?.as_str()
+            + "</html>";
+        let html = merge_conditionals(&html);
+        let html = inliner::inline(html.as_str(), header.inline_rules());
+        let html = inliner::set_attributes(html.as_str(), header.html_attributes());
+        if header.minify() {
+            Ok((minifier::minify(&html), attachments))
+        } else {
+            Ok((html, attachments))
+        }
+    }
+
+    /// generated html, split on its `{{ variable }}` placeholders to be rendered
+    /// for many recipients without rendering the whole template again
+    ///
+    /// The placeholders of the attributes are kept as is, where `render_with` replaces them
+    /// before rendering for the link rewriter and the asset resolver to get their values.
+    pub fn template(&self) -> Result<Template, Error> {
+        self.default_template()
+            .map(|(template, _)| template.clone())
+    }
+
+    /// generated html with the given options, split on its placeholders
+    ///
+    /// The template is rendered again on every call, so it should be kept by the caller
+    /// to render it for many recipients.
+    pub fn template_with_options(&self, options: &RenderOptions) -> Result<Template, Error> {
+        Ok(Template::parse(&self.get_html_with_options(options)?))
+    }
+
+    /// template and attachments rendered with the default options, only the first time
+    /// they're needed
+    pub(crate) fn default_template(&self) -> Result<&(Template, Vec<InlineImage>), Error> {
+        if let Some(result) = self.template.get() {
+            return Ok(result);
+        }
+        let (html, attachments) = self.get_html_with_attachments()?;
+        Ok(self
+            .template
+            .get_or_init(|| (Template::parse(&html), attachments)))
+    }
+
+    /// copy of the element with the placeholders of the attributes replaced by the given
+    /// values, the element itself when there are none
+    fn with_values<V: Variables + ?Sized>(&self, values: &V) -> Result<Cow<'_, Self>, Error> {
+        if !self.placeholders {
+            return Ok(Cow::Borrowed(self));
+        }
+        let error = RefCell::new(None);
+        let mut body = self.body.clone();
+        body.update_attributes(&|attributes: &mut Attributes| {
+            let replaced: Result<Vec<(String, String)>, TemplateError> = attributes
+                .inner()
+                .iter()
+                .filter(|(_, value)| has_placeholder(value))
+                .map(|(key, value)| {
+                    Template::parse_attribute(value)
+                        .render(values)
+                        .map(|value| (key.clone(), value))
+                })
+                .collect();
+            match replaced {
+                Ok(items) => {
+                    for (key, value) in items.into_iter() {
+                        attributes.set(key, value);
+                    }
+                }
+                Err(err) => {
+                    error.borrow_mut().get_or_insert(err);
+                }
+            };
+        });
+        if let Some(err) = error.into_inner() {
+            return Err(Error::Template(err));
+        }
+        Ok(Cow::Owned(MJMLElement {
+            attributes: self.attributes.clone(),
+            context: None,
+            head: self.head.clone(),
+            body,
+            placeholders: false,
+            template: OnceLock::new(),
+        }))
+    }
+
+    /// generated html, with the placeholders replaced by the given values
+    pub fn render_with<V: Variables + ?Sized>(&self, values: &V) -> Result<String, Error> {
+        self.with_values(values)?
+            .default_template()?
+            .0
+            .render(values)
+            .map_err(Error::Template)
+    }
+
+    /// generated html, with the placeholders replaced by the given values and the images
+    /// referenced with `cid:` urls to attach to the email
+    pub fn render_with_attachments<V: Variables + ?Sized>(
+        &self,
+        values: &V,
+    ) -> Result<(String, Vec<InlineImage>), Error> {
+        let element = self.with_values(values)?;
+        let (template, attachments) = element.default_template()?;
+        let html = template.render(values).map_err(Error::Template)?;
+        Ok((html, attachments.clone()))
+    }
+
+    /// generated html with the given options, with the placeholders replaced by the given
+    /// values, rendering the whole template on every call like `template_with_options`
+    pub fn render_with_options<V: Variables + ?Sized>(
+        &self,
+        options: &RenderOptions,
+        values: &V,
+    ) -> Result<String, Error> {
+        self.with_values(values)?
+            .template_with_options(options)?
+            .render(values)
+            .map_err(Error::Template)
+    }
+
+    /// body with the urls of the images provided by the asset resolver replaced
+    fn embed_assets(&self, header: &Header) -> Result<(Cow<'_, MJBody>, Vec<InlineImage>), Error> {
+        let mut urls = vec![];
+        asset::collect_urls(&self.body, &mut urls);
+        let mut replacements = vec![];
+        let mut attachments = vec![];
+        for url in urls.into_iter() {
+            let resolved = header
+                .resolve_asset(&url)
+                .map_err(|err| Error::Asset(url.clone(), err))?;
+            let asset = match resolved {
+                Some(value) => value,
+                None => continue,
+            };
+            let value = match header.asset_embedding() {
+                AssetEmbedding::DataUri => asset.to_data_uri(),
+                AssetEmbedding::ContentId => {
+                    let content_id = format!("image-{}@mrml", attachments.len() + 1);
+                    let value = format!("cid:{}", content_id);
+                    attachments.push(InlineImage::new(content_id, asset.content_type, asset.data));
+                    value
+                }
+            };
+            replacements.push((url, value));
+        }
+        if replacements.is_empty() {
+            return Ok((Cow::Borrowed(&self.body), attachments));
+        }
+        let mut body = self.body.clone();
+        asset::replace_urls(&mut body, &replacements);
+        Ok((Cow::Owned(body), attachments))
+    }
+}
+
+/// apply the attributes of the `mjml` element to the header used to render the template
+fn set_root_attributes(header: &mut Header, attributes: &Attributes) {
+    if let Some(value) = attributes.get("lang") {
+        header.set_lang(value.clone());
+    }
+    if let Some(value) = attributes.get("dir") {
+        header.set_direction(value.clone());
+    }
+    header
+        .set_force_owa_desktop(attributes.get("owa").map(|value| value == "desktop") == Some(true));
+}
+
+/// builder of the templates created without parsing, see `MJMLElement::builder`
+#[derive(Default)]
+pub struct MJMLBuilder {
+    attributes: Attributes,
+    options: Option<Options>,
+    head: Option<MJHead>,
+    body: Option<MJBody>,
+}
+
+impl MJMLBuilder {
+    /// set an attribute of the `mjml` element, like `lang`, `dir` or `owa`
+    pub fn set_attribute<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.attributes.set(key, value);
+        self
+    }
+
+    pub fn options(mut self, options: Options) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn head(mut self, head: MJHead) -> Self {
+        self.head = Some(head);
+        self
+    }
+
+    pub fn body(mut self, body: MJBody) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub fn build(self) -> MJMLElement {
+        let mut head = self.head.unwrap_or_default();
+        head.set_options(self.options.unwrap_or_default());
+        let attributes = SCHEMA.normalize(self.attributes);
+        set_root_attributes(head.get_mut_header(), &attributes);
+        MJMLElement::new(attributes, head, self.body.unwrap_or_else(MJBody::empty))
+    }
+}
+
+impl Print for MJMLElement {
+    fn print(&self, printer: &mut Printer) {
+        printer.open("mjml", &self.attributes);
+        if !self.head.is_empty() {
+            self.head.print(printer);
+        }
+        self.body.print(printer);
+        printer.close("mjml");
+    }
+}
+
+impl fmt::Display for MJMLElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_mjml())
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::tests::compare_render;
-    use crate::{to_html, Options};
+    use crate::elements::body::mj_body::MJBody;
+    use crate::elements::body::mj_column::MJColumn;
+    use crate::elements::body::mj_group::MJGroup;
+    use crate::elements::body::mj_image::MJImage;
+    use crate::elements::body::mj_navbar::{MJNavbar, MJNavbarLink};
+    use crate::elements::body::mj_section::MJSection;
+    use crate::elements::body::mj_social::{MJSocial, MJSocialElement};
+    use crate::elements::body::mj_text::MJText;
+    use crate::elements::head::mj_attributes::MJAttributes;
+    use crate::elements::head::mj_head::MJHead;
+    use crate::elements::head::mj_title::MJTitle;
+    use crate::elements::prelude::{Builder, Print};
+    use crate::elements::MJMLElement;
+    use crate::tests::{clean_str, compare_render, compare_render_with_options};
+    use crate::util::asset::{AssetEmbedding, FileAssetResolver};
+    use crate::util::attributes::Attributes;
+    use crate::util::header::DefaultAttributes;
+    use crate::util::link::{Link, LinkRewriter};
+    use crate::util::size::Size;
+    use crate::util::text::TextOptions;
+    use crate::validation::ValidationLevel;
+    use crate::{to_email, to_html, Options, RenderOptions};
+    use std::collections::HashMap;
+    use std::sync::Arc;
 
     #[test]
     fn basic() {
@@ -88,4 +453,486 @@ pub mod tests {
         let result = to_html(template, Options::default()).unwrap();
         println!("result: {:?}", result);
     }
+
+    #[test]
+    fn round_trip() {
+        use crate::util::include::FileIncludeLoader;
+        use std::sync::Arc;
+
+        // templates that are invalid on purpose
+        const EXCLUDED: &[&str] = &["mj-head-unknown-tag.mjml"];
+        let options = || Options {
+            include_loader: Arc::new(FileIncludeLoader::new("test")),
+            ..Options::default()
+        };
+        let mut paths: Vec<_> = std::fs::read_dir("test")
+            .unwrap()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map(|ext| ext == "mjml").unwrap_or(false))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let element = match crate::parse(&source, options()) {
+                Ok(value) => value,
+                Err(err) if EXCLUDED.contains(&name.as_ref()) => {
+                    println!("skipping {:?}: {}", path, err);
+                    continue;
+                }
+                Err(err) => panic!("unable to parse {:?}: {}", path, err),
+            };
+            let printed = element.to_mjml();
+            let reparsed = crate::parse(&printed, options())
+                .unwrap_or_else(|err| panic!("unable to parse printed {:?}: {}", path, err));
+            assert_eq!(
+                printed,
+                reparsed.to_mjml(),
+                "printing {:?} is not stable",
+                path
+            );
+            assert_eq!(
+                clean_str(element.get_html().unwrap()),
+                clean_str(reparsed.get_html().unwrap()),
+                "printed {:?} doesn't render the same html",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn deterministic_output() {
+        for _ in 0..5 {
+            compare_render(
+                include_str!("../../test/mjml-deterministic.mjml"),
+                include_str!("../../test/mjml-deterministic.html"),
+            );
+        }
+    }
+
+    #[test]
+    fn minify() {
+        let template = include_str!("../../test/mj-carousel.mjml");
+        let expected = to_html(template, Options::default()).unwrap();
+        let result = to_html(
+            template,
+            Options {
+                minify: true,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        assert!(result.len() < expected.len());
+        assert!(!result.contains("class=\"\""));
+        assert_eq!(
+            result.matches("<!--[if mso | IE]>").count(),
+            expected.matches("<!--[if mso | IE]>").count()
+        );
+        assert!(result.contains("<![endif]-->"));
+    }
+
+    #[test]
+    fn with_lang_and_dir() {
+        let template = r#"<mjml lang="ar" dir="rtl">
+  <mj-head>
+    <mj-attributes><mj-text align="center" /></mj-attributes>
+  </mj-head>
+  <mj-body>
+    <mj-section><mj-column><mj-text>Hello</mj-text><mj-table>World</mj-table></mj-column></mj-section>
+  </mj-body>
+</mjml>"#;
+        let element = crate::parse(template, Options::default()).unwrap();
+        let result = element.get_html().unwrap();
+        assert!(result.starts_with("<!doctype html><html lang=\"ar\" dir=\"rtl\" xmlns="));
+        assert!(result.contains("<body dir=\"rtl\" lang=\"ar\""));
+        assert!(result.contains("direction:rtl;"));
+        assert!(!result.contains("direction:ltr;"));
+        assert!(result.contains("text-align:center;"));
+        assert!(result.contains("align=\"right\""));
+        assert!(element
+            .to_mjml()
+            .starts_with("<mjml dir=\"rtl\" lang=\"ar\">"));
+        let ltr = to_html("<mjml lang=\"fr\"><mj-body /></mjml>", Options::default()).unwrap();
+        assert!(ltr.contains("<html lang=\"fr\" xmlns="));
+        assert!(!ltr.contains("dir="));
+    }
+
+    #[test]
+    fn with_owa() {
+        let template = "<mjml><mj-body><mj-section><mj-column><mj-text>Hello</mj-text></mj-column></mj-section></mj-body></mjml>";
+        let result = to_html(template, Options::default()).unwrap();
+        assert!(!result.contains("[owa]"));
+        let template = template.replace("<mjml>", "<mjml owa=\"desktop\">");
+        let result = to_html(&template, Options::default()).unwrap();
+        assert!(
+            result.contains("[owa] .mj-column-per-100 { width:100% !important; max-width: 100%; }")
+        );
+        let element = MJMLElement::builder()
+            .set_attribute("owa", "desktop")
+            .body(MJBody::new().section(MJSection::new().column(MJColumn::new())))
+            .build();
+        assert!(element
+            .get_html()
+            .unwrap()
+            .contains("[owa] .mj-column-per-100"));
+    }
+
+    fn column(width: &str, src: &str, title: &str, text: &str) -> MJColumn {
+        MJColumn::new()
+            .add_child(
+                MJImage::new()
+                    .set_attribute("width", width)
+                    .set_attribute("height", "185px")
+                    .set_attribute("padding", "0")
+                    .set_attribute("src", src),
+            )
+            .add_child(
+                MJText::new()
+                    .set_attribute("align", "center")
+                    .content(format!("<h2>{}</h2><p>{}</p>", title, text)),
+            )
+    }
+
+    #[test]
+    fn same_as_parsed() {
+        let element = MJMLElement::builder()
+            .body(MJBody::new().section(MJSection::new().group(
+                MJGroup::new()
+                    .column(column(
+                        "137px",
+                        "https://mjml.io/assets/img/easy-and-quick.png",
+                        "Easy and quick",
+                        "Write less code, save time and code more efficiently with MJML’s semantic syntax.",
+                    ))
+                    .column(column(
+                        "166px",
+                        "https://mjml.io/assets/img/responsive.png",
+                        "Responsive",
+                        "MJML is responsive by design on most-popular email clients, even Outlook.",
+                    )),
+            )))
+            .build();
+        assert_eq!(
+            clean_str(element.get_html().unwrap()),
+            clean_str(include_str!("../../test/mj-group.html").into())
+        );
+    }
+
+    #[test]
+    fn with_head_attributes() {
+        let element = MJMLElement::builder()
+            .head(
+                MJHead::new().add_child(
+                    MJAttributes::new()
+                        .element("mj-text", Attributes::default().add("padding", "0"))
+                        .class("blue", Attributes::default().add("color", "blue"))
+                        .class("big", Attributes::default().add("font-size", "20px"))
+                        .all(Attributes::default().add("font-family", "Arial")),
+                ),
+            )
+            .body(
+                MJBody::new().section(
+                    MJSection::new().column(
+                        MJColumn::new().add_child(
+                            MJText::new()
+                                .set_attribute("mj-class", "blue big")
+                                .content("Hello World!"),
+                        ),
+                    ),
+                ),
+            )
+            .build();
+        assert_eq!(
+            clean_str(element.get_html().unwrap()),
+            clean_str(include_str!("../../test/mj-attributes.html").into())
+        );
+    }
+
+    #[test]
+    fn nested_components() {
+        let element = MJMLElement::builder()
+            .options(Options {
+                keep_comments: false,
+                ..Options::default()
+            })
+            .head(MJHead::new().add_child(MJTitle::new("Newsletter")))
+            .body(
+                MJBody::new().section(
+                    MJSection::new().column(
+                        MJColumn::new()
+                            .add_child(
+                                MJSocial::new().set_attribute("icon-size", "30px").element(
+                                    MJSocialElement::new()
+                                        .set_attribute("name", "facebook")
+                                        .set_attribute("href", "https://mjml.io/")
+                                        .content("Facebook"),
+                                ),
+                            )
+                            .add_child(
+                                MJNavbar::new()
+                                    .set_attribute("base-url", "https://mjml.io")
+                                    .link(
+                                        MJNavbarLink::new()
+                                            .set_attribute("href", "/gettings-started-onboard")
+                                            .content("Getting started"),
+                                    ),
+                            ),
+                    ),
+                ),
+            )
+            .build();
+        assert_eq!(element.get_title(), "Newsletter");
+        let expected = to_html(
+            &element.to_mjml(),
+            Options {
+                keep_comments: false,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(clean_str(element.get_html().unwrap()), clean_str(expected));
+    }
+
+    fn options_with_defaults() -> Options {
+        let mut default_attributes = DefaultAttributes::default();
+        default_attributes.add_all_content(std::iter::once(("font-family", "Brand")));
+        default_attributes.add_element_content("mj-text", std::iter::once(("color", "#123456")));
+        default_attributes.add_class_content("big", std::iter::once(("font-size", "42px")));
+        Options {
+            default_attributes,
+            styles: vec![".brand { color: red; }".into()],
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn options_defaults() {
+        compare_render_with_options(
+            include_str!("../../test/mjml-options-defaults.mjml"),
+            include_str!("../../test/mjml-options-defaults.html"),
+            options_with_defaults(),
+        );
+        compare_render_with_options(
+            include_str!("../../test/mjml-options-defaults-head.mjml"),
+            include_str!("../../test/mjml-options-defaults-head.html"),
+            options_with_defaults(),
+        );
+    }
+
+    #[test]
+    fn plain_text() {
+        let element = crate::parse(
+            include_str!("../../test/mjml-plain-text.mjml"),
+            Options::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            element.get_text_with_options(TextOptions {
+                line_width: Some(30)
+            }),
+            include_str!("../../test/mjml-plain-text.txt")
+        );
+    }
+
+    fn asset_options(asset_embedding: AssetEmbedding) -> Options {
+        Options {
+            asset_resolver: Arc::new(FileAssetResolver::new("test")),
+            asset_embedding,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn embed_assets() {
+        let template = include_str!("../../test/mjml-embed-assets.mjml");
+        compare_render_with_options(
+            template,
+            include_str!("../../test/mjml-embed-assets.html"),
+            asset_options(AssetEmbedding::DataUri),
+        );
+        let email = to_email(template, asset_options(AssetEmbedding::ContentId)).unwrap();
+        assert!(email.html.contains("src=\"cid:image-1@mrml\""));
+        assert!(email.html.contains("url(cid:image-1@mrml)"));
+        assert!(email.html.contains("src=\"cid:image-2@mrml\""));
+        assert_eq!(email.attachments.len(), 2);
+        assert!(email
+            .message()
+            .to_string()
+            .contains("Content-ID: <image-2@mrml>"));
+        let error = to_html(
+            "<mjml><mj-body><mj-image src=\"missing.png\" /></mj-body></mjml>",
+            asset_options(AssetEmbedding::DataUri),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unable to embed \"missing.png\""));
+    }
+
+    #[derive(Debug, Default)]
+    struct TrackingRewriter(std::sync::Mutex<Vec<String>>);
+
+    impl LinkRewriter for TrackingRewriter {
+        fn rewrite(&self, link: &Link) -> Option<String> {
+            let value = format!("{}:{}:{}", link.position, link.element, link.url);
+            self.0.lock().unwrap().push(value.clone());
+            Some(format!("https://track.example.com/{}", value))
+        }
+    }
+
+    #[test]
+    fn rewrite_links() {
+        let template = include_str!("../../test/mjml-link-rewriter.mjml");
+        let rewriter = Arc::new(TrackingRewriter::default());
+        let options = || Options {
+            link_rewriter: rewriter.clone(),
+            ..Options::default()
+        };
+        compare_render_with_options(
+            template,
+            include_str!("../../test/mjml-link-rewriter.html"),
+            options(),
+        );
+        assert_eq!(
+            rewriter.0.lock().unwrap().clone(),
+            vec![
+                "0:mj-navbar-link:https://example.com/home",
+                "1:mj-image:https://example.com/image",
+                "2:a:https://example.com/text",
+                "3:mj-button:https://example.com/button",
+                "4:mj-social-element:https://www.facebook.com/sharer/sharer.php?u=https://example.com",
+                "5:mj-carousel-image:https://example.com/carousel",
+                // the first image is repeated for outlook
+                "6:mj-carousel-image:https://example.com/carousel",
+            ]
+        );
+        let element = crate::parse(template, options()).unwrap();
+        assert_eq!(element.get_html().unwrap(), element.get_html().unwrap());
+
+        rewriter.0.lock().unwrap().clear();
+        let text = element.get_text_with_options(TextOptions { line_width: None });
+        assert!(text.starts_with(
+            "- [Home](https://track.example.com/0:mj-navbar-link:https://example.com/home)"
+        ));
+        assert_eq!(
+            rewriter.0.lock().unwrap().clone(),
+            vec![
+                "0:mj-navbar-link:https://example.com/home",
+                "1:mj-image:https://example.com/image",
+                "2:a:https://example.com/text",
+                "3:mj-button:https://example.com/button",
+                "4:mj-social-element:https://www.facebook.com/sharer/sharer.php?u=https://example.com",
+                "5:mj-carousel-image:https://example.com/carousel",
+            ]
+        );
+    }
+
+    fn variables() -> HashMap<&'static str, &'static str> {
+        let mut values = HashMap::new();
+        values.insert("name", "Jane & John");
+        values.insert("url", "https://example.com/?id=1&t=\"x\"");
+        values.insert("signature", "<i>The team</i>");
+        values
+    }
+
+    #[test]
+    fn render_with_variables() {
+        let element = crate::parse(
+            include_str!("../../test/mjml-variables.mjml"),
+            Options::default(),
+        )
+        .unwrap();
+        let mut values = variables();
+        assert!(element.placeholders);
+        let result = element.render_with(&values).unwrap();
+        assert_eq!(
+            clean_str(result),
+            clean_str(include_str!("../../test/mjml-variables.html").into())
+        );
+        assert_eq!(
+            element.template().unwrap().variables(),
+            vec!["name", "signature", "url"]
+        );
+        values.remove("url");
+        assert!(element
+            .render_with(&values)
+            .unwrap_err()
+            .to_string()
+            .contains("missing variable \"url\""));
+    }
+
+    #[test]
+    fn render_with_variables_in_attributes() {
+        let rewriter = Arc::new(TrackingRewriter::default());
+        let element = crate::parse(
+            include_str!("../../test/mjml-variables-attributes.mjml"),
+            Options {
+                asset_resolver: Arc::new(FileAssetResolver::new("test")),
+                link_rewriter: rewriter.clone(),
+                validation_level: ValidationLevel::Strict,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        let mut values = HashMap::new();
+        values.insert("logo", "assets/pixel.png");
+        values.insert("url", "https://example.com/?a=1&b=2");
+        values.insert("brand", "#ff0000");
+        let result = element.render_with(&values).unwrap();
+        assert_eq!(
+            clean_str(result),
+            clean_str(include_str!("../../test/mjml-variables-attributes.html").into())
+        );
+        assert_eq!(
+            rewriter.0.lock().unwrap().clone(),
+            vec!["0:mj-button:https://example.com/?a=1&amp;b=2"]
+        );
+        values.remove("logo");
+        assert!(element
+            .render_with(&values)
+            .unwrap_err()
+            .to_string()
+            .contains("missing variable \"logo\""));
+    }
+
+    #[test]
+    fn render_concurrently() {
+        fn assert_shareable<T: Send + Sync + 'static>(_value: &T) {}
+        let template = r#"<mjml>
+  <mj-body>
+    <mj-section><mj-column><mj-text>Hello {{ name }}</mj-text></mj-column></mj-section>
+  </mj-body>
+</mjml>"#;
+        let element = Arc::new(crate::parse(template, Options::default()).unwrap());
+        assert_shareable(&element);
+        let handles: Vec<_> = (0..4)
+            .map(|index| {
+                let element = element.clone();
+                std::thread::spawn(move || {
+                    let options = RenderOptions {
+                        breakpoint: Some(Size::Pixel(400.0 + index as f32)),
+                        ..RenderOptions::default()
+                    };
+                    let mut values = HashMap::new();
+                    values.insert("name", format!("reader {}", index));
+                    element.render_with_options(&options, &values).unwrap()
+                })
+            })
+            .collect();
+        for (index, handle) in handles.into_iter().enumerate() {
+            let result = handle.join().unwrap();
+            assert!(result.contains(&format!("min-width:{}px", 400 + index)));
+            assert!(result.contains(&format!("Hello reader {}", index)));
+        }
+        assert!(element.get_html().unwrap().contains("min-width:480px"));
+        assert!(!element.placeholders);
+        assert!(element.template.get().is_none());
+        let mut values = HashMap::new();
+        values.insert("name", "reader");
+        assert!(element
+            .render_with(&values)
+            .unwrap()
+            .contains("Hello reader"));
+        assert!(element.template.get().is_some());
+    }
 }
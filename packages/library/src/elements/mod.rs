@@ -4,14 +4,16 @@ use crate::Options;
 pub mod body;
 pub mod error;
 pub mod head;
+pub mod include;
 mod mjml;
 pub mod prelude;
+pub mod schema;
 
 use error::Error;
 use prelude::Component;
 
-pub use mjml::MJMLElement;
+pub use mjml::{MJMLBuilder, MJMLElement};
 
-pub fn parse<'a>(node: &Node<'a>, opts: Options) -> Result<mjml::MJMLElement<'a>, Error> {
+pub fn parse(node: &Node, opts: Options) -> Result<mjml::MJMLElement, Error> {
     mjml::MJMLElement::parse(node, opts)
 }
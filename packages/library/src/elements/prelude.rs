@@ -1,7 +1,9 @@
 use super::error::Error;
 use crate::parser::Node;
+use crate::util::attributes::Attributes;
 use crate::util::context::Context;
 use crate::util::header::Header;
+use crate::util::printer::{PrintOptions, Printer};
 use std::collections::HashMap;
 
 pub fn get_node_attributes<'a>(node: &Node<'a>) -> HashMap<String, String> {
@@ -23,3 +25,28 @@ pub trait Component {
     fn update_header(&self, _header: &mut Header) {}
     fn render(&self, header: &Header) -> Result<String, Error>;
 }
+
+pub trait Print {
+    fn print(&self, printer: &mut Printer);
+
+    /// serialize the element back to mjml
+    fn to_mjml(&self) -> String {
+        self.to_mjml_with_options(PrintOptions::default())
+    }
+
+    fn to_mjml_with_options(&self, options: PrintOptions) -> String {
+        let mut printer = Printer::new(options);
+        self.print(&mut printer);
+        printer.finish()
+    }
+}
+
+/// elements that can be created without template, using the attributes that would be written in it
+pub trait Builder: Sized {
+    fn source_attributes_mut(&mut self) -> &mut Attributes;
+
+    fn set_attribute<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.source_attributes_mut().set(key, value);
+        self
+    }
+}
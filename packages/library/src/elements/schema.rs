@@ -0,0 +1,153 @@
+use super::body::mj_accordion::{
+    MJAccordion, MJAccordionElement, MJAccordionText, MJAccordionTitle,
+};
+use super::body::mj_body::MJBody;
+use super::body::mj_button::MJButton;
+use super::body::mj_carousel::{MJCarousel, MJCarouselImage};
+use super::body::mj_column::MJColumn;
+use super::body::mj_divider::MJDivider;
+use super::body::mj_group::MJGroup;
+use super::body::mj_hero::MJHero;
+use super::body::mj_image::MJImage;
+use super::body::mj_navbar::{MJNavbar, MJNavbarLink};
+use super::body::mj_raw::MJRaw;
+use super::body::mj_section::MJSection;
+use super::body::mj_social::{MJSocial, MJSocialElement};
+use super::body::mj_spacer::MJSpacer;
+use super::body::mj_table::MJTable;
+use super::body::mj_text::MJText;
+use super::body::mj_wrapper::MJWrapper;
+use super::head::mj_attributes::MJAttributes;
+use super::head::mj_breakpoint::MJBreakpoint;
+use super::head::mj_font::MJFont;
+use super::head::mj_head::MJHead;
+use super::head::mj_html_attributes::{MJHtmlAttribute, MJHtmlAttributes, MJSelector};
+use super::head::mj_preview::MJPreview;
+use super::head::mj_style::MJStyle;
+use super::head::mj_title::MJTitle;
+use super::include::Include;
+use super::MJMLElement;
+use crate::util::schema::Schema;
+
+/// tag names of the elements having a schema
+pub const TAGS: &[&str] = &[
+    "mjml",
+    "mj-accordion",
+    "mj-accordion-element",
+    "mj-accordion-text",
+    "mj-accordion-title",
+    "mj-attributes",
+    "mj-body",
+    "mj-breakpoint",
+    "mj-button",
+    "mj-carousel",
+    "mj-carousel-image",
+    "mj-column",
+    "mj-divider",
+    "mj-font",
+    "mj-group",
+    "mj-head",
+    "mj-hero",
+    "mj-html-attribute",
+    "mj-html-attributes",
+    "mj-image",
+    "mj-include",
+    "mj-navbar",
+    "mj-navbar-link",
+    "mj-preview",
+    "mj-raw",
+    "mj-section",
+    "mj-selector",
+    "mj-social",
+    "mj-social-element",
+    "mj-spacer",
+    "mj-style",
+    "mj-table",
+    "mj-text",
+    "mj-title",
+    "mj-wrapper",
+];
+
+/// schema of the element with the given tag name
+///
+/// ```rust
+/// use mrml::elements::schema;
+/// use mrml::util::schema::AttributeType;
+/// let text = schema::get("mj-text").unwrap();
+/// assert_eq!(text.get("color"), Some(&AttributeType::Color));
+/// assert!(schema::get("mj-unknown").is_none());
+/// ```
+pub fn get(tag: &str) -> Option<&'static Schema> {
+    let res = match tag {
+        "mjml" => MJMLElement::schema(),
+        "mj-accordion" => MJAccordion::schema(),
+        "mj-accordion-element" => MJAccordionElement::schema(),
+        "mj-accordion-text" => MJAccordionText::schema(),
+        "mj-accordion-title" => MJAccordionTitle::schema(),
+        "mj-attributes" => MJAttributes::schema(),
+        "mj-body" => MJBody::schema(),
+        "mj-breakpoint" => MJBreakpoint::schema(),
+        "mj-button" => MJButton::schema(),
+        "mj-carousel" => MJCarousel::schema(),
+        "mj-carousel-image" => MJCarouselImage::schema(),
+        "mj-column" => MJColumn::schema(),
+        "mj-divider" => MJDivider::schema(),
+        "mj-font" => MJFont::schema(),
+        "mj-group" => MJGroup::schema(),
+        "mj-head" => MJHead::schema(),
+        "mj-hero" => MJHero::schema(),
+        "mj-html-attribute" => MJHtmlAttribute::schema(),
+        "mj-html-attributes" => MJHtmlAttributes::schema(),
+        "mj-image" => MJImage::schema(),
+        "mj-include" => Include::schema(),
+        "mj-navbar" => MJNavbar::schema(),
+        "mj-navbar-link" => MJNavbarLink::schema(),
+        "mj-preview" => MJPreview::schema(),
+        "mj-raw" => MJRaw::schema(),
+        "mj-section" => MJSection::schema(),
+        "mj-selector" => MJSelector::schema(),
+        "mj-social" => MJSocial::schema(),
+        "mj-social-element" => MJSocialElement::schema(),
+        "mj-spacer" => MJSpacer::schema(),
+        "mj-style" => MJStyle::schema(),
+        "mj-table" => MJTable::schema(),
+        "mj-text" => MJText::schema(),
+        "mj-title" => MJTitle::schema(),
+        "mj-wrapper" => MJWrapper::schema(),
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// json document describing every element, meant for the editor autocompletion
+///
+/// ```rust
+/// let json = mrml::elements::schema::to_json();
+/// assert!(json.starts_with(r#"{"mjml":{"children":["mj-body","mj-head"],"attributes":{"dir":"#));
+/// ```
+pub fn to_json() -> String {
+    let items = TAGS
+        .iter()
+        .filter_map(|tag| get(tag).map(|schema| format!("\"{}\":{}", tag, schema.to_json())))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", items.join(","))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn every_tag_has_a_schema() {
+        for tag in TAGS {
+            assert!(get(tag).is_some(), "missing schema for {}", tag);
+        }
+    }
+
+    #[test]
+    fn normalize_on_parse() {
+        let template = r#"<mjml><mj-body><mj-section><mj-column><mj-text padding="10 25">Hello</mj-text></mj-column></mj-section></mj-body></mjml>"#;
+        let result = crate::to_mjml(template, crate::Options::default()).unwrap();
+        assert!(result.contains(r#"<mj-text padding="10px 25px">Hello</mj-text>"#));
+    }
+}
@@ -1,26 +1,310 @@
 use crate::elements::error::Error as MJMLError;
 use crate::parser::Error as ParserError;
+use crate::parser::Span;
+use crate::validation::Report;
+use std::collections::HashMap;
+use std::fmt;
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+/// position of an error in the source template
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    /// line number, starting at 1
+    pub line: usize,
+    /// column number in characters, starting at 1
+    pub column: usize,
+    /// byte range in the template
+    pub span: Span,
+    /// path of the element in the template (e.g. `mjml > mj-body > mj-section[2]`)
+    pub path: String,
+    /// content of the line where the error starts
+    pub snippet: String,
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map(|idx| idx + 1).unwrap_or(0)
+}
+
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map(|idx| offset + idx)
+        .unwrap_or_else(|| source.len())
+}
+
+fn clamp_offset(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+fn offset_from_position(source: &str, line: usize, column: usize) -> usize {
+    let start = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(|item| item.len())
+        .sum::<usize>();
+    let end = line_end(source, start.min(source.len()));
+    source[start.min(end)..end]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map(|(idx, _)| start + idx)
+        .unwrap_or(end)
+}
+
+fn path_at(source: &str, offset: usize) -> String {
+    let mut stack: Vec<(String, usize)> = vec![];
+    let mut counters: Vec<HashMap<String, usize>> = vec![HashMap::new()];
+    for token in Tokenizer::from(source) {
+        let token = match token {
+            Ok(value) => value,
+            Err(_) => break,
+        };
+        match token {
+            Token::ElementStart { local, span, .. } => {
+                if span.start() > offset {
+                    break;
+                }
+                let name = local.as_str().to_string();
+                let counter = counters.last_mut().unwrap();
+                let index = counter.entry(name.clone()).or_insert(0);
+                *index += 1;
+                stack.push((name, *index));
+                counters.push(HashMap::new());
+            }
+            Token::ElementEnd { end, span } => {
+                if span.end() > offset {
+                    if span.start() > offset {
+                        break;
+                    }
+                    continue;
+                }
+                if let ElementEnd::Empty | ElementEnd::Close(_, _) = end {
+                    stack.pop();
+                    counters.pop();
+                }
+            }
+            _ => (),
+        };
+    }
+    stack
+        .iter()
+        .map(|(name, index)| {
+            if *index > 1 {
+                format!("{}[{}]", name, index)
+            } else {
+                name.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+impl Location {
+    /// locate a byte range in the template
+    pub fn from_span(source: &str, span: Span) -> Self {
+        let start = clamp_offset(source, span.start);
+        let end = clamp_offset(source, span.end.max(start));
+        let line_start = line_start(source, start);
+        Self {
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            span: Span::new(start, end),
+            path: path_at(source, start),
+            snippet: source[line_start..line_end(source, start)].to_string(),
+        }
+    }
+
+    /// locate a line and column in the template
+    pub fn from_position(source: &str, line: usize, column: usize) -> Self {
+        let start = offset_from_position(source, line, column);
+        let end = source[start..]
+            .chars()
+            .next()
+            .map(|c| start + c.len_utf8())
+            .unwrap_or(start);
+        Self::from_span(source, Span::new(start, end))
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line = self.line.to_string();
+        let gutter = " ".repeat(line.len());
+        let prefix: String = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = self
+            .snippet
+            .chars()
+            .skip(self.column - 1)
+            .take(self.span.end - self.span.start)
+            .count()
+            .max(1);
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line, self.snippet)?;
+        write!(f, "{} | {}{}", gutter, prefix, "^".repeat(width))
+    }
+}
 
 #[derive(Debug)]
 pub enum Error {
-    MJMLError(MJMLError),
-    ParserError(ParserError),
+    MJMLError(MJMLError, Box<Location>),
+    ParserError(ParserError, Box<Location>),
+    /// the template doesn't pass the strict validation, located at the first error
+    ValidationError(Report, Box<Location>),
 }
 
 impl Error {
+    /// build an error from an element error, locating it in the template
+    pub fn from_mjml(source: &str, error: MJMLError) -> Self {
+        let location = Location::from_span(source, error.span());
+        Error::MJMLError(error, Box::new(location))
+    }
+
+    /// build an error from a parser error, locating it in the template
+    pub fn from_parser(source: &str, error: ParserError) -> Self {
+        let location = match &error {
+            ParserError::ParserError(inner) => {
+                let pos = inner.pos();
+                Location::from_position(source, pos.row as usize, pos.col as usize)
+            }
+            ParserError::SizeLimit => Location {
+                line: 1,
+                column: 1,
+                span: Span::default(),
+                path: String::new(),
+                snippet: String::new(),
+            },
+            other => Location::from_span(source, other.span().unwrap_or_default()),
+        };
+        Error::ParserError(error, Box::new(location))
+    }
+
+    /// build an error from a validation report containing errors
+    pub fn from_report(report: Report) -> Self {
+        let location = report
+            .errors()
+            .next()
+            .map(|finding| finding.location.clone())
+            .unwrap_or_else(|| Location::from_span("", Span::default()));
+        Error::ValidationError(report, Box::new(location))
+    }
+
     pub fn is_mjml_error(&self) -> bool {
-        matches!(self, Error::MJMLError(_))
+        matches!(self, Error::MJMLError(_, _))
+    }
+
+    pub fn location(&self) -> &Location {
+        match self {
+            Error::MJMLError(_, location) => location,
+            Error::ParserError(_, location) => location,
+            Error::ValidationError(_, location) => location,
+        }
+    }
+
+    /// description of the error, without its location
+    pub fn message(&self) -> String {
+        match self {
+            Error::MJMLError(error, _) => error.to_string(),
+            Error::ParserError(error, _) => error.to_string(),
+            Error::ValidationError(report, _) => match report.errors().next() {
+                Some(finding) => finding.kind.to_string(),
+                None => "invalid template".into(),
+            },
+        }
+    }
+
+    /// findings of the validation, when the template failed the strict validation
+    pub fn report(&self) -> Option<&Report> {
+        match self {
+            Error::ValidationError(report, _) => Some(report),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message())?;
+        let location = self.location();
+        write!(f, " at line {}, column {}", location.line, location.column)?;
+        if !location.path.is_empty() {
+            write!(f, " ({})", location.path)?;
+        }
+        if location.snippet.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "\n{}", location)
+        }
     }
 }
 
-impl From<MJMLError> for Error {
-    fn from(error: MJMLError) -> Error {
-        Error::MJMLError(error)
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::MJMLError(error, _) => Some(error),
+            Error::ParserError(error, _) => Some(error),
+            Error::ValidationError(_, _) => None,
+        }
     }
 }
 
-impl From<ParserError> for Error {
-    fn from(error: ParserError) -> Error {
-        Error::ParserError(error)
+#[cfg(test)]
+mod tests {
+    use crate::{to_html, Options};
+
+    #[test]
+    fn unexpected_element_location() {
+        let template = "<mjml>\n  <mj-body>\n    <mj-section></mj-section>\n    <mj-section>\n      <mj-column><mj-carousel><mj-text /></mj-carousel></mj-column>\n    </mj-section>\n  </mj-body>\n</mjml>";
+        let error = to_html(template, Options::default()).unwrap_err();
+        assert!(error.is_mjml_error());
+        let location = error.location();
+        assert_eq!(location.line, 5);
+        assert_eq!(location.column, 31);
+        assert_eq!(
+            location.path,
+            "mjml > mj-body > mj-section[2] > mj-column > mj-carousel > mj-text"
+        );
+        assert_eq!(
+            &template[location.span.start..location.span.end],
+            "<mj-text"
+        );
+    }
+
+    #[test]
+    fn invalid_format_location() {
+        let template = "<mjml>\n  <mj-body>\n  </mj-head>\n</mjml>";
+        let error = to_html(template, Options::default()).unwrap_err();
+        assert!(!error.is_mjml_error());
+        let location = error.location();
+        assert_eq!(location.line, 3);
+        assert_eq!(location.column, 3);
+        assert_eq!(location.path, "mjml > mj-body");
+    }
+
+    #[test]
+    fn xml_error_location() {
+        let template = "<mjml>\n  <mj-body <>\n</mjml>";
+        let error = to_html(template, Options::default()).unwrap_err();
+        let location = error.location();
+        assert_eq!(location.line, 2);
+        assert_eq!(location.path, "mjml > mj-body");
+    }
+
+    #[test]
+    fn display_with_snippet() {
+        let template = "<mjml>\n  <mj-head>\n    <mj-unknown></mj-unknown>\n  </mj-head>\n</mjml>";
+        let error = to_html(template, Options::default()).unwrap_err();
+        assert_eq!(error.message(), "unexpected element <mj-unknown>");
+        assert_eq!(
+            error.to_string(),
+            "unexpected element <mj-unknown> at line 3, column 5 (mjml > mj-head > mj-unknown)\n  |\n3 |     <mj-unknown></mj-unknown>\n  |     ^^^^^^^^^^^"
+        );
     }
 }
@@ -6,15 +6,24 @@ extern crate difference;
 extern crate lazy_static;
 
 use std::default::Default;
+use std::path::Path;
+use std::sync::Arc;
 
 pub mod elements;
 mod error;
 pub mod parser;
 pub mod util;
+pub mod validation;
 
-pub use error::Error;
+pub use error::{Error, Location};
+use util::asset::{AssetEmbedding, AssetResolver, NoopAssetResolver};
+use util::config::{Config, ConfigError};
 use util::fonts::FontRegistry;
+use util::header::{DefaultAttributes, Header};
+use util::include::{IncludeLoader, NoopIncludeLoader};
+use util::link::{LinkRewriter, NoopLinkRewriter};
 use util::size::Size;
+use validation::{Report, ValidationLevel};
 
 /// global options for renderer
 #[derive(Clone, Debug)]
@@ -25,6 +34,28 @@ pub struct Options {
     pub fonts: FontRegistry,
     /// rather the comments should be kept
     pub keep_comments: bool,
+    /// loader used to resolve the `mj-include` elements
+    pub include_loader: Arc<dyn IncludeLoader>,
+    /// maximum depth of nested `mj-include`
+    pub include_max_depth: usize,
+    /// how strictly the template is checked before rendering
+    pub validation_level: ValidationLevel,
+    /// seed of the ids generated for the elements like `mj-carousel` or `mj-navbar`,
+    /// the same seed always gives the same ids
+    pub id_seed: u64,
+    /// rather the generated html should be minified
+    pub minify: bool,
+    /// default attributes applied before the `mj-attributes` of the template,
+    /// which can override them
+    pub default_attributes: DefaultAttributes,
+    /// css added to the head before the content of the `mj-style` of the template
+    pub styles: Vec<String>,
+    /// resolver of the images used by the components, to embed the local ones
+    pub asset_resolver: Arc<dyn AssetResolver>,
+    /// how the images provided by the resolver are embedded in the generated html
+    pub asset_embedding: AssetEmbedding,
+    /// rewriter of the links emitted by the components, like for click tracking
+    pub link_rewriter: Arc<dyn LinkRewriter>,
 }
 
 impl Default for Options {
@@ -33,16 +64,102 @@ impl Default for Options {
             breakpoint: Size::Pixel(480.0),
             fonts: FontRegistry::new(),
             keep_comments: true,
+            include_loader: Arc::new(NoopIncludeLoader),
+            include_max_depth: 10,
+            validation_level: ValidationLevel::default(),
+            id_seed: 0,
+            minify: false,
+            default_attributes: DefaultAttributes::default(),
+            styles: vec![],
+            asset_resolver: Arc::new(NoopAssetResolver),
+            asset_embedding: AssetEmbedding::default(),
+            link_rewriter: Arc::new(NoopLinkRewriter),
         }
     }
 }
 
+impl Options {
+    /// default options overridden by the ones of a configuration file, see [`util::config`]
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut result = Self::default();
+        Config::from_file(path)?.apply(&mut result);
+        Ok(result)
+    }
+}
+
+/// options of a single rendering of a parsed template, overriding the ones given when
+/// parsing it
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// size between mobile and desktop, instead of the one of the template
+    pub breakpoint: Option<Size>,
+    /// rather the comments should be kept
+    pub keep_comments: Option<bool>,
+    /// rather the generated html should be minified
+    pub minify: Option<bool>,
+    /// rewriter of the links emitted by the components
+    pub link_rewriter: Option<Arc<dyn LinkRewriter>>,
+}
+
 pub fn parse(input: &str, options: Options) -> Result<elements::MJMLElement, Error> {
-    let root = parser::parse(input)?;
-    let element = elements::parse(&root, options)?;
+    let (element, report) = parse_with_report(input, options)?;
+    for finding in report.findings {
+        log::warn!("{}", finding);
+    }
     Ok(element)
 }
 
+/// parse the template along with the findings of its validation, empty when the validation
+/// level is [`ValidationLevel::Skip`], the default one
+///
+/// ```rust
+/// use mrml::{parse_with_report, validation::ValidationLevel, Options};
+/// let options = Options {
+///     validation_level: ValidationLevel::Soft,
+///     ..Options::default()
+/// };
+/// let (_, report) = parse_with_report("<mjml><mj-body><mj-column /></mj-body></mjml>", options).unwrap();
+/// assert_eq!(report.errors().count(), 1);
+/// ```
+pub fn parse_with_report(
+    input: &str,
+    options: Options,
+) -> Result<(elements::MJMLElement, Report), Error> {
+    let root = parser::parse(input).map_err(|err| Error::from_parser(input, err))?;
+    let report = match options.validation_level {
+        ValidationLevel::Skip => Report::default(),
+        level => {
+            let header = Header::from(options.clone());
+            let report = validation::validate(input, &root, &header)
+                .map_err(|err| Error::from_mjml(input, err))?;
+            if level == ValidationLevel::Strict && !report.is_valid() {
+                return Err(Error::from_report(report));
+            }
+            report
+        }
+    };
+    let element = elements::parse(&root, options).map_err(|err| Error::from_mjml(input, err))?;
+    Ok((element, report))
+}
+
+/// check the template against the mjml rules, whatever the validation level, the
+/// templates included with the loader of the options being checked too
+///
+/// ```rust
+/// use mrml::{validate, Options};
+/// let report = validate("<mjml><mj-body><mj-column /></mj-body></mjml>", &Options::default()).unwrap();
+/// assert!(!report.is_valid());
+/// assert_eq!(
+///     report.findings[0].to_string(),
+///     "error: element <mj-column> is not allowed in <mj-body> at line 1, column 16 (mjml > mj-body > mj-column)"
+/// );
+/// ```
+pub fn validate(input: &str, options: &Options) -> Result<Report, Error> {
+    let root = parser::parse(input).map_err(|err| Error::from_parser(input, err))?;
+    let header = Header::from(options.clone());
+    validation::validate(input, &root, &header).map_err(|err| Error::from_mjml(input, err))
+}
+
 /// generate the title from mjml
 ///
 /// ```rust
@@ -101,14 +218,90 @@ pub fn to_preview(input: &str, options: Options) -> Result<String, Error> {
 /// ```
 pub fn to_html(input: &str, options: Options) -> Result<String, Error> {
     let element = parse(input, options)?;
-    Ok(element.get_html()?)
+    element
+        .get_html()
+        .map_err(|err| Error::from_mjml(input, err))
+}
+
+/// render the body of the template as plain text
+///
+/// ```rust
+/// use mrml::{to_text, Options};
+/// let result = to_text(r#"<mjml><mj-body><mj-text><h1>Hello</h1><p>Dear reader</p></mj-text><mj-divider /><mj-button href="https://example.com">Read more</mj-button></mj-body></mjml>"#, Options::default());
+/// assert_eq!(result.unwrap(), format!("# Hello\n\nDear reader\n\n{}\n\n[Read more](https://example.com)\n", "-".repeat(78)));
+/// ```
+pub fn to_text(input: &str, options: Options) -> Result<String, Error> {
+    let element = parse(input, options)?;
+    Ok(element.get_text())
 }
 
 #[derive(Clone, Debug)]
 pub struct Email {
     pub subject: String,
+    /// plain text alternative of the html
     pub text: String,
     pub html: String,
+    /// images referenced in the html with `cid:` urls
+    pub attachments: Vec<util::mime::InlineImage>,
+}
+
+impl Email {
+    /// build the mime message to send the email
+    pub fn message(&self) -> util::mime::Message<'_> {
+        util::mime::Message::new(self)
+    }
+}
+
+/// email split on its `{{ variable }}` placeholders, to be rendered for many recipients
+#[derive(Clone, Debug)]
+pub struct EmailTemplate {
+    pub subject: util::template::Template,
+    pub text: util::template::Template,
+    element: elements::MJMLElement,
+}
+
+impl EmailTemplate {
+    /// email with the placeholders of the subject, text and html replaced by the given values
+    pub fn render<V: util::template::Variables + ?Sized>(
+        &self,
+        values: &V,
+    ) -> Result<Email, elements::error::Error> {
+        let (html, attachments) = self.element.render_with_attachments(values)?;
+        Ok(Email {
+            subject: self
+                .subject
+                .render(values)
+                .map_err(elements::error::Error::Template)?,
+            text: self
+                .text
+                .render(values)
+                .map_err(elements::error::Error::Template)?,
+            html,
+            attachments,
+        })
+    }
+}
+
+/// parse the template and print it back as canonical mjml
+///
+/// ```rust
+/// use mrml::{to_mjml, Options};
+/// let result = to_mjml(r#"<mjml><mj-body><mj-section><mj-column><mj-text color="red"  align="left">Hello <b>World</b></mj-text></mj-column></mj-section></mj-body></mjml>"#, Options::default());
+/// assert_eq!(result.unwrap(), r#"<mjml>
+///   <mj-body>
+///     <mj-section>
+///       <mj-column>
+///         <mj-text align="left" color="red">Hello <b>World</b></mj-text>
+///       </mj-column>
+///     </mj-section>
+///   </mj-body>
+/// </mjml>
+/// "#);
+/// ```
+pub fn to_mjml(input: &str, options: Options) -> Result<String, Error> {
+    use elements::prelude::Print;
+    let element = parse(input, options)?;
+    Ok(element.to_mjml())
 }
 
 /// generate an email from mjml
@@ -121,12 +314,15 @@ pub struct Email {
 ///             <mj-title>Testing</mj-title>
 ///             <mj-preview>Preview</mj-preview>
 ///         </mj-head>
+///         <mj-body>
+///             <mj-text>Hello <b>World</b></mj-text>
+///         </mj-body>
 ///     </mjml>
 /// "#, Options::default());
 /// assert!(result.is_ok());
 /// let result = result.unwrap();
 /// assert_eq!(result.subject, "Testing");
-/// assert_eq!(result.text, "Preview");
+/// assert_eq!(result.text, "Hello World\n");
 /// ```
 ///
 /// ```rust
@@ -137,12 +333,51 @@ pub struct Email {
 pub fn to_email(input: &str, options: Options) -> Result<Email, Error> {
     let element = parse(input, options)?;
     let subject = element.get_title();
-    let text = element.get_preview();
-    let html = element.get_html()?;
+    let text = element.get_text();
+    let (html, attachments) = element
+        .get_html_with_attachments()
+        .map_err(|err| Error::from_mjml(input, err))?;
     Ok(Email {
         subject,
         text,
         html,
+        attachments,
+    })
+}
+
+/// generate an email from mjml, split on its `{{ variable }}` placeholders
+///
+/// ```rust
+/// use mrml::{to_email_template, Options};
+/// use std::collections::HashMap;
+/// let template = to_email_template(r#"
+///     <mjml>
+///         <mj-head>
+///             <mj-title>Hello {{ name }}</mj-title>
+///             <mj-preview>News for {{ name }}</mj-preview>
+///         </mj-head>
+///         <mj-body>
+///             <mj-text>Dear {{ name }}</mj-text>
+///         </mj-body>
+///     </mjml>
+/// "#, Options::default()).unwrap();
+/// let mut values = HashMap::new();
+/// values.insert("name", "Tom & Jerry");
+/// let email = template.render(&values).unwrap();
+/// assert_eq!(email.subject, "Hello Tom & Jerry");
+/// assert_eq!(email.text, "Dear Tom & Jerry\n");
+/// assert!(email.html.contains("News for Tom &amp; Jerry"));
+/// ```
+pub fn to_email_template(input: &str, options: Options) -> Result<EmailTemplate, Error> {
+    use util::template::Template;
+    let element = parse(input, options)?;
+    element
+        .default_template()
+        .map_err(|err| Error::from_mjml(input, err))?;
+    Ok(EmailTemplate {
+        subject: Template::parse_text(&element.get_title()),
+        text: Template::parse_text(&element.get_text()),
+        element,
     })
 }
 
@@ -165,7 +400,7 @@ pub mod tests {
         }
     }
 
-    fn clean_str(input: String) -> String {
+    pub fn clean_str(input: String) -> String {
         input
             .replace("class=\"\"", "")
             .replace("style=\"\"", "")
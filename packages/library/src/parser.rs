@@ -1,11 +1,55 @@
+use std::fmt;
 use xmlparser::{StrSpan, Token, Tokenizer};
 
 pub type Attributes<'a> = Vec<(StrSpan<'a>, StrSpan<'a>)>;
 pub type Children<'a> = Vec<Element<'a>>;
 
+/// byte range of an item in the source template
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+impl<'a> From<&StrSpan<'a>> for Span {
+    fn from(value: &StrSpan<'a>) -> Self {
+        Self::new(value.start(), value.end())
+    }
+}
+
+impl<'a> From<StrSpan<'a>> for Span {
+    fn from(value: StrSpan<'a>) -> Self {
+        Self::from(&value)
+    }
+}
+
+fn token_span(token: &Token) -> Span {
+    match token {
+        Token::Declaration { span, .. }
+        | Token::ProcessingInstruction { span, .. }
+        | Token::Comment { span, .. }
+        | Token::DtdStart { span, .. }
+        | Token::EmptyDtd { span, .. }
+        | Token::EntityDeclaration { span, .. }
+        | Token::DtdEnd { span }
+        | Token::ElementStart { span, .. }
+        | Token::Attribute { span, .. }
+        | Token::ElementEnd { span, .. }
+        | Token::Cdata { span, .. } => span.into(),
+        Token::Text { text } => text.into(),
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    InvalidFormat,
+    /// The template is not a valid xml document at the given position.
+    InvalidFormat(Span),
     /// The input string should be smaller than 4GiB.
     SizeLimit,
     /// Errors detected by the `xmlparser` crate.
@@ -14,6 +58,29 @@ pub enum Error {
     NoRootNode,
 }
 
+impl Error {
+    /// byte range of the error in the template, when known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::InvalidFormat(span) => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidFormat(_) => write!(f, "invalid format"),
+            Error::SizeLimit => write!(f, "input too large"),
+            Error::ParserError(err) => write!(f, "{}", err),
+            Error::NoRootNode => write!(f, "no root node"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<xmlparser::Error> for Error {
     fn from(err: xmlparser::Error) -> Self {
         Error::ParserError(err)
@@ -24,23 +91,35 @@ pub struct Node<'a> {
     pub name: StrSpan<'a>,
     pub attributes: Attributes<'a>,
     pub children: Children<'a>,
+    /// span of the opening tag, from `<` to the end of the element name
+    pub span: StrSpan<'a>,
 }
 
 impl<'a> Node<'a> {
-    pub fn new(name: StrSpan<'a>, attributes: Attributes<'a>, children: Children<'a>) -> Self {
+    pub fn new(
+        name: StrSpan<'a>,
+        attributes: Attributes<'a>,
+        children: Children<'a>,
+        span: StrSpan<'a>,
+    ) -> Self {
         Node {
             name,
             attributes,
             children,
+            span,
         }
     }
 
-    fn parse(parser: &mut Tokenizer<'a>, tag: StrSpan<'a>) -> Result<Self, Error> {
+    fn parse(
+        parser: &mut Tokenizer<'a>,
+        tag: StrSpan<'a>,
+        span: StrSpan<'a>,
+    ) -> Result<Self, Error> {
         let mut attributes = vec![];
         loop {
             let token = match parser.next() {
                 Some(value) => value,
-                None => return Err(Error::InvalidFormat),
+                None => return Err(Error::InvalidFormat(span.into())),
             };
             let token = token?;
             match token {
@@ -54,18 +133,19 @@ impl<'a> Node<'a> {
                 }
                 Token::ElementEnd { end, span: _ } => match end {
                     xmlparser::ElementEnd::Empty => {
-                        return Ok(Node::new(tag, attributes, vec![]));
+                        return Ok(Node::new(tag, attributes, vec![], span));
                     }
                     xmlparser::ElementEnd::Open => {
                         return Ok(Node::new(
                             tag,
                             attributes,
-                            Element::parse_children(parser, tag)?,
+                            Element::parse_children(parser, Some((tag, span)))?,
+                            span,
                         ));
                     }
-                    _ => return Err(Error::InvalidFormat),
+                    _ => return Err(Error::InvalidFormat(span.into())),
                 },
-                _ => return Err(Error::InvalidFormat),
+                other => return Err(Error::InvalidFormat(token_span(&other))),
             };
         }
     }
@@ -80,8 +160,8 @@ impl<'a> Node<'a> {
             Token::ElementStart {
                 local,
                 prefix: _,
-                span: _,
-            } => Node::parse(parser, local),
+                span,
+            } => Node::parse(parser, local, span),
             _ => Err(Error::NoRootNode),
         }
     }
@@ -121,43 +201,62 @@ impl<'a> Element<'a> {
             _ => None,
         }
     }
-    fn parse_children(parser: &mut Tokenizer<'a>, tag: StrSpan<'a>) -> Result<Vec<Self>, Error> {
+    /// byte range of the element in the template
+    pub fn span(&self) -> Span {
+        match self {
+            Element::Comment(value) => value.into(),
+            Element::Node(node) => node.span.into(),
+            Element::Text(value) => value.into(),
+        }
+    }
+
+    /// parse the children of the `parent` tag (name and span), or until the end of
+    /// the input when there is no parent
+    fn parse_children(
+        parser: &mut Tokenizer<'a>,
+        parent: Option<(StrSpan<'a>, StrSpan<'a>)>,
+    ) -> Result<Vec<Self>, Error> {
         let mut children: Vec<Element<'a>> = vec![];
         loop {
-            let token = match parser.next() {
-                Some(value) => value,
+            let token = match (parser.next(), parent) {
+                (Some(value), _) => value,
+                (None, None) => return Ok(children),
                 // end before having the closing element
-                None => return Err(Error::InvalidFormat),
+                (None, Some((_tag, tag_span))) => {
+                    return Err(Error::InvalidFormat(tag_span.into()))
+                }
             };
             let token = token?;
             match token {
                 Token::ElementStart {
                     local,
                     prefix: _,
-                    span: _,
+                    span,
                 } => {
-                    children.push(Element::Node(Node::parse(parser, local)?));
+                    children.push(Element::Node(Node::parse(parser, local, span)?));
                 }
                 Token::Text { text } => {
                     if !text.as_str().trim().is_empty() {
                         children.push(Element::Text(text));
                     }
                 }
-                Token::ElementEnd { end, span: _ } => match end {
+                Token::ElementEnd { end, span } => match end {
                     xmlparser::ElementEnd::Close(_prefix, local) => {
-                        if local.as_str() == tag.as_str() {
-                            return Ok(children);
+                        if let Some((tag, _tag_span)) = parent {
+                            if local.as_str() == tag.as_str() {
+                                return Ok(children);
+                            }
                         }
                         // end before having the closing element
-                        return Err(Error::InvalidFormat);
+                        return Err(Error::InvalidFormat(span.into()));
                     }
-                    _ => return Err(Error::InvalidFormat),
+                    _ => return Err(Error::InvalidFormat(span.into())),
                 },
                 // TODO handle comments
                 Token::Comment { text, span: _ } => {
                     children.push(Element::Comment(text));
                 }
-                _ => return Err(Error::InvalidFormat),
+                other => return Err(Error::InvalidFormat(token_span(&other))),
             };
         }
     }
@@ -188,6 +287,20 @@ pub fn parse(text: &'_ str) -> Result<Node<'_>, Error> {
     Node::parse_root(&mut parser)
 }
 
+/// parse a list of elements without a common root, like the content of an included template
+///
+/// ```rust
+/// let result = mrml::parser::parse_fragment("<mj-section></mj-section><mj-section />");
+/// assert_eq!(result.unwrap().len(), 2);
+/// ```
+pub fn parse_fragment(text: &'_ str) -> Result<Vec<Element<'_>>, Error> {
+    if text.len() > std::u32::MAX as usize {
+        return Err(Error::SizeLimit);
+    }
+    let mut parser = Tokenizer::from_fragment(text, 0..text.len());
+    Element::parse_children(&mut parser, None)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +324,29 @@ mod tests {
         assert!(root.is_ok());
     }
 
+    #[test]
+    fn parse_keeps_spans() {
+        let template = "<mjml><mj-body><mj-text>Hello</mj-text></mj-body></mjml>";
+        let root = parse(template).unwrap();
+        let body = root
+            .children
+            .first()
+            .and_then(|item| item.as_node())
+            .unwrap();
+        let text = body.children.first().unwrap();
+        assert_eq!(text.span(), Span::new(15, 23));
+        assert_eq!(body.span.as_str(), "<mj-body");
+    }
+
+    #[test]
+    fn parse_invalid_format_span() {
+        let template = "<mjml><mj-body></mj-head></mjml>";
+        match parse(template) {
+            Err(Error::InvalidFormat(span)) => assert_eq!(span, Span::new(15, 25)),
+            _ => panic!("should fail with invalid format"),
+        };
+    }
+
     #[test]
     fn parse_with_comment() {
         let root = parse("<mjml><mj-body><!--<a href=\"toto\">yolo</a>--></mj-body></mjml>");
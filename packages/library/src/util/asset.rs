@@ -0,0 +1,229 @@
+//! resolution of the images used by the components, to embed the local ones in the email
+//! as `data:` uris or as `cid:` references to attachments.
+
+use super::mime::base64;
+use crate::elements::body::prelude::BodyComponent;
+use crate::util::attributes::Attributes;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// attributes of the components holding the url of an image
+pub const IMAGE_ATTRIBUTES: [&str; 7] = [
+    "background-url",
+    "icon-unwrapped-url",
+    "icon-wrapped-url",
+    "left-icon",
+    "right-icon",
+    "src",
+    "thumbnails-src",
+];
+
+#[derive(Debug)]
+pub enum AssetResolverError {
+    /// The requested asset doesn't exist.
+    NotFound,
+    /// The resolver refused to load the requested asset.
+    Denied(String),
+    /// The underlying storage failed.
+    Io(std::io::Error),
+    /// Any other error coming from a custom resolver.
+    Other(String),
+}
+
+impl fmt::Display for AssetResolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetResolverError::NotFound => write!(f, "asset not found"),
+            AssetResolverError::Denied(reason) => write!(f, "access denied: {}", reason),
+            AssetResolverError::Io(err) => write!(f, "{}", err),
+            AssetResolverError::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AssetResolverError {}
+
+impl From<std::io::Error> for AssetResolverError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            ErrorKind::NotFound => AssetResolverError::NotFound,
+            _ => AssetResolverError::Io(err),
+        }
+    }
+}
+
+/// how the resolved assets are embedded in the generated html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AssetEmbedding {
+    /// the urls are replaced by `data:` uris containing the assets
+    #[default]
+    DataUri,
+    /// the urls are replaced by `cid:` references to inline attachments
+    ContentId,
+}
+
+/// content of an image used in the template
+#[derive(Clone, Debug)]
+pub struct Asset {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Asset {
+    pub fn new<T: ToString>(content_type: T, data: Vec<u8>) -> Self {
+        Self {
+            content_type: content_type.to_string(),
+            data,
+        }
+    }
+
+    /// ```rust
+    /// use mrml::util::asset::Asset;
+    /// let asset = Asset::new("image/png", b"mrml".to_vec());
+    /// assert_eq!(asset.to_data_uri(), "data:image/png;base64,bXJtbA==");
+    /// ```
+    pub fn to_data_uri(&self) -> String {
+        format!("data:{};base64,{}", self.content_type, base64(&self.data))
+    }
+}
+
+/// resolves the urls of the images used in the template
+///
+/// Returning `None` keeps the url untouched, like for the images hosted on a server.
+pub trait AssetResolver: fmt::Debug + Send + Sync {
+    fn resolve(&self, url: &str) -> Result<Option<Asset>, AssetResolverError>;
+}
+
+/// resolver used by default, keeping every url untouched
+#[derive(Clone, Debug, Default)]
+pub struct NoopAssetResolver;
+
+impl AssetResolver for NoopAssetResolver {
+    fn resolve(&self, _url: &str) -> Result<Option<Asset>, AssetResolverError> {
+        Ok(None)
+    }
+}
+
+/// content type of a file, from its extension
+///
+/// ```rust
+/// use mrml::util::asset::content_type;
+/// assert_eq!(content_type("images/logo.PNG"), "image/png");
+/// assert_eq!(content_type("logo"), "application/octet-stream");
+/// ```
+pub fn content_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "bmp" => "image/bmp",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// resolver reading the images with a local path from the filesystem, below a root directory
+///
+/// The urls with a scheme, like `https://` or `data:`, are kept untouched.
+#[derive(Clone, Debug)]
+pub struct FileAssetResolver {
+    root: PathBuf,
+}
+
+impl FileAssetResolver {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl AssetResolver for FileAssetResolver {
+    fn resolve(&self, url: &str) -> Result<Option<Asset>, AssetResolverError> {
+        if url.is_empty() || url.starts_with("//") || url.contains(':') {
+            return Ok(None);
+        }
+        let relative = Path::new(url.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|item| !matches!(item, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(AssetResolverError::Denied(format!(
+                "{} is outside of {}",
+                url,
+                self.root.display()
+            )));
+        }
+        let data = std::fs::read(self.root.join(relative))?;
+        Ok(Some(Asset::new(content_type(url), data)))
+    }
+}
+
+/// urls of the images used by the element and its children, in order of appearance
+///
+/// The values containing `{{ variable }}` placeholders are skipped, being only known
+/// when the template is rendered.
+pub fn collect_urls(element: &dyn BodyComponent, result: &mut Vec<String>) {
+    for name in IMAGE_ATTRIBUTES.iter() {
+        if let Some(value) = element.get_attribute(name) {
+            if !value.is_empty() && !value.contains("{{") && !result.contains(value) {
+                result.push(value.clone());
+            }
+        }
+    }
+    for child in element.get_children().iter() {
+        collect_urls(child.inner(), result);
+    }
+}
+
+/// replace the urls in the image attributes of the element and its children
+pub fn replace_urls(element: &mut dyn BodyComponent, replacements: &[(String, String)]) {
+    element.update_attributes(&|attributes: &mut Attributes| {
+        for name in IMAGE_ATTRIBUTES.iter() {
+            let replacement = attributes.get(name).and_then(|value| {
+                replacements
+                    .iter()
+                    .find(|(url, _)| url == value)
+                    .map(|(_, replacement)| replacement.clone())
+            });
+            if let Some(value) = replacement {
+                attributes.set(name, value);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_resolver() {
+        let resolver = FileAssetResolver::new("test");
+        assert!(resolver
+            .resolve("https://example.com/a.png")
+            .unwrap()
+            .is_none());
+        assert!(resolver
+            .resolve("data:image/png;base64,AA==")
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            resolver.resolve("../secret.png"),
+            Err(AssetResolverError::Denied(_))
+        ));
+        assert!(matches!(
+            resolver.resolve("missing.png"),
+            Err(AssetResolverError::NotFound)
+        ));
+        let asset = resolver.resolve("assets/pixel.png").unwrap().unwrap();
+        assert_eq!(asset.content_type, "image/png");
+        assert_eq!(&asset.data[1..4], b"PNG");
+    }
+}
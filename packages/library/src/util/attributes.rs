@@ -40,6 +40,10 @@ impl Attributes {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn has<K: ToString>(&self, key: K) -> bool {
         self.0.contains_key(&key.to_string())
     }
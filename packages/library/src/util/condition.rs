@@ -1,3 +1,5 @@
+use regex::Regex;
+
 pub const START_CONDITIONAL_TAG: &str = "<!--[if mso | IE]>";
 pub const START_MSO_CONDITIONAL_TAG: &str = "<!--[if mso]>";
 pub const END_CONDITIONAL_TAG: &str = "<![endif]-->";
@@ -5,6 +7,23 @@ pub const START_NEGATION_CONDITIONAL_TAG: &str = "<!--[if !mso | IE]><!-->";
 pub const START_MSO_NEGATION_CONDITIONAL_TAG: &str = "<!--[if !mso><!-->";
 pub const END_NEGATION_CONDITIONAL_TAG: &str = "<!--<![endif]-->";
 
+lazy_static! {
+    static ref CONDITION_TAGS: Regex = Regex::new(
+        &[
+            START_CONDITIONAL_TAG,
+            START_MSO_CONDITIONAL_TAG,
+            START_NEGATION_CONDITIONAL_TAG,
+            END_NEGATION_CONDITIONAL_TAG,
+            END_CONDITIONAL_TAG,
+        ]
+        .iter()
+        .map(|tag| regex::escape(tag))
+        .collect::<Vec<_>>()
+        .join("|")
+    )
+    .unwrap();
+}
+
 pub fn conditional_tag(input: String) -> String {
     format!("{}{}{}", START_CONDITIONAL_TAG, input, END_CONDITIONAL_TAG)
 }
@@ -29,3 +48,57 @@ pub fn mso_negation_conditional_tag(input: String) -> String {
         START_MSO_NEGATION_CONDITIONAL_TAG, input, END_NEGATION_CONDITIONAL_TAG
     )
 }
+
+/// merge the consecutive conditional comments of the same kind, like `mergeOutlookConditionnals` in mjml
+///
+/// ```rust
+/// use mrml::util::condition::merge_conditionals;
+/// assert_eq!(
+///     merge_conditionals("<!--[if mso | IE]><table><![endif]-->\n<!--[if mso | IE]><tr><![endif]-->"),
+///     "<!--[if mso | IE]><table><tr><![endif]-->"
+/// );
+/// ```
+pub fn merge_conditionals(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut position = 0;
+    let mut opened: Option<&str> = None;
+    // start and end of the last closing tag, with the opening tag of its block
+    let mut closed: Option<(usize, usize, &str)> = None;
+    for item in CONDITION_TAGS.find_iter(html) {
+        let tag = item.as_str();
+        if tag == END_CONDITIONAL_TAG || tag == END_NEGATION_CONDITIONAL_TAG {
+            closed = opened.take().map(|kind| (item.start(), item.end(), kind));
+            continue;
+        }
+        if let Some((start, end, kind)) = closed.take() {
+            if kind == tag && html[end..item.start()].trim().is_empty() {
+                result.push_str(&html[position..start]);
+                position = item.end();
+            }
+        }
+        opened = Some(tag);
+    }
+    result.push_str(&html[position..]);
+    result
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_same_kind_only() {
+        let html = String::new()
+            + &conditional_tag("<table>".into())
+            + &conditional_tag("<tr>".into())
+            + &mso_conditional_tag("<td>".into())
+            + &negation_conditional_tag("<div>".into())
+            + "  "
+            + &negation_conditional_tag("</div>".into())
+            + &conditional_tag("</tr>".into());
+        assert_eq!(
+            merge_conditionals(&html),
+            "<!--[if mso | IE]><table><tr><![endif]--><!--[if mso]><td><![endif]--><!--[if !mso | IE]><!--><div></div><!--<![endif]--><!--[if mso | IE]></tr><![endif]-->"
+        );
+    }
+}
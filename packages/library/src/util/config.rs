@@ -0,0 +1,327 @@
+//! configuration file shared by the command line and the library, so that every caller
+//! renders the templates the same way
+//!
+//! The file is named `mrml.toml` or `.mrmlrc` and written in toml, with a `[fonts]` table
+//! associating the font names to their url. The relative paths are resolved from the
+//! directory of the file.
+//!
+//! ```toml
+//! breakpoint = "320px"
+//! keep_comments = false
+//! minify = true
+//! validation_level = "strict"
+//! include_root = "partials"
+//! styles = [".footer { color: #888; }"]
+//!
+//! [fonts]
+//! "Open Sans" = "https://fonts.example.com/open-sans.css"
+//! ```
+
+use crate::util::include::FileIncludeLoader;
+use crate::util::size::Size;
+use crate::validation::ValidationLevel;
+use crate::Options;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use toml::value::{Table, Value};
+
+/// names of the configuration files, by order of precedence in a same directory
+pub const CONFIG_FILES: [&str; 2] = ["mrml.toml", ".mrmlrc"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// the file cannot be read
+    Io(PathBuf, std::io::Error),
+    /// the file is not valid toml, has an unknown option or a value of the wrong type
+    Toml(toml::de::Error),
+    /// the value doesn't have the format expected by the option
+    InvalidValue { key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "unable to read {}: {}", path.display(), err),
+            ConfigError::Toml(err) => write!(f, "{}", err),
+            ConfigError::InvalidValue { key, message } => {
+                write!(f, "invalid value for {}: {}", key, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigError::Toml(err)
+    }
+}
+
+/// content of the file, before the values are checked
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    breakpoint: Option<Value>,
+    keep_comments: Option<bool>,
+    minify: Option<bool>,
+    validation_level: Option<String>,
+    id_seed: Option<u64>,
+    include_root: Option<PathBuf>,
+    include_max_depth: Option<usize>,
+    styles: Option<Value>,
+    fonts: Option<Table>,
+}
+
+fn invalid(key: &str, message: &str) -> ConfigError {
+    ConfigError::InvalidValue {
+        key: key.into(),
+        message: message.into(),
+    }
+}
+
+fn as_size(key: &str, value: &Value) -> Result<Size, ConfigError> {
+    match value {
+        Value::Integer(value) => Ok(Size::Pixel(*value as f32)),
+        Value::Float(value) => Ok(Size::Pixel(*value as f32)),
+        Value::String(value) => {
+            Size::from_str(value).map_err(|_| invalid(key, "expected a size like 480px"))
+        }
+        _ => Err(invalid(key, "expected a size like 480px")),
+    }
+}
+
+fn as_string(key: &str, value: &Value) -> Result<String, ConfigError> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| invalid(key, "expected a string"))
+}
+
+/// options defined by a configuration file, the missing ones keeping their value
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub breakpoint: Option<Size>,
+    pub keep_comments: Option<bool>,
+    pub minify: Option<bool>,
+    pub validation_level: Option<ValidationLevel>,
+    pub id_seed: Option<u64>,
+    /// directory from which the `mj-include` are loaded
+    pub include_root: Option<PathBuf>,
+    pub include_max_depth: Option<usize>,
+    /// css added to the one of the options
+    pub styles: Vec<String>,
+    /// fonts registered in addition to the ones of the options
+    pub fonts: Vec<(String, String)>,
+}
+
+impl Config {
+    /// parses the content of a configuration file, the relative paths being resolved
+    /// from the given directory
+    ///
+    /// ```rust
+    /// use mrml::util::config::Config;
+    /// use mrml::util::size::Size;
+    /// let config = Config::parse("breakpoint = \"320px\"\nminify = true", ".").unwrap();
+    /// assert_eq!(config.breakpoint, Some(Size::Pixel(320.0)));
+    /// assert_eq!(config.minify, Some(true));
+    /// ```
+    pub fn parse<P: AsRef<Path>>(content: &str, directory: P) -> Result<Self, ConfigError> {
+        let file: ConfigFile = toml::from_str(content)?;
+        let mut result = Self {
+            breakpoint: None,
+            keep_comments: file.keep_comments,
+            minify: file.minify,
+            validation_level: None,
+            id_seed: file.id_seed,
+            include_root: file.include_root.map(|path| directory.as_ref().join(path)),
+            include_max_depth: file.include_max_depth,
+            styles: vec![],
+            fonts: vec![],
+        };
+        if let Some(ref value) = file.breakpoint {
+            result.breakpoint = Some(as_size("breakpoint", value)?);
+        }
+        if let Some(ref value) = file.validation_level {
+            result.validation_level = Some(
+                ValidationLevel::from_str(value)
+                    .map_err(|err| invalid("validation_level", &err))?,
+            );
+        }
+        match file.styles {
+            None => (),
+            Some(Value::Array(items)) => {
+                for item in items.iter() {
+                    result.styles.push(as_string("styles", item)?);
+                }
+            }
+            Some(Value::String(item)) => result.styles.push(item),
+            Some(_) => return Err(invalid("styles", "expected a list of strings")),
+        };
+        for (name, url) in file.fonts.unwrap_or_default().iter() {
+            result.fonts.push((name.clone(), as_string("fonts", url)?));
+        }
+        Ok(result)
+    }
+
+    /// reads a configuration file, the relative paths being resolved from its directory
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        Self::parse(&content, directory)
+    }
+
+    /// overrides the given options with the ones of the configuration
+    pub fn apply(&self, options: &mut Options) {
+        if let Some(ref value) = self.breakpoint {
+            options.breakpoint = value.clone();
+        }
+        if let Some(value) = self.keep_comments {
+            options.keep_comments = value;
+        }
+        if let Some(value) = self.minify {
+            options.minify = value;
+        }
+        if let Some(value) = self.validation_level {
+            options.validation_level = value;
+        }
+        if let Some(value) = self.id_seed {
+            options.id_seed = value;
+        }
+        if let Some(ref root) = self.include_root {
+            options.include_loader = Arc::new(FileIncludeLoader::new(root.clone()));
+        }
+        if let Some(value) = self.include_max_depth {
+            options.include_max_depth = value;
+        }
+        options.styles.extend(self.styles.iter().cloned());
+        for (name, url) in self.fonts.iter() {
+            options.fonts.add(name, url);
+        }
+    }
+}
+
+/// closest configuration file in the given directory or one of its parents
+pub fn discover<P: AsRef<Path>>(directory: P) -> Option<PathBuf> {
+    let directory = directory.as_ref();
+    let directory = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf());
+    directory.ancestors().find_map(|current| {
+        CONFIG_FILES
+            .iter()
+            .map(|name| current.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        let config = Config::parse(
+            r#"
+# shared by the whole team
+breakpoint = 320
+keep_comments = false # no comment
+validation_level = 'strict'
+id_seed = 42
+include_root = "partials"
+styles = [".a { color: red; }", ".b { content: \"]\"; }"]
+
+[fonts]
+"Open Sans" = "https://example.com/open-sans.css"
+Inter = "https://example.com/inter.css"
+"#,
+            "templates",
+        )
+        .unwrap();
+        assert_eq!(config.breakpoint, Some(Size::Pixel(320.0)));
+        assert_eq!(config.keep_comments, Some(false));
+        assert_eq!(config.minify, None);
+        assert_eq!(config.validation_level, Some(ValidationLevel::Strict));
+        assert_eq!(config.id_seed, Some(42));
+        assert_eq!(
+            config.include_root,
+            Some(PathBuf::from("templates/partials"))
+        );
+        assert_eq!(
+            config.styles,
+            vec![".a { color: red; }", ".b { content: \"]\"; }"]
+        );
+        assert_eq!(config.fonts.len(), 2);
+        assert_eq!(config.fonts[0].0, "Open Sans");
+
+        let mut options = Options::default();
+        config.apply(&mut options);
+        assert_eq!(options.breakpoint, Size::Pixel(320.0));
+        assert!(!options.keep_comments);
+        assert!(!options.minify);
+        assert_eq!(options.id_seed, 42);
+        assert_eq!(
+            options.fonts.get("Open Sans").unwrap(),
+            "https://example.com/open-sans.css"
+        );
+        assert!(options.fonts.get("Roboto").is_some());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Config::parse("minify = true\nbreakpoint 320", ".").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected an equals, found an identifier at line 2 column 12"
+        );
+        let error = Config::parse("title = \"unterminated", ".").unwrap_err();
+        assert_eq!(error.to_string(), "unterminated string at line 1 column 9");
+        let error = Config::parse("minfy = true", ".").unwrap_err();
+        assert!(error.to_string().starts_with("unknown field `minfy`"));
+        let error = Config::parse("minify = \"yes\"", ".").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid type: string \"yes\", expected a boolean for key `minify` at line 1 column 10"
+        );
+        let error = Config::parse("validation_level = \"lax\"", ".").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value for validation_level: unknown validation level \"lax\""
+        );
+        let error = Config::parse("breakpoint = true", ".").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value for breakpoint: expected a size like 480px"
+        );
+        let error = Config::parse("[colors]", ".").unwrap_err();
+        assert!(error.to_string().starts_with("unknown field `colors`"));
+    }
+
+    #[test]
+    fn discover_file() {
+        let path = discover("test/config/nested").unwrap();
+        assert!(path.ends_with("test/config/mrml.toml"));
+        let options = Options::from_config_file(&path).unwrap();
+        assert_eq!(options.breakpoint, Size::Pixel(320.0));
+        assert!(!options.keep_comments);
+        let result = crate::to_html(
+            r#"<mjml><mj-body><mj-include path="header.mjml" /></mj-body></mjml>"#,
+            options,
+        )
+        .unwrap();
+        assert!(result.contains("(min-width:320px)"));
+        assert!(result.contains("Header from the config"));
+        assert!(matches!(
+            Config::from_file("test/config/missing.toml"),
+            Err(ConfigError::Io(_, _))
+        ));
+    }
+}
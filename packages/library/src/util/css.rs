@@ -0,0 +1,595 @@
+//! minimal css parser used to inline the styles declared with `<mj-style inline="inline">`
+//!
+//! Only the selectors that can be resolved without a browser are supported: type, universal,
+//! class, id and attribute selectors combined with the descendant or child combinators.
+//! Everything else (at-rules, pseudo classes, sibling combinators...) is kept as is to be
+//! rendered in a `<style>` element.
+
+use std::fmt;
+
+/// single `name: value` declaration of a css block
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub value: String,
+    pub important: bool,
+}
+
+impl Declaration {
+    fn parse(input: &str) -> Option<Self> {
+        let index = input.find(':')?;
+        let name = input[..index].trim().to_lowercase();
+        let mut value = input[index + 1..].trim();
+        if name.is_empty() || value.is_empty() {
+            return None;
+        }
+        let important = match value.rfind('!') {
+            Some(position)
+                if value[position + 1..]
+                    .trim()
+                    .eq_ignore_ascii_case("important") =>
+            {
+                value = value[..position].trim_end();
+                true
+            }
+            _ => false,
+        };
+        Some(Self {
+            name,
+            value: value.to_string(),
+            important,
+        })
+    }
+}
+
+impl fmt::Display for Declaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.important {
+            write!(f, "{}:{} !important;", self.name, self.value)
+        } else {
+            write!(f, "{}:{};", self.name, self.value)
+        }
+    }
+}
+
+/// split the content of a css block, or of a `style` attribute, into declarations
+///
+/// ```rust
+/// use mrml::util::css::parse_declarations;
+/// let result = parse_declarations("color: red; background: url('a;b.png') !important");
+/// assert_eq!(result.len(), 2);
+/// assert_eq!(result[1].value, "url('a;b.png')");
+/// assert!(result[1].important);
+/// ```
+pub fn parse_declarations(input: &str) -> Vec<Declaration> {
+    split_outside(input, ';')
+        .into_iter()
+        .filter_map(Declaration::parse)
+        .collect()
+}
+
+/// split the input on the given separator when not in quotes, parenthesis or brackets
+fn split_outside(input: &str, separator: char) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (index, character) in input.char_indices() {
+        match (quote, character) {
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (None, '(') | (None, '[') => depth += 1,
+            (None, ')') | (None, ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                result.push(&input[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => (),
+        };
+    }
+    result.push(&input[start..]);
+    result
+}
+
+/// element as seen by the selectors, with its lowercase name and its attributes
+#[derive(Clone, Debug, Default)]
+pub struct ElementData {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl ElementData {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_class(&self, name: &str) -> bool {
+        self.attribute("class")
+            .map(|value| value.split_whitespace().any(|item| item == name))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum AttributeOperator {
+    Exists,
+    Equals(String),
+    Includes(String),
+    DashMatch(String),
+    Prefix(String),
+    Suffix(String),
+    Substring(String),
+}
+
+impl AttributeOperator {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            AttributeOperator::Exists => true,
+            AttributeOperator::Equals(expected) => value == expected,
+            AttributeOperator::Includes(expected) => {
+                value.split_whitespace().any(|item| item == expected)
+            }
+            AttributeOperator::DashMatch(expected) => {
+                value == expected || value.starts_with(&format!("{}-", expected))
+            }
+            AttributeOperator::Prefix(expected) => {
+                !expected.is_empty() && value.starts_with(expected.as_str())
+            }
+            AttributeOperator::Suffix(expected) => {
+                !expected.is_empty() && value.ends_with(expected.as_str())
+            }
+            AttributeOperator::Substring(expected) => {
+                !expected.is_empty() && value.contains(expected.as_str())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum SimpleSelector {
+    Type(String),
+    Universal,
+    Class(String),
+    Id(String),
+    Attribute(String, AttributeOperator),
+}
+
+impl SimpleSelector {
+    fn matches(&self, element: &ElementData) -> bool {
+        match self {
+            SimpleSelector::Type(name) => element.name == *name,
+            SimpleSelector::Universal => true,
+            SimpleSelector::Class(name) => element.has_class(name),
+            SimpleSelector::Id(name) => element.attribute("id") == Some(name.as_str()),
+            SimpleSelector::Attribute(name, operator) => element
+                .attribute(name)
+                .map(|value| operator.matches(value))
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+/// specificity of a selector, as (ids, classes and attributes, types)
+pub type Specificity = (u32, u32, u32);
+
+/// complex selector, like `.header > td a[href]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selector {
+    compounds: Vec<Vec<SimpleSelector>>,
+    combinators: Vec<Combinator>,
+}
+
+fn is_name_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '-' || character == '_'
+}
+
+fn read_name(input: &[char], position: &mut usize) -> Option<String> {
+    let start = *position;
+    while *position < input.len() && is_name_char(input[*position]) {
+        *position += 1;
+    }
+    if start == *position {
+        None
+    } else {
+        Some(input[start..*position].iter().collect())
+    }
+}
+
+fn read_attribute(input: &[char], position: &mut usize) -> Option<SimpleSelector> {
+    let end = input[*position..].iter().position(|c| *c == ']')? + *position;
+    let content: String = input[*position..end].iter().collect();
+    *position = end + 1;
+    let (name, operator) = match content.find('=') {
+        None => (content.trim(), AttributeOperator::Exists),
+        Some(index) => {
+            let (name, prefix) = match content[..index].chars().last() {
+                Some(c) if "~|^$*".contains(c) => (&content[..index - 1], Some(c)),
+                _ => (&content[..index], None),
+            };
+            let value = content[index + 1..].trim();
+            let value = if value.len() >= 2
+                && (value.starts_with('"') && value.ends_with('"')
+                    || value.starts_with('\'') && value.ends_with('\''))
+            {
+                value[1..value.len() - 1].to_string()
+            } else if !value.is_empty() && value.chars().all(is_name_char) {
+                value.to_string()
+            } else {
+                // case sensitivity flags or invalid values are not supported
+                return None;
+            };
+            let operator = match prefix {
+                None => AttributeOperator::Equals(value),
+                Some('~') => AttributeOperator::Includes(value),
+                Some('|') => AttributeOperator::DashMatch(value),
+                Some('^') => AttributeOperator::Prefix(value),
+                Some('$') => AttributeOperator::Suffix(value),
+                _ => AttributeOperator::Substring(value),
+            };
+            (name.trim(), operator)
+        }
+    };
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return None;
+    }
+    Some(SimpleSelector::Attribute(name.to_lowercase(), operator))
+}
+
+impl Selector {
+    /// parse a single selector, returns `None` when it cannot be used to inline styles
+    ///
+    /// ```rust
+    /// use mrml::util::css::Selector;
+    /// assert!(Selector::parse("table.header > td a[href^='https']").is_some());
+    /// assert!(Selector::parse("a:hover").is_none());
+    /// ```
+    pub fn parse(input: &str) -> Option<Self> {
+        let input: Vec<char> = input.trim().chars().collect();
+        if input.is_empty() {
+            return None;
+        }
+        let mut compounds = vec![];
+        let mut combinators = vec![];
+        let mut current = vec![];
+        let mut position = 0;
+        while position < input.len() {
+            match input[position] {
+                '*' => {
+                    position += 1;
+                    current.push(SimpleSelector::Universal);
+                }
+                '.' => {
+                    position += 1;
+                    current.push(SimpleSelector::Class(read_name(&input, &mut position)?));
+                }
+                '#' => {
+                    position += 1;
+                    current.push(SimpleSelector::Id(read_name(&input, &mut position)?));
+                }
+                '[' => {
+                    position += 1;
+                    current.push(read_attribute(&input, &mut position)?);
+                }
+                c if c.is_whitespace() || c == '>' => {
+                    let mut combinator = Combinator::Descendant;
+                    while position < input.len()
+                        && (input[position].is_whitespace() || input[position] == '>')
+                    {
+                        if input[position] == '>' {
+                            if combinator == Combinator::Child {
+                                return None;
+                            }
+                            combinator = Combinator::Child;
+                        }
+                        position += 1;
+                    }
+                    if current.is_empty() {
+                        return None;
+                    }
+                    compounds.push(current);
+                    combinators.push(combinator);
+                    current = vec![];
+                }
+                c if is_name_char(c) && current.is_empty() => {
+                    let name = read_name(&input, &mut position)?;
+                    current.push(SimpleSelector::Type(name.to_lowercase()));
+                }
+                // pseudo classes, sibling combinators, namespaces...
+                _ => return None,
+            };
+        }
+        if current.is_empty() {
+            return None;
+        }
+        compounds.push(current);
+        Some(Self {
+            compounds,
+            combinators,
+        })
+    }
+
+    /// parse a list of selectors separated by commas, the ones that cannot be used to
+    /// inline styles being ignored
+    ///
+    /// ```rust
+    /// use mrml::util::css::Selector;
+    /// assert_eq!(Selector::parse_list("p, a[title='a,b'], :is(h1, h2)").len(), 2);
+    /// ```
+    pub fn parse_list(input: &str) -> Vec<Self> {
+        split_outside(input, ',')
+            .into_iter()
+            .filter_map(Self::parse)
+            .collect()
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        self.compounds
+            .iter()
+            .flatten()
+            .fold((0, 0, 0), |(a, b, c), item| match item {
+                SimpleSelector::Id(_) => (a + 1, b, c),
+                SimpleSelector::Class(_) | SimpleSelector::Attribute(_, _) => (a, b + 1, c),
+                SimpleSelector::Type(_) => (a, b, c + 1),
+                SimpleSelector::Universal => (a, b, c),
+            })
+    }
+
+    fn matches_compound(&self, index: usize, element: &ElementData) -> bool {
+        self.compounds[index]
+            .iter()
+            .all(|item| item.matches(element))
+    }
+
+    fn matches_at(&self, index: usize, stack: &[ElementData], position: usize) -> bool {
+        if !self.matches_compound(index, &stack[position]) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+        match self.combinators[index - 1] {
+            Combinator::Child => position > 0 && self.matches_at(index - 1, stack, position - 1),
+            Combinator::Descendant => {
+                (0..position).any(|parent| self.matches_at(index - 1, stack, parent))
+            }
+        }
+    }
+
+    /// check if the last element of the stack matches, the previous ones being its ancestors
+    pub fn matches(&self, stack: &[ElementData]) -> bool {
+        if stack.is_empty() {
+            false
+        } else {
+            self.matches_at(self.compounds.len() - 1, stack, stack.len() - 1)
+        }
+    }
+}
+
+/// rule with a single selector that can be inlined
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub selector: Selector,
+    pub declarations: Vec<Declaration>,
+}
+
+/// result of parsing a stylesheet, split between what can be inlined and what can't
+#[derive(Clone, Debug, Default)]
+pub struct Stylesheet {
+    pub rules: Vec<Rule>,
+    pub remaining: Vec<String>,
+}
+
+fn strip_comments(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// find the index of the closing brace matching the block opened just before `input`
+fn find_block_end(input: &str) -> usize {
+    let mut depth = 1;
+    for (index, character) in input.char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => (),
+        };
+    }
+    input.len()
+}
+
+impl Stylesheet {
+    /// parse a stylesheet, the invalid parts are ignored
+    ///
+    /// ```rust
+    /// use mrml::util::css::Stylesheet;
+    /// let sheet = Stylesheet::parse(".red, a:hover { color: red; } @media (max-width: 480px) { .red { color: blue; } }");
+    /// assert_eq!(sheet.rules.len(), 1);
+    /// assert_eq!(sheet.remaining.len(), 2);
+    /// ```
+    pub fn parse(input: &str) -> Self {
+        let content = strip_comments(input);
+        let mut result = Self::default();
+        let mut rest = content.as_str();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let open = rest.find('{');
+            let semicolon = rest.find(';');
+            // statement at-rules like @import end with a semicolon
+            if let (true, Some(semicolon)) = (rest.starts_with('@'), semicolon) {
+                if open.map(|open| semicolon < open).unwrap_or(true) {
+                    result.remaining.push(rest[..=semicolon].trim().to_string());
+                    rest = &rest[semicolon + 1..];
+                    continue;
+                }
+            }
+            let open = match open {
+                Some(value) => value,
+                None => break,
+            };
+            let end = open + 1 + find_block_end(&rest[open + 1..]);
+            let prelude = rest[..open].trim();
+            let block = &rest[open + 1..end.min(rest.len())];
+            if prelude.starts_with('@') {
+                result
+                    .remaining
+                    .push(format!("{} {{ {} }}", prelude, block.trim()));
+            } else {
+                result.add_rule(prelude, block);
+            }
+            rest = if end < rest.len() {
+                &rest[end + 1..]
+            } else {
+                ""
+            };
+        }
+        result
+    }
+
+    fn add_rule(&mut self, prelude: &str, block: &str) {
+        let declarations = parse_declarations(block);
+        if declarations.is_empty() {
+            return;
+        }
+        let mut others = vec![];
+        for item in split_outside(prelude, ',') {
+            match Selector::parse(item) {
+                Some(selector) => self.rules.push(Rule {
+                    selector,
+                    declarations: declarations.clone(),
+                }),
+                None => others.push(item.trim()),
+            };
+        }
+        if !others.is_empty() {
+            let content: Vec<String> = declarations.iter().map(|item| item.to_string()).collect();
+            self.remaining
+                .push(format!("{} {{ {} }}", others.join(", "), content.join(" ")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, attributes: &[(&str, &str)]) -> ElementData {
+        ElementData {
+            name: name.into(),
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn declaration_important() {
+        let result = parse_declarations("color: red !important;;font-weight:bold");
+        assert_eq!(result.len(), 2);
+        assert!(result[0].important);
+        assert_eq!(result[0].value, "red");
+        assert_eq!(result[0].to_string(), "color:red !important;");
+        assert!(!result[1].important);
+    }
+
+    #[test]
+    fn selector_specificity() {
+        assert_eq!(Selector::parse("div").unwrap().specificity(), (0, 0, 1));
+        assert_eq!(Selector::parse("*").unwrap().specificity(), (0, 0, 0));
+        assert_eq!(
+            Selector::parse("#main .red a[href]").unwrap().specificity(),
+            (1, 2, 1)
+        );
+    }
+
+    #[test]
+    fn selector_unsupported() {
+        assert!(Selector::parse("a + b").is_none());
+        assert!(Selector::parse("a ~ b").is_none());
+        assert!(Selector::parse("a::before").is_none());
+        assert!(Selector::parse("a >> b").is_none());
+        assert!(Selector::parse("[data-x=\"y\" i]").is_none());
+    }
+
+    #[test]
+    fn selector_matches() {
+        let stack = vec![
+            element("table", &[("id", "main"), ("class", "header big")]),
+            element("tr", &[]),
+            element("td", &[("align", "left")]),
+            element("a", &[("href", "https://example.com"), ("lang", "en-US")]),
+        ];
+        assert!(Selector::parse("a").unwrap().matches(&stack));
+        assert!(Selector::parse("A").unwrap().matches(&stack));
+        assert!(Selector::parse("#main a").unwrap().matches(&stack));
+        assert!(Selector::parse(".header td > a").unwrap().matches(&stack));
+        assert!(Selector::parse("table.big.header a")
+            .unwrap()
+            .matches(&stack));
+        assert!(Selector::parse("td[align=left] > *")
+            .unwrap()
+            .matches(&stack));
+        assert!(Selector::parse("a[href^='https']").unwrap().matches(&stack));
+        assert!(Selector::parse("a[href$=\".com\"]")
+            .unwrap()
+            .matches(&stack));
+        assert!(Selector::parse("a[href*=example]").unwrap().matches(&stack));
+        assert!(Selector::parse("a[lang|=en]").unwrap().matches(&stack));
+        assert!(Selector::parse(".big a").unwrap().matches(&stack));
+        assert!(!Selector::parse("table > a").unwrap().matches(&stack));
+        assert!(!Selector::parse("td").unwrap().matches(&stack));
+        assert!(!Selector::parse(".small a").unwrap().matches(&stack));
+        assert!(!Selector::parse("a[href^=http]")
+            .unwrap()
+            .matches(&stack[..3]));
+    }
+
+    #[test]
+    fn stylesheet_split() {
+        let sheet = Stylesheet::parse(
+            r#"
+            @import url(https://example.com/style.css);
+            /* comment { with: braces } */
+            .red, .blue:hover { color: red; }
+            @media (max-width: 480px) { .red { color: blue; } }
+            .empty { }
+            "#,
+        );
+        assert_eq!(sheet.rules.len(), 1);
+        assert_eq!(
+            sheet.remaining,
+            vec![
+                "@import url(https://example.com/style.css);".to_string(),
+                ".blue:hover { color:red; }".to_string(),
+                "@media (max-width: 480px) { .red { color: blue; } }".to_string(),
+            ]
+        );
+    }
+}
@@ -1,20 +1,68 @@
+use super::asset::{Asset, AssetEmbedding, AssetResolver, AssetResolverError};
 use super::attributes::{Attributes, Merge};
+use super::css::Rule;
 use super::fonts::FontRegistry;
+use super::id::IdGenerator;
+use super::include::{join_path, IncludeLoader};
+use super::link::{Link, LinkCounter, LinkRewriter};
 use super::size::Size;
-use crate::parser::Node;
-use crate::Options;
+use crate::elements::error::IncludeError;
+use crate::{Options, RenderOptions};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::string::ToString;
+use std::sync::Arc;
 
 #[derive(Clone, Debug, Default)]
 pub struct DefaultAttributes {
+    /// defaults implied by the attributes of the `mjml` element, like its direction
+    root: HashMap<String, Attributes>,
     all: Attributes,
     classes: HashMap<String, Attributes>,
+    /// defaults of the elements nested in an element having the class, like
+    /// `<mj-class name="blue"><mj-text color="blue" /></mj-class>`
+    class_elements: HashMap<String, HashMap<String, Attributes>>,
     elements: HashMap<String, Attributes>,
+    /// classes of the closest parent having a `mj-class` attribute, while resolving the body
+    scope: Vec<String>,
 }
 
 impl DefaultAttributes {
+    pub fn all(&self) -> &Attributes {
+        &self.all
+    }
+
+    pub fn classes(&self) -> &HashMap<String, Attributes> {
+        &self.classes
+    }
+
+    pub fn elements(&self) -> &HashMap<String, Attributes> {
+        &self.elements
+    }
+
+    pub fn class_elements(&self) -> &HashMap<String, HashMap<String, Attributes>> {
+        &self.class_elements
+    }
+
+    /// merge the defaults of another `mj-attributes` block, its values taking precedence
+    pub fn merge(&mut self, other: &DefaultAttributes) {
+        self.all.merge(&other.all);
+        for (name, attributes) in other.root.iter() {
+            self.add_root_content(name, attributes.inner().iter());
+        }
+        for (name, attributes) in other.classes.iter() {
+            self.add_class_content(name, attributes.inner().iter());
+        }
+        for (name, elements) in other.class_elements.iter() {
+            for (tag, attributes) in elements.iter() {
+                self.add_class_element_content(name, tag, attributes.inner().iter());
+            }
+        }
+        for (name, attributes) in other.elements.iter() {
+            self.add_element_content(name, attributes.inner().iter());
+        }
+    }
+
     pub fn add_all_content<K, V, I>(&mut self, items: I)
     where
         K: ToString,
@@ -44,6 +92,19 @@ impl DefaultAttributes {
         }
     }
 
+    /// add defaults to an element with a lower priority than the ones of `mj-attributes`
+    pub fn add_root_content<K, V, I>(&mut self, name: &str, items: I)
+    where
+        K: ToString,
+        V: ToString,
+        I: Iterator<Item = (K, V)>,
+    {
+        let container = self.root.entry(name.to_string()).or_default();
+        for (key, value) in items {
+            container.set(key, value);
+        }
+    }
+
     fn get_mut_class(&mut self, name: &str) -> &mut Attributes {
         if !self.classes.contains_key(name) {
             self.classes.insert(name.to_string(), Attributes::default());
@@ -63,6 +124,48 @@ impl DefaultAttributes {
         }
     }
 
+    pub fn add_class_element_content<K, V, I>(&mut self, class: &str, tag: &str, items: I)
+    where
+        K: ToString,
+        V: ToString,
+        I: Iterator<Item = (K, V)>,
+    {
+        self.get_mut_class(class);
+        let container = self
+            .class_elements
+            .entry(class.to_string())
+            .or_default()
+            .entry(tag.to_string())
+            .or_default();
+        for (key, value) in items {
+            container.set(key, value);
+        }
+    }
+
+    /// scope in which the children of an element with the given `mj-class` attribute are
+    /// resolved, `None` when it doesn't change the defaults of the current scope
+    pub fn child_scope(&self, classes: Option<&String>) -> Option<Vec<String>> {
+        let classes: Vec<String> = match classes {
+            Some(value) => value.split_whitespace().map(String::from).collect(),
+            None => return None,
+        };
+        let nested = |list: &[String]| -> Vec<String> {
+            list.iter()
+                .filter(|name| self.class_elements.contains_key(name.as_str()))
+                .cloned()
+                .collect()
+        };
+        if nested(&classes) == nested(&self.scope) {
+            None
+        } else {
+            Some(classes)
+        }
+    }
+
+    pub fn set_scope(&mut self, classes: Vec<String>) {
+        self.scope = classes;
+    }
+
     pub fn set_element_attributes(&self, name: &str, other: Attributes) -> Attributes {
         let result = other.concat(&self.all);
         match self.elements.get(name) {
@@ -78,23 +181,43 @@ impl DefaultAttributes {
         }
     }
 
-    pub fn get_attributes<'a>(&self, node: &Node<'a>, base: Attributes) -> Attributes {
-        let tag_name = node.name.as_str();
-        let mut result = base.concat(&self.all);
+    /// compute the default attributes of an element, depending on its tag name and its
+    /// `mj-class` attribute
+    pub fn get_attributes(
+        &self,
+        tag_name: &str,
+        attributes: &Attributes,
+        base: Attributes,
+    ) -> Attributes {
+        let mut result = match self.root.get(tag_name) {
+            Some(root) => base.concat(root),
+            None => base,
+        };
+        result.merge(&self.all);
         if let Some(element) = self.elements.get(tag_name) {
             result.merge(element);
         }
-        if let Some(classes) = node
-            .attributes
-            .iter()
-            .find(|(key, _value)| key.as_str() == "mj-class")
-            .map(|(_key, value)| value.as_str().split(' ').collect::<Vec<&str>>())
-        {
-            for classname in classes {
+        if let Some(classes) = attributes.get("mj-class") {
+            let mut css_classes: Vec<&str> = vec![];
+            for classname in classes.split_whitespace() {
                 if let Some(attrs) = self.classes.get(classname) {
                     result.merge(attrs);
+                    css_classes.extend(attrs.get("css-class").map(|value| value.as_str()));
                 }
             }
+            // the css classes of the mj-classes are combined, like in mjml
+            if css_classes.len() > 1 {
+                result.set("css-class", css_classes.join(" "));
+            }
+        }
+        for classname in self.scope.iter() {
+            if let Some(attrs) = self
+                .class_elements
+                .get(classname)
+                .and_then(|elements| elements.get(tag_name))
+            {
+                result.merge(attrs);
+            }
         }
         result
     }
@@ -102,15 +225,29 @@ impl DefaultAttributes {
 
 #[derive(Clone, Debug)]
 pub struct Header {
+    asset_embedding: AssetEmbedding,
+    asset_resolver: Arc<dyn AssetResolver>,
     breakpoint: Size,
     default_attributes: DefaultAttributes,
-    font_families: HashSet<String>,
+    direction: Option<String>,
+    font_families: Vec<String>,
     font_registry: FontRegistry,
+    html_attributes: Vec<(String, Vec<(String, String)>)>,
+    include_loader: Arc<dyn IncludeLoader>,
+    include_max_depth: usize,
+    include_stack: Vec<String>,
+    inline_rules: Vec<Rule>,
+    lang: Option<String>,
+    link_counter: LinkCounter,
+    link_rewriter: Arc<dyn LinkRewriter>,
     keep_comments: bool,
-    media_queries: HashMap<String, Size>,
+    force_owa_desktop: bool,
+    id_generator: IdGenerator,
+    media_queries: Vec<(String, Size)>,
+    minify: bool,
     preview: Option<String>,
     title: Option<String>,
-    styles: HashSet<String>,
+    styles: Vec<String>,
 }
 
 impl Header {
@@ -118,6 +255,46 @@ impl Header {
         self.keep_comments
     }
 
+    pub fn minify(&self) -> bool {
+        self.minify
+    }
+
+    pub fn lang(&self) -> Option<&String> {
+        self.lang.as_ref()
+    }
+
+    pub fn set_lang(&mut self, value: String) {
+        self.lang = Some(value);
+    }
+
+    pub fn direction(&self) -> Option<&String> {
+        self.direction.as_ref()
+    }
+
+    /// set the direction of the document, a right to left direction changes the defaults
+    /// of the elements to render the columns and the texts from the right
+    pub fn set_direction(&mut self, value: String) {
+        if value == "rtl" {
+            for name in ["mj-column", "mj-group", "mj-section", "mj-wrapper"].iter() {
+                self.default_attributes
+                    .add_root_content(name, std::iter::once(("direction", "rtl")));
+            }
+            for name in ["mj-table", "mj-text"].iter() {
+                self.default_attributes
+                    .add_root_content(name, std::iter::once(("align", "right")));
+            }
+        }
+        self.direction = Some(value);
+    }
+
+    pub fn force_owa_desktop(&self) -> bool {
+        self.force_owa_desktop
+    }
+
+    pub fn set_force_owa_desktop(&mut self, value: bool) {
+        self.force_owa_desktop = value;
+    }
+
     pub fn title(&self) -> Option<&String> {
         self.title.as_ref()
     }
@@ -138,8 +315,22 @@ impl Header {
         &self.default_attributes
     }
 
-    pub fn set_default_attributes(&mut self, value: DefaultAttributes) {
-        self.default_attributes = value;
+    /// add the defaults of a `mj-attributes` block to the ones of the previous blocks
+    pub fn add_default_attributes(&mut self, value: &DefaultAttributes) {
+        self.default_attributes.merge(value);
+    }
+
+    /// header used to resolve the children of an element with the given `mj-class` attribute,
+    /// only copied when the element changes the defaults of its children
+    pub fn scoped(&self, classes: Option<&String>) -> Cow<'_, Header> {
+        match self.default_attributes.child_scope(classes) {
+            Some(scope) => {
+                let mut header = self.clone();
+                header.default_attributes.set_scope(scope);
+                Cow::Owned(header)
+            }
+            None => Cow::Borrowed(self),
+        }
     }
 
     pub fn preview(&self) -> Option<&String> {
@@ -155,7 +346,15 @@ impl Header {
     }
 
     pub fn add_media_query<K: ToString>(&mut self, classname: K, size: Size) {
-        self.media_queries.insert(classname.to_string(), size);
+        let classname = classname.to_string();
+        match self
+            .media_queries
+            .iter_mut()
+            .find(|(name, _)| *name == classname)
+        {
+            Some(item) => item.1 = size,
+            None => self.media_queries.push((classname, size)),
+        };
     }
 
     pub fn maybe_add_style<K: ToString>(&mut self, style: Option<K>) {
@@ -165,7 +364,31 @@ impl Header {
     }
 
     pub fn add_style<K: ToString>(&mut self, style: K) {
-        self.styles.insert(style.to_string());
+        let style = style.to_string();
+        if !self.styles.contains(&style) {
+            self.styles.push(style);
+        }
+    }
+
+    /// add the attributes to set on the elements of the generated html matching the selector
+    pub fn add_html_attributes<I: Iterator<Item = (String, String)>>(
+        &mut self,
+        path: String,
+        attributes: I,
+    ) {
+        self.html_attributes.push((path, attributes.collect()));
+    }
+
+    pub fn html_attributes(&self) -> &[(String, Vec<(String, String)>)] {
+        &self.html_attributes
+    }
+
+    pub fn add_inline_rules<I: IntoIterator<Item = Rule>>(&mut self, rules: I) {
+        self.inline_rules.extend(rules);
+    }
+
+    pub fn inline_rules(&self) -> &[Rule] {
+        &self.inline_rules
     }
 
     pub fn maybe_add_font_families(&mut self, font_family_list: Option<&String>) {
@@ -180,7 +403,9 @@ impl Header {
             .map(|v| v.trim().to_string())
             .collect::<Vec<String>>();
         for item in result {
-            self.font_families.insert(item);
+            if !self.font_families.contains(&item) {
+                self.font_families.push(item);
+            }
         }
     }
 
@@ -188,12 +413,12 @@ impl Header {
         self.font_registry.add(name, href);
     }
 
-    pub fn get_styles(&self) -> Vec<String> {
-        self.styles.iter().cloned().collect()
+    pub fn get_styles(&self) -> &[String] {
+        &self.styles
     }
 
-    pub fn get_font_families(&self) -> Vec<String> {
-        self.font_families.iter().cloned().collect()
+    pub fn get_font_families(&self) -> &[String] {
+        &self.font_families
     }
 
     pub fn get_used_font_families(&self) -> Vec<&String> {
@@ -206,23 +431,105 @@ impl Header {
         res
     }
 
-    pub fn get_media_queries(&self) -> &HashMap<String, Size> {
+    pub fn get_media_queries(&self) -> &[(String, Size)] {
         &self.media_queries
     }
+
+    /// next id of the template, the sequence only depends on the seed of the options
+    pub fn generate_id(&self, size: usize) -> String {
+        self.id_generator.generate(size)
+    }
+
+    /// resolve the path of an `mj-include` relatively to the template being parsed
+    pub fn include_path(&self, path: &str) -> String {
+        join_path(self.include_stack.last().map(|item| item.as_str()), path)
+    }
+
+    /// url of a link emitted by the element, once given to the link rewriter
+    pub fn rewrite_link(&self, element: &str, url: &str) -> String {
+        let link = Link {
+            url,
+            element,
+            position: self.link_counter.next_position(),
+        };
+        self.link_rewriter
+            .rewrite(&link)
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    pub fn asset_embedding(&self) -> AssetEmbedding {
+        self.asset_embedding
+    }
+
+    /// load the image at the given url, `None` when it should be kept as is
+    pub fn resolve_asset(&self, url: &str) -> Result<Option<Asset>, AssetResolverError> {
+        self.asset_resolver.resolve(url)
+    }
+
+    /// copy of the header used to render the template once, with the given options
+    pub fn with_render_options(&self, options: &RenderOptions) -> Header {
+        let mut header = self.clone();
+        header.link_counter = LinkCounter::default();
+        if let Some(value) = options.breakpoint.as_ref() {
+            header.breakpoint = value.clone();
+        }
+        if let Some(value) = options.keep_comments {
+            header.keep_comments = value;
+        }
+        if let Some(value) = options.minify {
+            header.minify = value;
+        }
+        if let Some(value) = options.link_rewriter.as_ref() {
+            header.link_rewriter = value.clone();
+        }
+        header
+    }
+
+    /// load the template at the given resolved path, returning its content and the header
+    /// to use when parsing it
+    pub fn load_include(&self, path: &str) -> Result<(String, Header), IncludeError> {
+        if self.include_stack.iter().any(|item| item == path) {
+            return Err(IncludeError::Cycle);
+        }
+        if self.include_stack.len() >= self.include_max_depth {
+            return Err(IncludeError::MaxDepth(self.include_max_depth));
+        }
+        let content = self
+            .include_loader
+            .resolve(path)
+            .map_err(IncludeError::Loader)?;
+        let mut header = self.clone();
+        header.include_stack.push(path.to_string());
+        Ok((content, header))
+    }
 }
 
 impl From<Options> for Header {
     fn from(value: Options) -> Self {
         Header {
+            asset_embedding: value.asset_embedding,
+            asset_resolver: value.asset_resolver.clone(),
             breakpoint: value.breakpoint.clone(),
-            default_attributes: DefaultAttributes::default(),
-            font_families: HashSet::new(),
+            default_attributes: value.default_attributes.clone(),
+            direction: None,
+            font_families: vec![],
             font_registry: value.fonts.clone(),
+            html_attributes: vec![],
+            include_loader: value.include_loader.clone(),
+            include_max_depth: value.include_max_depth,
+            include_stack: vec![],
+            inline_rules: vec![],
+            lang: None,
+            link_counter: LinkCounter::default(),
+            link_rewriter: value.link_rewriter.clone(),
             keep_comments: value.keep_comments,
-            media_queries: HashMap::new(),
+            force_owa_desktop: false,
+            id_generator: IdGenerator::new(value.id_seed),
+            media_queries: vec![],
+            minify: value.minify,
             preview: None,
             title: None,
-            styles: HashSet::new(),
+            styles: value.styles.clone(),
         }
     }
 }
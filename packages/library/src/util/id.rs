@@ -1,21 +1,52 @@
-use std::iter;
-
-#[cfg(not(test))]
-pub fn generate(size: usize) -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    iter::repeat(())
-        .map(|()| rng.sample(rand::distributions::Alphanumeric))
-        .take(size)
-        .collect::<String>()
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// splitmix64, a small and well distributed hash of a 64 bits value
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
-#[cfg(test)]
-pub fn generate(size: usize) -> String {
-    iter::repeat(())
-        .map(|()| 'x')
-        .take(size)
-        .collect::<String>()
+/// generator of the ids of the elements, always giving the same sequence for the same seed
+///
+/// The clones share the same sequence, so that the headers scoped to a `mj-class` never
+/// give an id already given by their parent.
+///
+/// ```rust
+/// use mrml::util::id::IdGenerator;
+/// let first = IdGenerator::new(42);
+/// let second = IdGenerator::new(42);
+/// assert_eq!(first.generate(8), second.generate(8));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IdGenerator {
+    seed: u64,
+    counter: Arc<AtomicU64>,
+}
+
+impl IdGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// next id of the sequence, made of `size` alphanumeric characters
+    pub fn generate(&self, size: usize) -> String {
+        let index = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut state = mix(self.seed ^ mix(index));
+        (0..size)
+            .map(|_| {
+                state = mix(state);
+                ALPHABET[(state % ALPHABET.len() as u64) as usize] as char
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -24,6 +55,24 @@ mod tests {
 
     #[test]
     fn check_size() {
-        assert_eq!(generate(10).len(), 10);
+        assert_eq!(IdGenerator::new(0).generate(10).len(), 10);
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let first = IdGenerator::new(1);
+        let second = IdGenerator::new(1);
+        let first: Vec<String> = (0..3).map(|_| first.generate(8)).collect();
+        let second: Vec<String> = (0..3).map(|_| second.generate(8)).collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+        assert_ne!(first[0], IdGenerator::new(2).generate(8));
+    }
+
+    #[test]
+    fn clones_share_sequence() {
+        let first = IdGenerator::new(1);
+        let second = first.clone();
+        assert_ne!(first.generate(8), second.generate(8));
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug)]
+pub enum IncludeLoaderError {
+    /// The requested template doesn't exist.
+    NotFound,
+    /// The loader refused to load the requested template.
+    Denied(String),
+    /// The underlying storage failed.
+    Io(std::io::Error),
+    /// Any other error coming from a custom loader.
+    Other(String),
+}
+
+impl fmt::Display for IncludeLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IncludeLoaderError::NotFound => write!(f, "template not found"),
+            IncludeLoaderError::Denied(reason) => write!(f, "access denied: {}", reason),
+            IncludeLoaderError::Io(err) => write!(f, "{}", err),
+            IncludeLoaderError::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for IncludeLoaderError {}
+
+impl From<std::io::Error> for IncludeLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            ErrorKind::NotFound => IncludeLoaderError::NotFound,
+            _ => IncludeLoaderError::Io(err),
+        }
+    }
+}
+
+/// resolves the templates requested by `<mj-include path="...">`
+///
+/// The path given to `resolve` is already made relative to the including template.
+pub trait IncludeLoader: fmt::Debug + Send + Sync {
+    fn resolve(&self, path: &str) -> Result<String, IncludeLoaderError>;
+}
+
+/// loader used by default, refusing every include
+#[derive(Clone, Debug, Default)]
+pub struct NoopIncludeLoader;
+
+impl IncludeLoader for NoopIncludeLoader {
+    fn resolve(&self, _path: &str) -> Result<String, IncludeLoaderError> {
+        Err(IncludeLoaderError::Denied(
+            "no include loader configured".into(),
+        ))
+    }
+}
+
+/// loader reading templates from a map, useful for tests or templates stored in memory
+///
+/// ```rust
+/// use mrml::util::include::{IncludeLoader, MemoryIncludeLoader};
+/// let mut loader = MemoryIncludeLoader::default();
+/// loader.insert("header.mjml", "<mj-section></mj-section>");
+/// assert!(loader.resolve("header.mjml").is_ok());
+/// assert!(loader.resolve("footer.mjml").is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryIncludeLoader {
+    inner: HashMap<String, String>,
+}
+
+impl MemoryIncludeLoader {
+    pub fn insert<K: ToString, V: ToString>(&mut self, path: K, content: V) {
+        self.inner.insert(path.to_string(), content.to_string());
+    }
+}
+
+impl From<HashMap<String, String>> for MemoryIncludeLoader {
+    fn from(inner: HashMap<String, String>) -> Self {
+        Self { inner }
+    }
+}
+
+impl IncludeLoader for MemoryIncludeLoader {
+    fn resolve(&self, path: &str) -> Result<String, IncludeLoaderError> {
+        self.inner
+            .get(path)
+            .cloned()
+            .ok_or(IncludeLoaderError::NotFound)
+    }
+}
+
+/// loader reading templates from the filesystem, below a root directory
+#[derive(Clone, Debug)]
+pub struct FileIncludeLoader {
+    root: PathBuf,
+}
+
+impl FileIncludeLoader {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl IncludeLoader for FileIncludeLoader {
+    fn resolve(&self, path: &str) -> Result<String, IncludeLoaderError> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|item| !matches!(item, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(IncludeLoaderError::Denied(format!(
+                "{} is outside of {}",
+                path,
+                self.root.display()
+            )));
+        }
+        Ok(std::fs::read_to_string(self.root.join(relative))?)
+    }
+}
+
+/// resolve the path of an include relatively to the including template
+///
+/// ```rust
+/// use mrml::util::include::join_path;
+/// assert_eq!(join_path(None, "./header.mjml"), "header.mjml");
+/// assert_eq!(join_path(Some("partials/header.mjml"), "logo.mjml"), "partials/logo.mjml");
+/// assert_eq!(join_path(Some("partials/header.mjml"), "../footer.mjml"), "footer.mjml");
+/// assert_eq!(join_path(Some("partials/header.mjml"), "/footer.mjml"), "footer.mjml");
+/// ```
+pub fn join_path(parent: Option<&str>, path: &str) -> String {
+    let mut parts: Vec<&str> = match parent {
+        Some(parent) if !path.starts_with('/') => {
+            let mut parts: Vec<&str> = parent.split('/').collect();
+            parts.pop();
+            parts
+        }
+        _ => vec![],
+    };
+    for part in path.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                if parts.last().map(|last| *last != "..").unwrap_or(false) {
+                    parts.pop();
+                } else {
+                    parts.push(part);
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.retain(|part| !part.is_empty());
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_path_escaping() {
+        assert_eq!(join_path(None, "../header.mjml"), "../header.mjml");
+        assert_eq!(join_path(Some("a/b.mjml"), "../../c.mjml"), "../c.mjml");
+    }
+
+    #[test]
+    fn file_loader_denies_parent() {
+        let loader = FileIncludeLoader::new(".");
+        match loader.resolve("../header.mjml") {
+            Err(IncludeLoaderError::Denied(_)) => (),
+            other => panic!("unexpected result {:?}", other),
+        };
+    }
+
+    #[test]
+    fn file_loader_not_found() {
+        let loader = FileIncludeLoader::new("test");
+        match loader.resolve("not-existing.mjml") {
+            Err(IncludeLoaderError::NotFound) => (),
+            other => panic!("unexpected result {:?}", other),
+        };
+    }
+
+    #[test]
+    fn file_loader_found() {
+        let loader = FileIncludeLoader::new("test");
+        assert!(loader.resolve("include/header.mjml").is_ok());
+    }
+}
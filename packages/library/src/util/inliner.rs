@@ -0,0 +1,421 @@
+//! post render passes applying the styles of `<mj-style inline="inline">` and the attributes of
+//! `<mj-html-attributes>` to the matching elements of the generated html.
+
+use super::css::{parse_declarations, Declaration, ElementData, Rule, Selector, Specificity};
+use std::collections::HashMap;
+
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+/// ordering of the declarations applying to an element, the greatest one wins
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Priority {
+    important: bool,
+    inline: bool,
+    specificity: Specificity,
+    order: usize,
+}
+
+/// start tag found in the html, with the byte ranges of its attributes and of its `style`
+/// attribute value
+struct StartTag {
+    start: usize,
+    spans: Vec<(usize, usize)>,
+    style: Option<(usize, usize)>,
+    /// position where a `style` attribute can be inserted
+    insert_at: usize,
+    end: usize,
+    self_closing: bool,
+}
+
+fn skip_whitespaces(html: &[u8], mut position: usize) -> usize {
+    while position < html.len() && html[position].is_ascii_whitespace() {
+        position += 1;
+    }
+    position
+}
+
+fn read_name(html: &[u8], mut position: usize) -> usize {
+    while position < html.len()
+        && !html[position].is_ascii_whitespace()
+        && !matches!(html[position], b'>' | b'/' | b'=')
+    {
+        position += 1;
+    }
+    position
+}
+
+fn parse_start_tag(source: &str, start: usize) -> Option<(ElementData, StartTag)> {
+    let html = source.as_bytes();
+    let name_end = read_name(html, start + 1);
+    let mut element = ElementData {
+        name: source[start + 1..name_end].to_lowercase(),
+        attributes: vec![],
+    };
+    let mut style = None;
+    let mut spans = vec![];
+    let mut position = name_end;
+    loop {
+        position = skip_whitespaces(html, position);
+        match html.get(position)? {
+            b'>' => {
+                return Some((
+                    element,
+                    StartTag {
+                        start,
+                        spans,
+                        style,
+                        insert_at: position,
+                        end: position + 1,
+                        self_closing: false,
+                    },
+                ))
+            }
+            b'/' if html.get(position + 1) == Some(&b'>') => {
+                return Some((
+                    element,
+                    StartTag {
+                        start,
+                        spans,
+                        style,
+                        insert_at: position,
+                        end: position + 2,
+                        self_closing: true,
+                    },
+                ))
+            }
+            b'/' => position += 1,
+            _ => {
+                let key_start = position;
+                let key_end = read_name(html, position);
+                let key = source[position..key_end].to_lowercase();
+                position = skip_whitespaces(html, key_end);
+                let mut value = (position, position);
+                let mut attribute_end = key_end;
+                if html.get(position) == Some(&b'=') {
+                    position = skip_whitespaces(html, position + 1);
+                    match html.get(position)? {
+                        quote @ b'"' | quote @ b'\'' => {
+                            let length = html[position + 1..].iter().position(|c| c == quote)?;
+                            value = (position + 1, position + 1 + length);
+                            position += length + 2;
+                        }
+                        _ => {
+                            let end = read_name(html, position);
+                            value = (position, end);
+                            position = end;
+                        }
+                    };
+                    attribute_end = position;
+                }
+                if key.is_empty() {
+                    position += 1;
+                    continue;
+                }
+                if key == "style" {
+                    style = Some(value);
+                }
+                spans.push((key_start, attribute_end));
+                element
+                    .attributes
+                    .push((key, source[value.0..value.1].to_string()));
+            }
+        };
+    }
+}
+
+/// find the position of `pattern` in `html` starting at `from`, ignoring the ascii case
+pub(crate) fn find_ignore_case(html: &str, from: usize, pattern: &str) -> Option<usize> {
+    let pattern = pattern.as_bytes();
+    html.as_bytes()[from..]
+        .windows(pattern.len())
+        .position(|window| window.eq_ignore_ascii_case(pattern))
+        .map(|index| from + index)
+}
+
+/// compute the content of the `style` attribute of an element
+fn compute_style(stack: &[ElementData], current: &str, rules: &[Rule]) -> Option<String> {
+    let mut names: Vec<String> = vec![];
+    let mut winners: HashMap<String, (Priority, &str)> = HashMap::new();
+    let existing = parse_declarations(current);
+    let mut matched: Vec<(Specificity, usize, &Rule)> = rules
+        .iter()
+        .enumerate()
+        .filter(|(_, rule)| rule.selector.matches(stack))
+        .map(|(order, rule)| (rule.selector.specificity(), order, rule))
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+    matched.sort_by_key(|(specificity, order, _)| (*specificity, *order));
+    let candidates = existing
+        .iter()
+        .enumerate()
+        .map(|(order, item)| ((true, (0, 0, 0), order), item))
+        .chain(matched.iter().flat_map(|(specificity, order, rule)| {
+            rule.declarations
+                .iter()
+                .map(move |item| ((false, *specificity, *order), item))
+        }));
+    for ((inline, specificity, order), item) in candidates {
+        let priority = Priority {
+            important: item.important,
+            inline,
+            specificity,
+            order,
+        };
+        match winners.get(&item.name) {
+            Some((previous, _)) if *previous > priority => (),
+            Some(_) => {
+                winners.insert(item.name.clone(), (priority, item.value.as_str()));
+            }
+            None => {
+                names.push(item.name.clone());
+                winners.insert(item.name.clone(), (priority, item.value.as_str()));
+            }
+        };
+    }
+    let result: Vec<String> = names
+        .into_iter()
+        .map(|name| {
+            let (priority, value) = winners[&name];
+            Declaration {
+                name,
+                value: value.replace('"', "'"),
+                important: priority.important,
+            }
+            .to_string()
+        })
+        .collect();
+    Some(result.join(""))
+}
+
+/// walk through the start tags of the html, the given function receiving the stack of the
+/// elements, the current one being the last, and returning the new content of the tag if changed
+fn rewrite<F>(html: &str, mut update: F) -> String
+where
+    F: FnMut(&[ElementData], &StartTag) -> Option<String>,
+{
+    let bytes = html.as_bytes();
+    let mut result = String::with_capacity(html.len());
+    let mut stack: Vec<ElementData> = vec![];
+    let mut copied = 0;
+    let mut position = 0;
+    while let Some(index) = html[position..].find('<') {
+        let start = position + index;
+        position = start + 1;
+        if html[start..].starts_with("<!--") {
+            position = html[start..]
+                .find("-->")
+                .map(|end| start + end + 3)
+                .unwrap_or(html.len());
+            continue;
+        }
+        match bytes.get(start + 1) {
+            Some(b'!') | Some(b'?') => {
+                position = html[start..]
+                    .find('>')
+                    .map(|end| start + end + 1)
+                    .unwrap_or(html.len());
+            }
+            Some(b'/') => {
+                let name_end = read_name(bytes, start + 2);
+                let name = html[start + 2..name_end].to_lowercase();
+                if let Some(index) = stack.iter().rposition(|item| item.name == name) {
+                    stack.truncate(index);
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let (element, tag) = match parse_start_tag(html, start) {
+                    Some(value) => value,
+                    None => break,
+                };
+                position = tag.end;
+                let name = element.name.clone();
+                stack.push(element);
+                if let Some(content) = update(&stack, &tag) {
+                    result.push_str(&html[copied..tag.start]);
+                    result.push_str(&content);
+                    copied = tag.end;
+                }
+                if tag.self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                    stack.pop();
+                } else if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                    stack.pop();
+                    position = find_ignore_case(html, position, &format!("</{}", name))
+                        .unwrap_or(html.len());
+                }
+            }
+            _ => (),
+        };
+    }
+    result.push_str(&html[copied..]);
+    result
+}
+
+/// apply the given rules to the elements of the html document
+///
+/// ```rust
+/// use mrml::util::css::Stylesheet;
+/// use mrml::util::inliner::inline;
+/// let sheet = Stylesheet::parse("p a { color: red; } .link { color: blue; }");
+/// let result = inline("<p><a class=\"link\" style=\"margin:0;\">Hello</a></p>", &sheet.rules);
+/// assert_eq!(result, "<p><a class=\"link\" style=\"margin:0;color:blue;\">Hello</a></p>");
+/// ```
+pub fn inline(html: &str, rules: &[Rule]) -> String {
+    if rules.is_empty() {
+        return html.to_string();
+    }
+    rewrite(html, |stack, tag| {
+        let current = tag.style.map(|(from, to)| &html[from..to]).unwrap_or("");
+        let style = compute_style(stack, current, rules)?;
+        Some(match tag.style {
+            Some((from, to)) => {
+                format!("{}{}{}", &html[tag.start..from], style, &html[to..tag.end])
+            }
+            None => format!(
+                "{} style=\"{}\"{}",
+                &html[tag.start..tag.insert_at],
+                style,
+                &html[tag.insert_at..tag.end]
+            ),
+        })
+    })
+}
+
+/// set the attributes of the elements matching the selectors, replacing the existing values
+///
+/// ```rust
+/// use mrml::util::inliner::set_attributes;
+/// let selectors = vec![(".link".to_string(), vec![("data-id".to_string(), "42".to_string())])];
+/// let result = set_attributes("<p><a class=\"link\" data-id=\"1\">Hello</a><a>World</a></p>", &selectors);
+/// assert_eq!(result, "<p><a class=\"link\" data-id=\"42\">Hello</a><a>World</a></p>");
+/// ```
+pub fn set_attributes(html: &str, selectors: &[(String, Vec<(String, String)>)]) -> String {
+    let selectors = selectors
+        .iter()
+        .map(|(path, attributes)| (Selector::parse_list(path), attributes))
+        .collect::<Vec<_>>();
+    if selectors.is_empty() {
+        return html.to_string();
+    }
+    rewrite(html, |stack, tag| {
+        let element = stack.last()?;
+        let mut changes: Vec<(&str, &str)> = vec![];
+        for (items, attributes) in selectors.iter() {
+            if items.iter().any(|selector| selector.matches(stack)) {
+                for (name, value) in attributes.iter() {
+                    match changes.iter_mut().find(|(key, _)| key == name) {
+                        Some(item) => item.1 = value,
+                        None => changes.push((name, value)),
+                    };
+                }
+            }
+        }
+        if changes.is_empty() {
+            return None;
+        }
+        let mut replaced: Vec<(usize, usize, String)> = vec![];
+        let mut added = String::new();
+        for (name, value) in changes {
+            let attribute = format!("{}=\"{}\"", name, value.replace('"', "&quot;"));
+            match element.attributes.iter().position(|(key, _)| key == name) {
+                Some(index) => replaced.push((tag.spans[index].0, tag.spans[index].1, attribute)),
+                None => {
+                    added.push(' ');
+                    added.push_str(&attribute);
+                }
+            };
+        }
+        replaced.sort_by_key(|(from, _, _)| *from);
+        let mut result = String::new();
+        let mut copied = tag.start;
+        for (from, to, attribute) in replaced {
+            result.push_str(&html[copied..from]);
+            result.push_str(&attribute);
+            copied = to;
+        }
+        result.push_str(&html[copied..tag.insert_at]);
+        result.push_str(&added);
+        result.push_str(&html[tag.insert_at..tag.end]);
+        Some(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::css::Stylesheet;
+
+    fn apply(css: &str, html: &str) -> String {
+        inline(html, &Stylesheet::parse(css).rules)
+    }
+
+    #[test]
+    fn specificity_and_order() {
+        let css = "div p { color: red; } p { color: blue; font-size: 12px; } .text { color: green; } #main { color: black; } p { font-size: 14px; }";
+        assert_eq!(
+            apply(css, "<div><p class=\"text\">a</p><p>b</p><p id=\"main\" class=\"text\">c</p></div>"),
+            "<div><p class=\"text\" style=\"color:green;font-size:14px;\">a</p><p style=\"color:red;font-size:14px;\">b</p><p id=\"main\" class=\"text\" style=\"color:black;font-size:14px;\">c</p></div>"
+        );
+    }
+
+    #[test]
+    fn important() {
+        let css = "#main { color: black; } p { color: red !important; margin: 0 !important; }";
+        assert_eq!(
+            apply(
+                css,
+                "<p id=\"main\" style=\"color:blue;margin:2px !important;\">a</p>"
+            ),
+            "<p id=\"main\" style=\"color:red !important;margin:2px !important;\">a</p>"
+        );
+    }
+
+    #[test]
+    fn existing_style_wins() {
+        assert_eq!(
+            apply(
+                "p { color: red; padding: 0; }",
+                "<p style=\"color:blue;\">a</p>"
+            ),
+            "<p style=\"color:blue;padding:0;\">a</p>"
+        );
+    }
+
+    #[test]
+    fn ignores_comments_and_raw_text() {
+        let html = "<head><style>p { color: blue; }</style><title><p></title></head><!--[if mso]><p><![endif]--><img src=\"a.png\"><br/><p>a</p>";
+        assert_eq!(
+            apply("head p, p, img { color: red; }", html),
+            "<head><style>p { color: blue; }</style><title><p></title></head><!--[if mso]><p><![endif]--><img src=\"a.png\" style=\"color:red;\"><br/><p style=\"color:red;\">a</p>"
+        );
+    }
+
+    #[test]
+    fn set_attributes_with_commas_in_selectors() {
+        let selectors = vec![(
+            "a[data-x=\"a,b\"], :not(.a, .b), p".to_string(),
+            vec![("data-id".to_string(), "42".to_string())],
+        )];
+        assert_eq!(
+            set_attributes(
+                "<div><a data-x=\"a,b\">a</a><a data-x=\"a\">b</a><p>c</p></div>",
+                &selectors
+            ),
+            "<div><a data-x=\"a,b\" data-id=\"42\">a</a><a data-x=\"a\">b</a><p data-id=\"42\">c</p></div>"
+        );
+    }
+
+    #[test]
+    fn child_combinator_after_void_element() {
+        let html = "<td><img src=\"a.png\" /><a href=\"#\">link</a></td>";
+        assert_eq!(
+            apply("td > a { font-family: \"Helvetica\"; }", html),
+            "<td><img src=\"a.png\" /><a href=\"#\" style=\"font-family:'Helvetica';\">link</a></td>"
+        );
+    }
+}
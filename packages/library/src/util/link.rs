@@ -0,0 +1,117 @@
+//! rewriting of the links emitted by the components, like for click tracking.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// link emitted by a component, given to the link rewriter
+#[derive(Clone, Debug)]
+pub struct Link<'a> {
+    /// url of the link, once joined with the `base-url` of a navbar or the share url
+    /// of a social network
+    pub url: &'a str,
+    /// name of the element emitting the link, like `mj-button` or `a` for the raw html
+    pub element: &'a str,
+    /// position of the link in the generated html, starting at 0
+    pub position: usize,
+}
+
+/// rewrites the links of the generated html, like to go through a tracking redirector
+pub trait LinkRewriter: fmt::Debug + Send + Sync {
+    /// new url of the link, `None` to keep it untouched
+    fn rewrite(&self, link: &Link) -> Option<String>;
+}
+
+/// rewriter used by default, keeping every link untouched
+#[derive(Clone, Debug, Default)]
+pub struct NoopLinkRewriter;
+
+impl LinkRewriter for NoopLinkRewriter {
+    fn rewrite(&self, _link: &Link) -> Option<String> {
+        None
+    }
+}
+
+/// rewriter adding parameters to the query of the `http` and `https` links,
+/// like the UTM parameters
+///
+/// ```rust
+/// use mrml::util::link::{Link, LinkRewriter, QueryLinkRewriter};
+/// let rewriter = QueryLinkRewriter::default()
+///     .add("utm_source", "newsletter")
+///     .add("utm_campaign", "summer sale");
+/// let link = |url| Link { url, element: "mj-button", position: 0 };
+/// assert_eq!(
+///     rewriter.rewrite(&link("https://example.com/?id=1#top")),
+///     Some("https://example.com/?id=1&utm_source=newsletter&utm_campaign=summer%20sale#top".into())
+/// );
+/// assert_eq!(rewriter.rewrite(&link("mailto:hello@example.com")), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct QueryLinkRewriter {
+    params: Vec<(String, String)>,
+}
+
+impl QueryLinkRewriter {
+    pub fn add<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// percent encode the characters that are not allowed in a query parameter
+fn encode_component(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char)
+            }
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        };
+    }
+    result
+}
+
+impl LinkRewriter for QueryLinkRewriter {
+    fn rewrite(&self, link: &Link) -> Option<String> {
+        if self.params.is_empty()
+            || !(link.url.starts_with("http://") || link.url.starts_with("https://"))
+        {
+            return None;
+        }
+        let (url, fragment) = match link.url.find('#') {
+            Some(index) => link.url.split_at(index),
+            None => (link.url, ""),
+        };
+        let query = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode_component(key), encode_component(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let separator = if url.ends_with('?') || url.ends_with('&') {
+            ""
+        } else if url.contains('?') {
+            "&"
+        } else {
+            "?"
+        };
+        Some(format!("{}{}{}{}", url, separator, query, fragment))
+    }
+}
+
+/// position of the next link of the generated html
+#[derive(Debug, Default)]
+pub struct LinkCounter(AtomicUsize);
+
+impl LinkCounter {
+    pub fn next_position(&self) -> usize {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl Clone for LinkCounter {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.0.load(Ordering::Relaxed)))
+    }
+}
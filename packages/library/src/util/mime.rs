@@ -0,0 +1,597 @@
+//! build a sendable MIME message (RFC 5322 / RFC 2045) out of a rendered email.
+//!
+//! The html and the plain text are sent as `multipart/alternative` parts encoded in
+//! quoted-printable, wrapped in a `multipart/related` part when inline images are attached.
+//! The headers containing non ascii characters are encoded following RFC 2047 and every
+//! line is kept under the limits of RFC 5322.
+
+use crate::Email;
+use std::fmt;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CRLF: &str = "\r\n";
+/// recommended maximum length of a line, without the line break
+const MAX_LINE_LENGTH: usize = 76;
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// headers containing addresses, only their display names are encoded
+const ADDRESS_HEADERS: [&str; 5] = ["bcc", "cc", "from", "reply-to", "to"];
+const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// messages built by the process, so that their ids are unique
+static MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// format the time as the `Date` header, in UTC
+///
+/// ```rust
+/// use mrml::util::mime::format_date;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// assert_eq!(format_date(time), "Tue, 14 Nov 2023 22:13:20 +0000");
+/// ```
+pub fn format_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let days = seconds / 86400;
+    let time = seconds % 86400;
+    // civil date of the days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 2 } else { month - 10 };
+    let year = year_of_era + era * 400 + u64::from(month < 2);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAYS[(days % 7) as usize],
+        day,
+        MONTHS[month as usize],
+        year,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// encode the bytes in base64
+///
+/// ```rust
+/// use mrml::util::mime::base64;
+/// assert_eq!(base64(b"mrml"), "bXJtbA==");
+/// ```
+pub fn base64(input: &[u8]) -> String {
+    let mut result = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |res, (index, byte)| {
+            res | (*byte as u32) << (16 - index * 8)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                result.push(BASE64_CHARS[(value >> (18 - index * 6)) as usize & 0x3f] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// encode the text in quoted-printable, with CRLF line breaks and soft breaks
+/// keeping the lines under 76 characters
+///
+/// ```rust
+/// use mrml::util::mime::quoted_printable;
+/// assert_eq!(quoted_printable("a=b é \nc"), "a=3Db =C3=A9=20\r\nc");
+/// ```
+pub fn quoted_printable(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let normalized = input.replace("\r\n", "\n");
+    for (index, line) in normalized.split('\n').enumerate() {
+        if index > 0 {
+            result.push_str(CRLF);
+        }
+        let bytes = line.as_bytes();
+        let mut length = 0;
+        for (position, byte) in bytes.iter().enumerate() {
+            let is_last = position + 1 == bytes.len();
+            let encoded = match byte {
+                b' ' | b'\t' if is_last => format!("={:02X}", byte),
+                b'=' => format!("={:02X}", byte),
+                b' ' | b'\t' | 33..=126 => (*byte as char).to_string(),
+                _ => format!("={:02X}", byte),
+            };
+            // keep room for the soft line break, unless it's the end of the line
+            let limit = if is_last {
+                MAX_LINE_LENGTH
+            } else {
+                MAX_LINE_LENGTH - 1
+            };
+            if length + encoded.len() > limit {
+                result.push('=');
+                result.push_str(CRLF);
+                length = 0;
+            }
+            result.push_str(&encoded);
+            length += encoded.len();
+        }
+    }
+    result
+}
+
+/// encode the header value following RFC 2047 when it contains non ascii characters
+///
+/// ```rust
+/// use mrml::util::mime::encode_word;
+/// assert_eq!(encode_word("Hello"), "Hello");
+/// assert_eq!(encode_word("Héllo"), "=?UTF-8?B?SMOpbGxv?=");
+/// ```
+pub fn encode_word(input: &str) -> String {
+    if input.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return input.to_string();
+    }
+    // each encoded word is limited to 75 characters, 39 bytes of content keeping it
+    // on a single line with the header name
+    let mut words = vec![];
+    let mut current = String::new();
+    for c in input.chars() {
+        if current.len() + c.len_utf8() > 39 {
+            words.push(format!("=?UTF-8?B?{}?=", base64(current.as_bytes())));
+            current.clear();
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(format!("=?UTF-8?B?{}?=", base64(current.as_bytes())));
+    }
+    words.join(" ")
+}
+
+/// split a list of addresses on the commas that are not in a quoted display name
+fn split_addresses(input: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, character) in input.char_indices() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                result.push(&input[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        };
+    }
+    result.push(&input[start..]);
+    result
+}
+
+/// encode a display name, quoting it when it contains special characters like commas
+fn encode_display_name(input: &str) -> String {
+    let name = input.trim();
+    let name = match name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    {
+        Some(value) => value.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => name.to_string(),
+    };
+    if !name.is_ascii() || name.chars().any(|c| c.is_ascii_control()) {
+        encode_word(&name)
+    } else if name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        name
+    }
+}
+
+/// encode the display names of a list of addresses like `Jane Doe <jane@example.com>`
+fn encode_addresses(input: &str) -> String {
+    split_addresses(input)
+        .into_iter()
+        .map(|address| {
+            let address = address.trim();
+            match address.rfind('<') {
+                Some(index) if index > 0 => format!(
+                    "{} {}",
+                    encode_display_name(&address[..index]),
+                    &address[index..]
+                ),
+                _ => address.to_string(),
+            }
+        })
+        .filter(|address| !address.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// write the header, folding the value on its whitespaces to keep the lines short
+///
+/// The line breaks of the name and the value are removed, so that a value coming from the
+/// user cannot add headers to the message.
+fn write_header(result: &mut String, name: &str, value: &str) {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && *c != ':')
+        .collect();
+    let value = value.replace(['\r', '\n'], " ");
+    let value = if ADDRESS_HEADERS.contains(&name.to_lowercase().as_str()) {
+        encode_addresses(&value)
+    } else {
+        encode_word(&value)
+    };
+    result.push_str(&name);
+    result.push(':');
+    let mut length = name.len() + 1;
+    for word in value.split(' ').filter(|word| !word.is_empty()) {
+        if length + 1 + word.len() > MAX_LINE_LENGTH + 2 && length > name.len() + 1 {
+            result.push_str(CRLF);
+            length = 0;
+        }
+        result.push(' ');
+        result.push_str(word);
+        length += word.len() + 1;
+    }
+    result.push_str(CRLF);
+}
+
+/// image attached to the message, referenced in the html with `cid:<content_id>`
+#[derive(Clone, Debug)]
+pub struct InlineImage {
+    pub content_id: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl InlineImage {
+    pub fn new<I: ToString, T: ToString>(content_id: I, content_type: T, data: Vec<u8>) -> Self {
+        Self {
+            content_id: content_id.to_string(),
+            content_type: content_type.to_string(),
+            data,
+        }
+    }
+
+    fn write(&self, result: &mut String) {
+        write_header(result, "Content-Type", &self.content_type);
+        write_header(result, "Content-Transfer-Encoding", "base64");
+        write_header(result, "Content-ID", &format!("<{}>", self.content_id));
+        write_header(result, "Content-Disposition", "inline");
+        result.push_str(CRLF);
+        let encoded = base64(&self.data);
+        let mut rest = encoded.as_str();
+        while !rest.is_empty() {
+            let (line, next) = rest.split_at(rest.len().min(MAX_LINE_LENGTH));
+            result.push_str(line);
+            result.push_str(CRLF);
+            rest = next;
+        }
+    }
+}
+
+/// MIME message of an email, with its headers and its inline images
+///
+/// ```rust
+/// use mrml::{to_email, Options};
+/// let email = to_email("<mjml><mj-head><mj-title>Hello</mj-title></mj-head><mj-body><mj-text>World</mj-text></mj-body></mjml>", Options::default()).unwrap();
+/// let message = email.message()
+///     .from("Jane Doe <jane@example.com>")
+///     .to("john@example.com")
+///     .to_string();
+/// assert!(message.starts_with("From: Jane Doe <jane@example.com>\r\nTo: john@example.com\r\nSubject: Hello\r\nMIME-Version: 1.0\r\n"));
+/// assert!(message.contains("Content-Type: multipart/alternative;"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Message<'a> {
+    email: &'a Email,
+    headers: Vec<(String, String)>,
+    images: Vec<InlineImage>,
+    boundary: String,
+}
+
+impl<'a> Message<'a> {
+    pub fn new(email: &'a Email) -> Self {
+        Self {
+            email,
+            headers: vec![],
+            images: email.attachments.clone(),
+            boundary: String::from("=_mrml"),
+        }
+    }
+
+    /// add a header to the message, like `X-Campaign`
+    pub fn header<N: ToString, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// date of the message, the time it's written by default
+    pub fn date(self, value: SystemTime) -> Self {
+        self.header("Date", format_date(value))
+    }
+
+    /// id of the message, like `<welcome.42@example.com>`, one being generated by default
+    pub fn message_id<V: ToString>(self, value: V) -> Self {
+        self.header("Message-ID", value)
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+    }
+
+    /// unique id of the message, on the domain of the sender
+    fn generate_message_id(&self, time: SystemTime) -> String {
+        let domain = self
+            .headers
+            .iter()
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("from"))
+            .and_then(|(_, value)| value.rsplit_once('@'))
+            .map(|(_, domain)| {
+                domain
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-')
+                    .collect::<String>()
+            })
+            .filter(|domain| !domain.is_empty())
+            .unwrap_or_else(|| String::from("mrml"));
+        let nanos = time
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        format!(
+            "<{:x}.{:x}.{:x}@{}>",
+            nanos,
+            process::id(),
+            MESSAGE_COUNTER.fetch_add(1, Ordering::Relaxed),
+            domain
+        )
+    }
+
+    pub fn from<V: ToString>(self, value: V) -> Self {
+        self.header("From", value)
+    }
+
+    pub fn to<V: ToString>(self, value: V) -> Self {
+        self.header("To", value)
+    }
+
+    pub fn cc<V: ToString>(self, value: V) -> Self {
+        self.header("Cc", value)
+    }
+
+    pub fn reply_to<V: ToString>(self, value: V) -> Self {
+        self.header("Reply-To", value)
+    }
+
+    /// attach an image referenced in the html, the message becoming `multipart/related`
+    ///
+    /// The attachments of the email are already attached.
+    pub fn inline_image(mut self, image: InlineImage) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    /// prefix of the boundaries between the parts, starting with `=_` so that
+    /// it can't be found in the encoded content
+    pub fn boundary<V: ToString>(mut self, value: V) -> Self {
+        self.boundary = value.to_string();
+        self
+    }
+
+    fn write_alternative(&self, result: &mut String) {
+        let boundary = format!("{}_alternative", self.boundary);
+        write_header(
+            result,
+            "Content-Type",
+            &format!("multipart/alternative; boundary=\"{}\"", boundary),
+        );
+        result.push_str(CRLF);
+        for (content_type, content) in [
+            ("text/plain", &self.email.text),
+            ("text/html", &self.email.html),
+        ]
+        .iter()
+        {
+            result.push_str(&format!("--{}{}", boundary, CRLF));
+            write_header(
+                result,
+                "Content-Type",
+                &format!("{}; charset=utf-8", content_type),
+            );
+            write_header(result, "Content-Transfer-Encoding", "quoted-printable");
+            result.push_str(CRLF);
+            result.push_str(&quoted_printable(content));
+            result.push_str(CRLF);
+        }
+        result.push_str(&format!("--{}--{}", boundary, CRLF));
+    }
+
+    fn build(&self) -> String {
+        let mut result = String::new();
+        for (name, value) in self.headers.iter() {
+            write_header(&mut result, name, value);
+        }
+        write_header(&mut result, "Subject", &self.email.subject);
+        write_header(&mut result, "MIME-Version", "1.0");
+        let now = SystemTime::now();
+        if !self.has_header("Date") {
+            write_header(&mut result, "Date", &format_date(now));
+        }
+        if !self.has_header("Message-ID") {
+            write_header(&mut result, "Message-ID", &self.generate_message_id(now));
+        }
+        if self.images.is_empty() {
+            self.write_alternative(&mut result);
+            return result;
+        }
+        let boundary = format!("{}_related", self.boundary);
+        write_header(
+            &mut result,
+            "Content-Type",
+            &format!(
+                "multipart/related; type=\"multipart/alternative\"; boundary=\"{}\"",
+                boundary
+            ),
+        );
+        result.push_str(CRLF);
+        result.push_str(&format!("--{}{}", boundary, CRLF));
+        self.write_alternative(&mut result);
+        for image in self.images.iter() {
+            result.push_str(&format!("--{}{}", boundary, CRLF));
+            image.write(&mut result);
+        }
+        result.push_str(&format!("--{}--{}", boundary, CRLF));
+        result
+    }
+
+    /// write the message, like in an `.eml` file or to an smtp server
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.build().as_bytes())
+    }
+}
+
+impl<'a> fmt::Display for Message<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> Email {
+        Email {
+            subject: "Un été très ensoleillé, avec beaucoup de soleil et de longues journées"
+                .into(),
+            text: format!("Hello\n{}\n", "long line ".repeat(10)),
+            html: "<p style=\"color:red\">Hello</p>".into(),
+            attachments: vec![],
+        }
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"a"), "YQ==");
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"abc"), "YWJj");
+    }
+
+    #[test]
+    fn line_lengths() {
+        let email = email();
+        let message = Message::new(&email)
+            .from("Élodie <elodie@example.com>, john@example.com")
+            .to_string();
+        for line in message.split(CRLF) {
+            assert!(line.len() <= 78, "line too long: {:?}", line);
+        }
+        assert!(message
+            .contains("From: =?UTF-8?B?w4lsb2RpZQ==?= <elodie@example.com>, john@example.com\r\n"));
+        assert!(message.contains("Subject: =?UTF-8?B?"));
+        assert!(message.contains("\r\n =?UTF-8?B?"));
+        assert!(message.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(message.contains("<p style=3D\"color:red\">Hello</p>"));
+        assert!(message.ends_with("--=_mrml_alternative--\r\n"));
+    }
+
+    #[test]
+    fn header_injection() {
+        let email = email();
+        let message = Message::new(&email)
+            .from("jane@example.com\r\nBcc: spy@example.com")
+            .to("John <john@example.com>\nBcc: spy@example.com")
+            .reply_to("\"Doe\r\nBcc: spy@example.com\" <jane@example.com>")
+            .header("X-Campaign\r\nBcc", "summer\r\nBcc: spy@example.com")
+            .to_string();
+        assert!(!message.contains("\nBcc"), "{}", message);
+        assert!(message.contains("From: jane@example.com Bcc: spy@example.com\r\n"));
+        assert!(message.contains("X-CampaignBcc: summer Bcc: spy@example.com\r\n"));
+    }
+
+    #[test]
+    fn quoted_display_names() {
+        let email = email();
+        let message = Message::new(&email)
+            .to("\"Doe, Jane\" <jane@example.com>, J. Doe <john@example.com>, bob@example.com")
+            .to_string();
+        assert!(message.contains(
+            "To: \"Doe, Jane\" <jane@example.com>, \"J. Doe\" <john@example.com>,\r\n bob@example.com\r\n"
+        ), "{}", message);
+    }
+
+    #[test]
+    fn quoted_printable_soft_breaks() {
+        let encoded = quoted_printable(&"é".repeat(40));
+        let lines = encoded.split(CRLF).collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[..3].iter().all(|line| line.ends_with('=')));
+        assert!(lines.iter().all(|line| line.len() <= 76));
+        assert_eq!(encoded.replace("=\r\n", ""), "=C3=A9".repeat(40));
+    }
+
+    #[test]
+    fn inline_images() {
+        let email = email();
+        let message = Message::new(&email)
+            .inline_image(InlineImage::new("logo", "image/png", vec![0; 100]))
+            .to_string();
+        assert!(message.contains(
+            "Content-Type: multipart/related; type=\"multipart/alternative\";\r\n boundary=\"=_mrml_related\"\r\n"
+        ));
+        assert!(message.contains("--=_mrml_related\r\nContent-Type: multipart/alternative;"));
+        assert!(message.contains("Content-ID: <logo>\r\nContent-Disposition: inline\r\n\r\nAAAA"));
+        assert!(message.ends_with("--=_mrml_related--\r\n"));
+        let mut buffer = vec![];
+        Message::new(&email).write(&mut buffer).unwrap();
+        assert!(String::from_utf8(buffer).unwrap().starts_with("Subject: "));
+    }
+
+    #[test]
+    fn date_and_message_id() {
+        let email = email();
+        let message = Message::new(&email)
+            .from("Jane <jane@example.com>")
+            .to_string();
+        assert_eq!(message.matches("\r\nDate: ").count(), 1);
+        let id = message
+            .split(CRLF)
+            .find_map(|line| line.strip_prefix("Message-ID: "))
+            .unwrap();
+        assert!(
+            id.starts_with('<') && id.ends_with("@example.com>"),
+            "{}",
+            id
+        );
+        assert_ne!(
+            Message::new(&email).to_string(),
+            Message::new(&email).to_string()
+        );
+
+        let message = Message::new(&email)
+            .date(UNIX_EPOCH + std::time::Duration::from_secs(951_782_400))
+            .message_id("<welcome@example.com>")
+            .to_string();
+        assert!(message.starts_with(
+            "Date: Tue, 29 Feb 2000 00:00:00 +0000\r\nMessage-ID: <welcome@example.com>\r\n"
+        ));
+        assert_eq!(message.matches("Date: ").count(), 1);
+        assert_eq!(message.matches("Message-ID: ").count(), 1);
+    }
+}
@@ -0,0 +1,261 @@
+//! post render pass reducing the size of the generated html, by collapsing the whitespaces,
+//! removing the empty `class` and `style` attributes and minifying the stylesheets.
+//!
+//! The comments, including the outlook conditional comments, and the content of the
+//! `pre`, `textarea` and `script` elements are kept untouched.
+
+use super::inliner::find_ignore_case;
+use regex::Regex;
+
+const PRESERVED_ELEMENTS: [&str; 3] = ["pre", "script", "textarea"];
+/// elements around which the whitespaces have no effect on the rendering
+const BLOCK_ELEMENTS: [&str; 22] = [
+    "body", "br", "center", "div", "h1", "h2", "h3", "h4", "h5", "h6", "head", "html", "li",
+    "link", "meta", "ol", "p", "table", "tbody", "td", "title", "tr",
+];
+
+lazy_static! {
+    static ref EMPTY_ATTRIBUTE: Regex = Regex::new(r#"\s+(class|style)="""#).unwrap();
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// position right after the end of the tag starting at `start`, ignoring the quoted values
+fn tag_end(html: &str, start: usize) -> usize {
+    let mut quote: Option<char> = None;
+    for (index, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return start + index + 1,
+            _ => (),
+        };
+    }
+    html.len()
+}
+
+/// whitespaces between two tags can be removed when one of them is a block element or a comment
+fn is_boundary(item: Option<&str>) -> bool {
+    match item {
+        None => true,
+        Some(item) if item.starts_with("<!") => true,
+        Some(item) => BLOCK_ELEMENTS.contains(&tag_name(item).as_str()),
+    }
+}
+
+fn push_text(result: &mut String, text: &str, previous: Option<&str>, next: Option<&str>) {
+    if text.is_empty() {
+        return;
+    }
+    if text.trim().is_empty() {
+        if !is_boundary(previous) && !is_boundary(next) {
+            result.push(' ');
+        }
+        return;
+    }
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !last_was_space {
+                result.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            result.push(c);
+            last_was_space = false;
+        }
+    }
+}
+
+/// the remaining stylesheet opens a block before ending the current declaration
+fn is_selector(rest: &str) -> bool {
+    rest.find(['{', '}', ';'])
+        .map(|index| rest[index..].starts_with('{'))
+        .unwrap_or(false)
+}
+
+/// minify a stylesheet, keeping the quoted strings untouched
+///
+/// ```rust
+/// use mrml::util::minifier::minify_css;
+/// assert_eq!(
+///     minify_css("/* main */\n.title ,\n.text > a {\n  color : red;\n  content: ' ; ';\n}\n"),
+///     ".title,.text>a{color:red;content:' ; '}"
+/// );
+/// ```
+pub fn minify_css(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut chars = css.char_indices().peekable();
+    let mut pending_space = false;
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '/' if chars.peek().map(|(_, c)| *c) == Some('*') => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, c) in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                pending_space = true;
+            }
+            '"' | '\'' => {
+                if pending_space && !result.is_empty() {
+                    result.push(' ');
+                }
+                pending_space = false;
+                result.push(c);
+                for (_, inner) in chars.by_ref() {
+                    result.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => pending_space = true,
+            '{' | '}' | ';' | ',' | '>' | ':' => {
+                if c == '}' && result.ends_with(';') {
+                    result.pop();
+                }
+                // in a selector, the space before a colon separates an element from a pseudo class
+                if c == ':' && pending_space && is_selector(&css[index..]) {
+                    result.push(' ');
+                }
+                result.push(c);
+                pending_space = false;
+                while chars
+                    .peek()
+                    .map(|(_, c)| c.is_whitespace())
+                    .unwrap_or(false)
+                {
+                    chars.next();
+                }
+            }
+            c => {
+                if pending_space
+                    && !result.is_empty()
+                    && !result.ends_with(['{', '}', ';', ',', '>', ':'])
+                {
+                    result.push(' ');
+                }
+                pending_space = false;
+                result.push(c);
+            }
+        };
+    }
+    result
+}
+
+/// minify the generated html document
+///
+/// ```rust
+/// use mrml::util::minifier::minify;
+/// let result = minify("<div class=\"\">\n  <p>Hello   <b>World</b> !</p>\n  <!--[if mso]>\n<br/>\n<![endif]-->\n</div>");
+/// assert_eq!(result, "<div><p>Hello <b>World</b> !</p><!--[if mso]>\n<br/>\n<![endif]--></div>");
+/// ```
+pub fn minify(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut position = 0;
+    let mut previous: Option<&str> = None;
+    while position < html.len() {
+        let start = html[position..]
+            .find('<')
+            .map(|index| position + index)
+            .unwrap_or(html.len());
+        let end = if start == html.len() {
+            start
+        } else if html[start..].starts_with("<!--") {
+            html[start..]
+                .find("-->")
+                .map(|index| start + index + 3)
+                .unwrap_or(html.len())
+        } else {
+            tag_end(html, start)
+        };
+        let item = Some(&html[start..end]).filter(|item| !item.is_empty());
+        push_text(&mut result, &html[position..start], previous, item);
+        let item = match item {
+            Some(value) => value,
+            None => break,
+        };
+        position = end;
+        previous = Some(item);
+        if item.starts_with("<!") || item.starts_with("</") {
+            result.push_str(item);
+            continue;
+        }
+        result.push_str(&EMPTY_ATTRIBUTE.replace_all(item, ""));
+        let name = tag_name(item);
+        if item.ends_with("/>") {
+            continue;
+        }
+        if name == "style" || PRESERVED_ELEMENTS.contains(&name.as_str()) {
+            let close =
+                find_ignore_case(html, position, &format!("</{}", name)).unwrap_or(html.len());
+            let content = &html[position..close];
+            if name == "style" {
+                result.push_str(&minify_css(content));
+            } else {
+                result.push_str(content);
+            }
+            position = close;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserved_elements() {
+        let html = "<div>\n<pre>  a\n    b</pre>\n<textarea> x  y </textarea></div>";
+        assert_eq!(
+            minify(html),
+            "<div><pre>  a\n    b</pre> <textarea> x  y </textarea></div>"
+        );
+    }
+
+    #[test]
+    fn style_elements() {
+        let html = "<head>\n<style type=\"text/css\">\n  @media only screen and (min-width:480px) {\n    .mj-column-per-100 { width:100% !important; max-width: 100%; }\n  }\n</style>\n</head>";
+        assert_eq!(
+            minify(html),
+            "<head><style type=\"text/css\">@media only screen and (min-width:480px){.mj-column-per-100{width:100% !important;max-width:100%}}</style></head>"
+        );
+    }
+
+    #[test]
+    fn pseudo_classes() {
+        assert_eq!(
+            minify_css("a :hover , a:hover { color : red }"),
+            "a :hover,a:hover{color:red}"
+        );
+    }
+
+    #[test]
+    fn empty_attributes() {
+        assert_eq!(
+            minify("<td class=\"\" style=\"\" align=\"left\">a</td>"),
+            "<td align=\"left\">a</td>"
+        );
+    }
+
+    #[test]
+    fn quoted_greater_than() {
+        assert_eq!(
+            minify("<a title=\"a > b\">\n  link\n</a>"),
+            "<a title=\"a > b\"> link </a>"
+        );
+    }
+}
@@ -1,13 +1,25 @@
+pub mod asset;
 pub mod attributes;
 pub mod condition;
+pub mod config;
 pub mod context;
+pub mod css;
 pub mod fonts;
 pub mod header;
 pub mod id;
+pub mod include;
+pub mod inliner;
+pub mod link;
+pub mod mime;
+pub mod minifier;
+pub mod printer;
+pub mod schema;
 pub mod size;
 pub mod spacing;
 pub mod style;
 pub mod tag;
+pub mod template;
+pub mod text;
 
 use std::cmp::Ordering;
 
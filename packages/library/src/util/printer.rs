@@ -0,0 +1,194 @@
+use super::attributes::Attributes;
+
+/// options used when printing a template back to mjml
+#[derive(Clone, Debug)]
+pub struct PrintOptions {
+    /// put each element on its own line, indented according to its depth
+    pub pretty: bool,
+    /// number of spaces per level of indentation
+    pub indent: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            pretty: true,
+            indent: 2,
+        }
+    }
+}
+
+/// buffer used by the elements to print themselves as mjml
+///
+/// ```rust
+/// use mrml::util::attributes::Attributes;
+/// use mrml::util::printer::{PrintOptions, Printer};
+/// let mut printer = Printer::new(PrintOptions::default());
+/// printer.open("mj-section", &Attributes::default().add("padding", "0"));
+/// printer.empty("mj-column", &Attributes::default());
+/// printer.close("mj-section");
+/// assert_eq!(printer.finish(), "<mj-section padding=\"0\">\n  <mj-column />\n</mj-section>\n");
+/// ```
+#[derive(Debug)]
+pub struct Printer {
+    options: PrintOptions,
+    level: usize,
+    buffer: String,
+}
+
+impl Printer {
+    pub fn new(options: PrintOptions) -> Self {
+        Self {
+            options,
+            level: 0,
+            buffer: String::new(),
+        }
+    }
+
+    fn new_line(&mut self) {
+        if self.options.pretty && !self.buffer.is_empty() {
+            self.buffer.push('\n');
+            self.buffer
+                .push_str(&" ".repeat(self.level * self.options.indent));
+        }
+    }
+
+    fn push_tag(&mut self, tag: &str, attributes: &Attributes, closing: &str) {
+        self.buffer.push('<');
+        self.buffer.push_str(tag);
+        let mut entries = attributes.entries();
+        entries.sort();
+        for (key, value) in entries {
+            self.buffer.push(' ');
+            self.buffer.push_str(key);
+            self.buffer.push_str("=\"");
+            self.buffer.push_str(&value.replace('"', "&quot;"));
+            self.buffer.push('"');
+        }
+        self.buffer.push_str(closing);
+    }
+
+    /// print an element without children, like `<mj-divider />`
+    pub fn empty(&mut self, tag: &str, attributes: &Attributes) {
+        self.new_line();
+        self.push_tag(tag, attributes, " />");
+    }
+
+    /// print the opening tag of an element, the following elements being its children
+    pub fn open(&mut self, tag: &str, attributes: &Attributes) {
+        self.new_line();
+        self.push_tag(tag, attributes, ">");
+        self.level += 1;
+    }
+
+    pub fn close(&mut self, tag: &str) {
+        self.level -= 1;
+        self.new_line();
+        self.buffer.push_str("</");
+        self.buffer.push_str(tag);
+        self.buffer.push('>');
+    }
+
+    /// print an element with some text content, kept on a single line
+    pub fn element_with_content(&mut self, tag: &str, attributes: &Attributes, content: &str) {
+        if content.is_empty() {
+            self.empty(tag, attributes);
+        } else {
+            self.new_line();
+            self.push_tag(tag, attributes, ">");
+            self.buffer.push_str(content);
+            self.buffer.push_str("</");
+            self.buffer.push_str(tag);
+            self.buffer.push('>');
+        }
+    }
+
+    /// print the children of an element in the given closure, with the opening and closing
+    /// tags around them
+    pub fn element<F>(&mut self, tag: &str, attributes: &Attributes, empty: bool, children: F)
+    where
+        F: FnOnce(&mut Printer),
+    {
+        if empty {
+            self.empty(tag, attributes);
+        } else {
+            self.open(tag, attributes);
+            children(self);
+            self.close(tag);
+        }
+    }
+
+    /// print on a single line, without indentation, what the closure prints.
+    /// Used for html content where adding whitespaces would change the result.
+    pub fn compact<F>(&mut self, content: F)
+    where
+        F: FnOnce(&mut Printer),
+    {
+        self.new_line();
+        let pretty = self.options.pretty;
+        self.options.pretty = false;
+        content(self);
+        self.options.pretty = pretty;
+    }
+
+    pub fn comment(&mut self, value: &str) {
+        self.new_line();
+        self.buffer.push_str("<!--");
+        self.buffer.push_str(value);
+        self.buffer.push_str("-->");
+    }
+
+    pub fn text(&mut self, value: &str) {
+        if self.options.pretty {
+            self.new_line();
+            self.buffer.push_str(value.trim());
+        } else {
+            self.buffer.push_str(value);
+        }
+    }
+
+    pub fn finish(mut self) -> String {
+        if self.options.pretty && !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_content() {
+        let mut printer = Printer::new(PrintOptions {
+            pretty: true,
+            indent: 4,
+        });
+        printer.open("mj-column", &Attributes::default());
+        printer.compact(|printer| {
+            printer.open("mj-text", &Attributes::default().add("color", "\"red\""));
+            printer.text("Hello ");
+            printer.element_with_content("b", &Attributes::default(), "World");
+            printer.comment(" comment ");
+            printer.close("mj-text");
+        });
+        printer.close("mj-column");
+        assert_eq!(
+            printer.finish(),
+            "<mj-column>\n    <mj-text color=\"&quot;red&quot;\">Hello <b>World</b><!-- comment --></mj-text>\n</mj-column>\n"
+        );
+    }
+
+    #[test]
+    fn not_pretty() {
+        let mut printer = Printer::new(PrintOptions {
+            pretty: false,
+            indent: 2,
+        });
+        printer.element("mjml", &Attributes::default(), false, |printer| {
+            printer.empty("mj-body", &Attributes::default());
+        });
+        assert_eq!(printer.finish(), "<mjml><mj-body /></mjml>");
+    }
+}
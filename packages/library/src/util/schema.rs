@@ -0,0 +1,340 @@
+use super::attributes::Attributes;
+use super::template::has_placeholder;
+use regex::Regex;
+use std::fmt;
+
+/// type of the value expected by an attribute
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttributeType {
+    /// `true` or `false`
+    Boolean,
+    /// hexadecimal, `rgb()`, `rgba()` or named color
+    Color,
+    /// one of the given values
+    Enum(&'static [&'static str]),
+    /// positive integer
+    Integer,
+    /// value in pixels or percent, like `10px` or `50%`, the unitless values being pixels
+    Size,
+    /// one to four sizes, like `10px 25px`
+    Spacing,
+    /// any value
+    String,
+    /// absolute or relative url
+    Url,
+}
+
+lazy_static! {
+    static ref COLOR: Regex = Regex::new(
+        r"^(#([0-9a-fA-F]{3}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})|rgba?\([\d\s,.%]+\)|[a-zA-Z]+)$"
+    )
+    .unwrap();
+    static ref INTEGER: Regex = Regex::new(r"^\d+$").unwrap();
+    static ref NUMBER: Regex = Regex::new(r"^\d*\.?\d+$").unwrap();
+    static ref SIZE: Regex = Regex::new(r"^(\d*\.?\d+(px|%)|0)$").unwrap();
+}
+
+fn normalize_size(value: &str) -> String {
+    if value != "0" && NUMBER.is_match(value) {
+        format!("{}px", value)
+    } else {
+        value.to_string()
+    }
+}
+
+impl AttributeType {
+    /// rather the value matches the type once normalized, like the unitless sizes, the
+    /// `{{ variable }}` placeholders being only known when the template is rendered
+    pub fn is_valid(&self, value: &str) -> bool {
+        if has_placeholder(value) {
+            return true;
+        }
+        let value = self.normalize(value);
+        let value = value.as_str();
+        match self {
+            AttributeType::Boolean => value == "true" || value == "false",
+            AttributeType::Color => COLOR.is_match(value),
+            AttributeType::Enum(values) => values.contains(&value),
+            AttributeType::Integer => INTEGER.is_match(value),
+            AttributeType::Size => SIZE.is_match(value),
+            AttributeType::Spacing => {
+                let count = value.split_whitespace().count();
+                (1..=4).contains(&count) && value.split_whitespace().all(|v| SIZE.is_match(v))
+            }
+            AttributeType::String => true,
+            AttributeType::Url => !value.is_empty() && !value.contains(char::is_whitespace),
+        }
+    }
+
+    /// canonical form of the value, like `10px` for a size written `10`
+    pub fn normalize(&self, value: &str) -> String {
+        match self {
+            AttributeType::Boolean | AttributeType::Enum(_) => value.trim().to_lowercase(),
+            AttributeType::Color | AttributeType::Integer | AttributeType::Url => {
+                value.trim().to_string()
+            }
+            AttributeType::Size => normalize_size(value.trim()),
+            AttributeType::Spacing => value
+                .split_whitespace()
+                .map(normalize_size)
+                .collect::<Vec<_>>()
+                .join(" "),
+            AttributeType::String => value.to_string(),
+        }
+    }
+
+    /// name of the type, as exported in the json schema
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeType::Boolean => "boolean",
+            AttributeType::Color => "color",
+            AttributeType::Enum(_) => "enum",
+            AttributeType::Integer => "integer",
+            AttributeType::Size => "size",
+            AttributeType::Spacing => "spacing",
+            AttributeType::String => "string",
+            AttributeType::Url => "url",
+        }
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeType::Boolean => f.write_str("true or false"),
+            AttributeType::Color => f.write_str("a color"),
+            AttributeType::Enum(values) => write!(f, "one of {}", values.join(", ")),
+            AttributeType::Integer => f.write_str("an integer"),
+            AttributeType::Size => f.write_str("a size in px or %"),
+            AttributeType::Spacing => f.write_str("one to four sizes in px or %"),
+            AttributeType::String => f.write_str("a string"),
+            AttributeType::Url => f.write_str("a url"),
+        }
+    }
+}
+
+pub type AttributeGroup = &'static [(&'static str, AttributeType)];
+
+pub const ALIGN: AttributeType = AttributeType::Enum(&["left", "center", "right"]);
+pub const DIRECTION: AttributeType = AttributeType::Enum(&["ltr", "rtl"]);
+pub const VERTICAL_ALIGN: AttributeType = AttributeType::Enum(&["top", "middle", "bottom"]);
+
+/// attributes accepted by every element of the body
+pub const BODY_COMMON: AttributeGroup = &[
+    ("css-class", AttributeType::String),
+    ("mj-class", AttributeType::String),
+];
+pub const BORDER: AttributeGroup = &[
+    ("border", AttributeType::String),
+    ("border-bottom", AttributeType::String),
+    ("border-left", AttributeType::String),
+    ("border-radius", AttributeType::String),
+    ("border-right", AttributeType::String),
+    ("border-top", AttributeType::String),
+];
+pub const FONT: AttributeGroup = &[
+    ("color", AttributeType::Color),
+    ("font-family", AttributeType::String),
+    ("font-size", AttributeType::Size),
+    ("font-style", AttributeType::String),
+    ("font-weight", AttributeType::String),
+    ("line-height", AttributeType::String),
+    ("text-decoration", AttributeType::String),
+];
+pub const ICON: AttributeGroup = &[
+    ("icon-align", VERTICAL_ALIGN),
+    ("icon-height", AttributeType::Size),
+    ("icon-position", AttributeType::Enum(&["left", "right"])),
+    ("icon-unwrapped-alt", AttributeType::String),
+    ("icon-unwrapped-url", AttributeType::Url),
+    ("icon-width", AttributeType::Size),
+    ("icon-wrapped-alt", AttributeType::String),
+    ("icon-wrapped-url", AttributeType::Url),
+];
+pub const LINK: AttributeGroup = &[
+    ("href", AttributeType::Url),
+    ("name", AttributeType::String),
+    ("rel", AttributeType::String),
+    ("target", AttributeType::String),
+    ("title", AttributeType::String),
+];
+pub const PADDING: AttributeGroup = &[
+    ("padding", AttributeType::Spacing),
+    ("padding-bottom", AttributeType::Size),
+    ("padding-left", AttributeType::Size),
+    ("padding-right", AttributeType::Size),
+    ("padding-top", AttributeType::Size),
+];
+
+/// allowed children and attributes of an element
+///
+/// ```rust
+/// use mrml::util::schema::{AttributeType, Schema, PADDING};
+/// let schema = Schema::new()
+///     .add("color", AttributeType::Color)
+///     .extend(PADDING);
+/// assert_eq!(schema.get("padding"), Some(&AttributeType::Spacing));
+/// assert!(schema.get("width").is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    /// allowed children, `None` when the content is not made of mjml elements
+    children: Option<Vec<&'static str>>,
+    attributes: Vec<(&'static str, AttributeType)>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn children(mut self, names: &[&'static str]) -> Self {
+        self.children = Some(names.to_vec());
+        self
+    }
+
+    pub fn add(mut self, name: &'static str, kind: AttributeType) -> Self {
+        match self.attributes.iter_mut().find(|(key, _)| *key == name) {
+            Some(item) => item.1 = kind,
+            None => self.attributes.push((name, kind)),
+        };
+        self
+    }
+
+    pub fn extend(self, group: AttributeGroup) -> Self {
+        group
+            .iter()
+            .fold(self, |res, (name, kind)| res.add(name, *kind))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AttributeType> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, kind)| kind)
+    }
+
+    pub fn attributes(&self) -> &[(&'static str, AttributeType)] {
+        &self.attributes
+    }
+
+    pub fn allowed_children(&self) -> Option<&[&'static str]> {
+        self.children.as_deref()
+    }
+
+    /// normalize the values of the known attributes
+    pub fn normalize(&self, attributes: Attributes) -> Attributes {
+        let entries: Vec<(String, String)> = attributes
+            .inner()
+            .iter()
+            .map(|(key, value)| match self.get(key) {
+                Some(kind) => (key.clone(), kind.normalize(value)),
+                None => (key.clone(), value.clone()),
+            })
+            .collect();
+        Attributes::default().concat_iter(entries.into_iter())
+    }
+
+    /// json representation of the schema, used for the editor autocompletion
+    pub fn to_json(&self) -> String {
+        let children = match self.children.as_ref() {
+            Some(list) => format!(
+                "[{}]",
+                list.iter()
+                    .map(|name| quote(name))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            None => "null".into(),
+        };
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(name, kind)| {
+                let values = match kind {
+                    AttributeType::Enum(values) => format!(
+                        ",\"values\":[{}]",
+                        values
+                            .iter()
+                            .map(|value| quote(value))
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    ),
+                    _ => String::new(),
+                };
+                format!(
+                    "{}:{{\"type\":{}{}}}",
+                    quote(name),
+                    quote(kind.name()),
+                    values
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"children\":{},\"attributes\":{{{}}}}}",
+            children, attributes
+        )
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn attribute_types() {
+        assert!(AttributeType::Color.is_valid("#abc"));
+        assert!(AttributeType::Color.is_valid("rgba(0, 0, 0, 0.5)"));
+        assert!(!AttributeType::Color.is_valid("#abcd1"));
+        assert!(AttributeType::Size.is_valid("12.5px"));
+        assert!(AttributeType::Size.is_valid("0"));
+        assert!(AttributeType::Size.is_valid(" 420 "));
+        assert!(AttributeType::Spacing.is_valid("10 0"));
+        assert!(AttributeType::Enum(&["left"]).is_valid("LEFT"));
+        assert!(!AttributeType::Size.is_valid("abc"));
+        assert!(AttributeType::Spacing.is_valid("0 10px 20% 4px"));
+        assert!(!AttributeType::Spacing.is_valid("1px 2px 3px 4px 5px"));
+        assert!(AttributeType::Boolean.is_valid("false"));
+        assert!(!AttributeType::Url.is_valid("http://example.com/a b"));
+        assert!(AttributeType::Url.is_valid("{{ url }}"));
+        assert!(AttributeType::Color.is_valid("{{ brand }}"));
+        assert!(AttributeType::Size.is_valid("{{{ width }}}"));
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(AttributeType::Size.normalize(" 10 "), "10px");
+        assert_eq!(AttributeType::Size.normalize("0"), "0");
+        assert_eq!(AttributeType::Size.normalize("abc"), "abc");
+        assert_eq!(
+            AttributeType::Spacing.normalize("10  20px 0"),
+            "10px 20px 0"
+        );
+        assert_eq!(AttributeType::Enum(&["left"]).normalize("LEFT"), "left");
+        let schema = Schema::new().add("padding", AttributeType::Spacing);
+        let attributes = schema.normalize(
+            Attributes::default()
+                .add("padding", "4 8")
+                .add("foo", " bar "),
+        );
+        assert_eq!(attributes.get("padding").unwrap(), "4px 8px");
+        assert_eq!(attributes.get("foo").unwrap(), " bar ");
+    }
+
+    #[test]
+    fn to_json() {
+        let schema = Schema::new()
+            .children(&["mj-column"])
+            .add("direction", DIRECTION)
+            .add("width", AttributeType::Size);
+        assert_eq!(
+            schema.to_json(),
+            r#"{"children":["mj-column"],"attributes":{"direction":{"type":"enum","values":["ltr","rtl"]},"width":{"type":"size"}}}"#
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! interpolation of `{{ variable }}` placeholders in the generated html, to render the same
+//! template for many recipients without parsing and rendering it again.
+//!
+//! The values are escaped according to where the placeholder is, in a text or in an
+//! attribute value, and `{{{ variable }}}` inserts the value without escaping it.
+//! The content of the `style` and `script` elements and the comments are never interpolated,
+//! apart from the conditional comments of outlook holding html.
+
+use super::inliner::find_ignore_case;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// values of the variables used in a template
+pub trait Variables {
+    fn get(&self, name: &str) -> Option<String>;
+}
+
+impl<K, V, S> Variables for HashMap<K, V, S>
+where
+    K: Borrow<str> + Eq + Hash,
+    V: ToString,
+    S: BuildHasher,
+{
+    fn get(&self, name: &str) -> Option<String> {
+        HashMap::get(self, name).map(|value| value.to_string())
+    }
+}
+
+impl<K, V> Variables for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+    V: ToString,
+{
+    fn get(&self, name: &str) -> Option<String> {
+        BTreeMap::get(self, name).map(|value| value.to_string())
+    }
+}
+
+/// where a placeholder is located, defining how its value is escaped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Escape {
+    /// in the text of an element
+    Text,
+    /// in the value of an attribute
+    Attribute,
+    /// inserted as is, with `{{{ variable }}}`
+    Raw,
+}
+
+#[derive(Debug)]
+pub enum TemplateError {
+    /// no value has been provided for the variable
+    MissingVariable(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::MissingVariable(name) => write!(f, "missing variable \"{}\"", name),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// escape the value to be inserted in the html
+///
+/// ```rust
+/// use mrml::util::template::{escape, Escape};
+/// assert_eq!(escape("<b>Tom & \"Jerry\"</b>", Escape::Text), "&lt;b&gt;Tom &amp; \"Jerry\"&lt;/b&gt;");
+/// assert_eq!(escape("Tom & \"Jerry\"", Escape::Attribute), "Tom &amp; &quot;Jerry&quot;");
+/// ```
+pub fn escape(value: &str, mode: Escape) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match (c, mode) {
+            (_, Escape::Raw) => result.push(c),
+            ('&', _) => result.push_str("&amp;"),
+            ('<', _) => result.push_str("&lt;"),
+            ('>', _) => result.push_str("&gt;"),
+            ('"', Escape::Attribute) => result.push_str("&quot;"),
+            ('\'', Escape::Attribute) => result.push_str("&#39;"),
+            _ => result.push(c),
+        };
+    }
+    result
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Variable(String, Escape),
+}
+
+/// placeholder starting the text, with its variable name and its length
+fn parse_placeholder(input: &str) -> Option<(&str, bool, usize)> {
+    let (raw, open, close) = if input.starts_with("{{{") {
+        (true, "{{{", "}}}")
+    } else if input.starts_with("{{") {
+        (false, "{{", "}}")
+    } else {
+        return None;
+    };
+    let end = input.find(close)?;
+    let name = input[open.len()..end].trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-');
+    if valid {
+        Some((name, raw, end + close.len()))
+    } else {
+        None
+    }
+}
+
+/// rather the value contains a `{{ variable }}` placeholder
+///
+/// ```rust
+/// use mrml::util::template::has_placeholder;
+/// assert!(has_placeholder("https://example.com/{{ path }}"));
+/// assert!(!has_placeholder("{{ not a variable }}"));
+/// ```
+pub fn has_placeholder(value: &str) -> bool {
+    value
+        .match_indices("{{")
+        .any(|(index, _)| parse_placeholder(&value[index..]).is_some())
+}
+
+/// generated html split on its placeholders
+///
+/// ```rust
+/// use mrml::util::template::Template;
+/// use std::collections::HashMap;
+/// let template = Template::parse(r#"<a href="{{ url }}" style="color:red">Hello {{name}}</a>"#);
+/// let mut values = HashMap::new();
+/// values.insert("url", "https://example.com/?a=1&b=2");
+/// values.insert("name", "<Jane>");
+/// assert_eq!(
+///     template.render(&values).unwrap(),
+///     r#"<a href="https://example.com/?a=1&amp;b=2" style="color:red">Hello &lt;Jane&gt;</a>"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(html: &str) -> Self {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut in_tag = false;
+        let mut tag = String::new();
+        let mut quote: Option<char> = None;
+        let mut position = 0;
+        while position < html.len() {
+            let rest = &html[position..];
+            if let Some((name, raw, length)) = parse_placeholder(rest) {
+                let mode = match (raw, in_tag) {
+                    (true, _) => Escape::Raw,
+                    (false, true) => Escape::Attribute,
+                    (false, false) => Escape::Text,
+                };
+                parts.push(Part::Literal(std::mem::take(&mut literal)));
+                parts.push(Part::Variable(name.to_string(), mode));
+                position += length;
+                continue;
+            }
+            if !in_tag && rest.starts_with("<!--") && !rest.starts_with("<!--[if") {
+                let end = rest
+                    .find("-->")
+                    .map(|index| index + 3)
+                    .unwrap_or(rest.len());
+                literal.push_str(&rest[..end]);
+                position += end;
+                continue;
+            }
+            let c = rest.chars().next().unwrap_or_default();
+            let self_closing = literal.ends_with('/');
+            literal.push(c);
+            position += c.len_utf8();
+            match (in_tag, quote, c) {
+                (true, Some(q), c) if q == c => quote = None,
+                (true, None, '"') | (true, None, '\'') => quote = Some(c),
+                (true, None, '>') => {
+                    in_tag = false;
+                    // the content of the style and script elements is kept as is
+                    if !self_closing && (tag == "script" || tag == "style") {
+                        let close = find_ignore_case(html, position, &format!("</{}", tag))
+                            .unwrap_or(html.len());
+                        literal.push_str(&html[position..close]);
+                        position = close;
+                    }
+                }
+                (false, _, '<') if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    in_tag = true;
+                    tag = rest[1..]
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ':')
+                        .collect::<String>()
+                        .to_lowercase();
+                }
+                _ => (),
+            };
+        }
+        parts.push(Part::Literal(literal));
+        parts.retain(|part| part != &Part::Literal(String::new()));
+        Self { parts }
+    }
+
+    /// split a plain text, like the subject of an email, on its placeholders, the values
+    /// being inserted without escaping them
+    ///
+    /// ```rust
+    /// use mrml::util::template::Template;
+    /// use std::collections::HashMap;
+    /// let template = Template::parse_text("Hello {{ name }}");
+    /// let mut values = HashMap::new();
+    /// values.insert("name", "Tom & Jerry");
+    /// assert_eq!(template.render(&values).unwrap(), "Hello Tom & Jerry");
+    /// ```
+    pub fn parse_text(text: &str) -> Self {
+        Self::parse_value(text, Escape::Raw)
+    }
+
+    /// split the value of an attribute on its placeholders, the values being escaped
+    /// unless written `{{{ variable }}}`
+    ///
+    /// ```rust
+    /// use mrml::util::template::Template;
+    /// use std::collections::HashMap;
+    /// let template = Template::parse_attribute("https://example.com/?id={{ id }}");
+    /// let mut values = HashMap::new();
+    /// values.insert("id", "1&2");
+    /// assert_eq!(template.render(&values).unwrap(), "https://example.com/?id=1&amp;2");
+    /// ```
+    pub fn parse_attribute(value: &str) -> Self {
+        Self::parse_value(value, Escape::Attribute)
+    }
+
+    fn parse_value(text: &str, mode: Escape) -> Self {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut position = 0;
+        while position < text.len() {
+            let rest = &text[position..];
+            if let Some((name, raw, length)) = parse_placeholder(rest) {
+                let mode = if raw { Escape::Raw } else { mode };
+                parts.push(Part::Literal(std::mem::take(&mut literal)));
+                parts.push(Part::Variable(name.to_string(), mode));
+                position += length;
+                continue;
+            }
+            let c = rest.chars().next().unwrap_or_default();
+            literal.push(c);
+            position += c.len_utf8();
+        }
+        parts.push(Part::Literal(literal));
+        parts.retain(|part| part != &Part::Literal(String::new()));
+        Self { parts }
+    }
+
+    /// names of the variables used in the template
+    pub fn variables(&self) -> Vec<&str> {
+        let mut result = vec![];
+        for part in self.parts.iter() {
+            if let Part::Variable(name, _) = part {
+                if !result.contains(&name.as_str()) {
+                    result.push(name.as_str());
+                }
+            }
+        }
+        result
+    }
+
+    pub fn render<V: Variables + ?Sized>(&self, values: &V) -> Result<String, TemplateError> {
+        let mut result = String::new();
+        for part in self.parts.iter() {
+            match part {
+                Part::Literal(value) => result.push_str(value),
+                Part::Variable(name, mode) => {
+                    let value = values
+                        .get(name)
+                        .ok_or_else(|| TemplateError::MissingVariable(name.clone()))?;
+                    result.push_str(&escape(&value, *mode));
+                }
+            };
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_and_scripts() {
+        let template = Template::parse(
+            "<style>.a{{color:red}} {{ name }}</style><script>var a = {{ name }};</script><p>{{ name }}</p>",
+        );
+        assert_eq!(template.variables(), vec!["name"]);
+        let mut values = BTreeMap::new();
+        values.insert("name".to_string(), "Jane & John");
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "<style>.a{{color:red}} {{ name }}</style><script>var a = {{ name }};</script><p>Jane &amp; John</p>"
+        );
+    }
+
+    #[test]
+    fn raw_and_invalid_placeholders() {
+        let template = Template::parse("<p>{{{ content }}} {{ not a variable }} {{}}</p>");
+        let mut values = HashMap::new();
+        values.insert("content", "<b>bold</b>");
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "<p><b>bold</b> {{ not a variable }} {{}}</p>"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let template = Template::parse(
+            "<!-- {{ hidden }} --><p>{{ name }}</p><!--[if mso]><a href=\"{{ url }}\"></a><![endif]-->",
+        );
+        assert_eq!(template.variables(), vec!["name", "url"]);
+        let mut values = HashMap::new();
+        values.insert("name", "Jane");
+        values.insert("url", "https://example.com");
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "<!-- {{ hidden }} --><p>Jane</p><!--[if mso]><a href=\"https://example.com\"></a><![endif]-->"
+        );
+    }
+
+    #[test]
+    fn missing_variable() {
+        let template = Template::parse("<p>{{ name }}</p>");
+        let values: HashMap<String, String> = HashMap::new();
+        assert!(matches!(
+            template.render(&values),
+            Err(TemplateError::MissingVariable(name)) if name == "name"
+        ));
+    }
+}
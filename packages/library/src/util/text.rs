@@ -0,0 +1,279 @@
+//! plain text rendering of a template, used as the `text/plain` alternative of the html.
+//!
+//! The elements write their content as paragraphs, the links and images being written
+//! like `[label](url)` and the paragraphs being wrapped at the configured width.
+
+use super::header::Header;
+
+/// options used when rendering a template as plain text
+#[derive(Clone, Debug)]
+pub struct TextOptions {
+    /// maximum number of characters per line, `None` to disable the wrapping
+    pub line_width: Option<usize>,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            line_width: Some(78),
+        }
+    }
+}
+
+/// width of the rules when the wrapping is disabled
+const DEFAULT_RULE_WIDTH: usize = 40;
+
+/// replace the most common html entities by the character they represent
+///
+/// ```rust
+/// use mrml::util::text::decode_entities;
+/// assert_eq!(decode_entities("Tom &amp; Jerry&nbsp;&#169; &#x2014; &unknown;"), "Tom & Jerry\u{a0}© — &unknown;");
+/// ```
+pub fn decode_entities(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|end| *end < 10).and_then(|end| {
+            let name = &rest[1..end];
+            let value = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                }
+                _ if name.starts_with('#') => {
+                    name[1..].parse::<u32>().ok().and_then(std::char::from_u32)
+                }
+                _ => None,
+            };
+            value.map(|value| (value, end))
+        });
+        match decoded {
+            Some((value, end)) => {
+                result.push(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn wrap_line(result: &mut String, line: &str, width: Option<usize>) {
+    let width = match width {
+        Some(value) => value,
+        None => {
+            result.push_str(line);
+            return;
+        }
+    };
+    let mut current = 0;
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+        let length = word.chars().count();
+        if current > 0 && current + 1 + length > width {
+            result.push('\n');
+            current = 0;
+        } else if current > 0 {
+            result.push(' ');
+            current += 1;
+        }
+        result.push_str(word);
+        current += length;
+    }
+}
+
+/// buffer used by the elements to write themselves as plain text
+///
+/// ```rust
+/// use mrml::util::text::{TextOptions, TextWriter};
+/// let mut writer = TextWriter::new(TextOptions { line_width: Some(12) });
+/// writer.text("Hello   dear\n  reader,");
+/// writer.block();
+/// writer.link("mj-button", "Read more", Some("https://example.com"));
+/// assert_eq!(writer.finish(), "Hello dear\nreader,\n\n[Read more](https://example.com)\n");
+/// ```
+#[derive(Debug)]
+pub struct TextWriter {
+    options: TextOptions,
+    header: Option<Header>,
+    buffer: String,
+    current: String,
+}
+
+impl TextWriter {
+    pub fn new(options: TextOptions) -> Self {
+        Self {
+            options,
+            header: None,
+            buffer: String::new(),
+            current: String::new(),
+        }
+    }
+
+    /// give the links to the link rewriter of the header, like the html does
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    fn rewrite_link(&self, element: &str, url: &str) -> String {
+        match self.header.as_ref() {
+            Some(header) => header.rewrite_link(element, url),
+            None => url.to_string(),
+        }
+    }
+
+    fn push_paragraph(&mut self, paragraph: &str) {
+        if !self.buffer.is_empty() {
+            self.buffer.push_str("\n\n");
+        }
+        self.buffer.push_str(paragraph);
+    }
+
+    /// append some text to the current paragraph, collapsing its whitespaces
+    pub fn text(&mut self, value: &str) {
+        let value = decode_entities(value);
+        for c in value.chars() {
+            if c.is_whitespace() && c != '\u{a0}' {
+                if !self.current.is_empty() && !self.current.ends_with([' ', '\n', '\u{a0}']) {
+                    self.current.push(' ');
+                }
+            } else {
+                self.current.push(c);
+            }
+        }
+    }
+
+    /// append a link emitted by the element to the current paragraph, only the label when
+    /// there is no url
+    ///
+    /// The link is never split when wrapping the lines.
+    pub fn link(&mut self, element: &str, label: &str, url: Option<&str>) {
+        let label = decode_entities(label);
+        let words = label.split_whitespace().collect::<Vec<_>>();
+        match url.filter(|url| !url.is_empty()) {
+            Some(url) if words.is_empty() || label.trim() == url => {
+                let url = self.rewrite_link(element, url);
+                self.text(&url)
+            }
+            Some(url) => {
+                let url = self.rewrite_link(element, url);
+                self.text(&format!("[{}]({})", words.join("\u{a0}"), url))
+            }
+            None => self.text(&words.join(" ")),
+        };
+    }
+
+    /// start a new line in the current paragraph, as an item of a list
+    pub fn list_item(&mut self) {
+        self.line_break();
+        self.current.push_str("-\u{a0}");
+    }
+
+    /// append an image of the element to the current paragraph, like a link when it has one
+    pub fn image(&mut self, element: &str, alt: Option<&str>, href: Option<&str>) {
+        let alt = alt.unwrap_or_default();
+        match href.filter(|href| !href.is_empty()) {
+            Some(href) => self.link(element, alt, Some(href)),
+            None if alt.trim().is_empty() => (),
+            None => self.text(&format!("[{}]", alt.trim())),
+        };
+    }
+
+    /// text written by the given function, on a single line
+    pub fn capture<F: FnOnce(&mut TextWriter)>(func: F) -> String {
+        let mut writer = TextWriter::new(TextOptions { line_width: None });
+        func(&mut writer);
+        writer
+            .finish()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// start a new line in the current paragraph
+    pub fn line_break(&mut self) {
+        while self.current.ends_with(' ') {
+            self.current.pop();
+        }
+        self.current.push('\n');
+    }
+
+    /// end the current paragraph, the following content starting a new one
+    pub fn block(&mut self) {
+        let current = std::mem::take(&mut self.current);
+        let mut paragraph = String::new();
+        for line in current.lines() {
+            let line = line.trim_matches(' ');
+            if line.is_empty() {
+                continue;
+            }
+            if !paragraph.is_empty() {
+                paragraph.push('\n');
+            }
+            wrap_line(&mut paragraph, line, self.options.line_width);
+        }
+        if !paragraph.is_empty() {
+            self.push_paragraph(&paragraph.replace('\u{a0}', " "));
+        }
+    }
+
+    /// write a horizontal rule as its own paragraph
+    pub fn rule(&mut self) {
+        self.block();
+        let width = self.options.line_width.unwrap_or(DEFAULT_RULE_WIDTH);
+        self.push_paragraph(&"-".repeat(width));
+    }
+
+    pub fn finish(mut self) -> String {
+        self.block();
+        if !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_words() {
+        let mut writer = TextWriter::new(TextOptions {
+            line_width: Some(10),
+        });
+        writer.text("see https://example.com/some/long/path now");
+        assert_eq!(
+            writer.finish(),
+            "see\nhttps://example.com/some/long/path\nnow\n"
+        );
+    }
+
+    #[test]
+    fn line_breaks_and_rules() {
+        let mut writer = TextWriter::new(TextOptions { line_width: None });
+        writer.text("first ");
+        writer.line_break();
+        writer.text(" second");
+        writer.rule();
+        writer.block();
+        writer.text("third");
+        assert_eq!(
+            writer.finish(),
+            format!("first\nsecond\n\n{}\n\nthird\n", "-".repeat(40))
+        );
+    }
+}
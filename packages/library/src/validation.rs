@@ -0,0 +1,515 @@
+use crate::elements::error::{Error, IncludeError};
+use crate::elements::include::{Include, IncludeType};
+use crate::elements::schema;
+use crate::error::Location;
+use crate::parser::{self, Element, Node, Span};
+use crate::util::header::Header;
+use crate::util::schema::{AttributeType, Schema};
+use std::fmt;
+use std::str::FromStr;
+
+/// how strictly the templates are checked before rendering
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ValidationLevel {
+    /// the rendering fails when the template has errors
+    Strict,
+    /// the findings are logged as warnings, or returned by
+    /// [`parse_with_report`](crate::parse_with_report), and the template is rendered anyway
+    Soft,
+    /// the template is not validated
+    #[default]
+    Skip,
+}
+
+impl FromStr for ValidationLevel {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "strict" => Ok(ValidationLevel::Strict),
+            "soft" => Ok(ValidationLevel::Soft),
+            "skip" => Ok(ValidationLevel::Skip),
+            other => Err(format!("unknown validation level \"{}\"", other)),
+        }
+    }
+}
+
+impl fmt::Display for ValidationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationLevel::Strict => f.write_str("strict"),
+            ValidationLevel::Soft => f.write_str("soft"),
+            ValidationLevel::Skip => f.write_str("skip"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// the template will not render as expected
+    Error,
+    /// the template renders but something is ignored
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FindingKind {
+    /// the `mj-*` element is not known
+    UnknownElement(String),
+    /// the element is not allowed in its parent
+    InvalidChild { parent: String, child: String },
+    /// the attribute is not used by the element
+    UnknownAttribute { element: String, attribute: String },
+    /// the value doesn't match the type of the attribute
+    InvalidAttribute {
+        element: String,
+        attribute: String,
+        value: String,
+        expected: AttributeType,
+    },
+}
+
+impl FindingKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::UnknownAttribute { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for FindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FindingKind::UnknownElement(name) => write!(f, "unknown element <{}>", name),
+            FindingKind::InvalidChild { parent, child } => {
+                write!(f, "element <{}> is not allowed in <{}>", child, parent)
+            }
+            FindingKind::UnknownAttribute { element, attribute } => {
+                write!(f, "unknown attribute \"{}\" on <{}>", attribute, element)
+            }
+            FindingKind::InvalidAttribute {
+                element,
+                attribute,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value \"{}\" for attribute \"{}\" on <{}>, expected {}",
+                value, attribute, element, expected
+            ),
+        }
+    }
+}
+
+/// problem found in a template
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// location in the file containing the finding
+    pub location: Location,
+    /// path of the included template containing the finding, `None` for the validated one
+    pub file: Option<String>,
+}
+
+impl Finding {
+    fn new(source: &str, file: Option<&str>, kind: FindingKind, span: Span) -> Self {
+        Self {
+            severity: kind.severity(),
+            kind,
+            location: Location::from_span(source, span),
+            file: file.map(String::from),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at line {}, column {}",
+            self.severity, self.kind, self.location.line, self.location.column
+        )?;
+        if let Some(file) = self.file.as_ref() {
+            write!(f, " of {:?}", file)?;
+        }
+        if !self.location.path.is_empty() {
+            write!(f, " ({})", self.location.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// result of the validation of a template
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// findings in the order of the template
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|item| item.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|item| item.severity == Severity::Warning)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in self.findings.iter() {
+            writeln!(f, "{}", finding)?;
+        }
+        Ok(())
+    }
+}
+
+struct Validator<'s> {
+    source: &'s str,
+    file: Option<&'s str>,
+    header: &'s Header,
+    report: Report,
+}
+
+impl<'s> Validator<'s> {
+    fn push(&mut self, kind: FindingKind, span: Span) {
+        self.report
+            .findings
+            .push(Finding::new(self.source, self.file, kind, span));
+    }
+
+    fn check_attributes(&mut self, node: &Node, schema: &Schema) {
+        let element = node.name.as_str();
+        for (key, value) in node.attributes.iter() {
+            match schema.get(key.as_str()) {
+                None => self.push(
+                    FindingKind::UnknownAttribute {
+                        element: element.to_string(),
+                        attribute: key.as_str().to_string(),
+                    },
+                    key.into(),
+                ),
+                Some(kind) if !kind.is_valid(value.as_str()) => self.push(
+                    FindingKind::InvalidAttribute {
+                        element: element.to_string(),
+                        attribute: key.as_str().to_string(),
+                        value: value.as_str().to_string(),
+                        expected: *kind,
+                    },
+                    value.into(),
+                ),
+                _ => (),
+            };
+        }
+    }
+
+    fn check_node(&mut self, node: &Node) -> Result<(), Error> {
+        let schema = match schema::get(node.name.as_str()) {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+        self.check_attributes(node, schema);
+        match schema.allowed_children() {
+            Some(allowed) => self.check_children(node, &node.children, allowed),
+            None => Ok(()),
+        }
+    }
+
+    fn check_children(
+        &mut self,
+        parent: &Node,
+        children: &[Element],
+        allowed: &[&str],
+    ) -> Result<(), Error> {
+        for child in children.iter() {
+            let child = match child {
+                Element::Node(child) => child,
+                _ => continue,
+            };
+            let name = child.name.as_str();
+            if name.starts_with("mj-") && schema::get(name).is_none() {
+                self.push(
+                    FindingKind::UnknownElement(name.to_string()),
+                    child.span.into(),
+                );
+            } else if name == "mj-include" {
+                self.check_node(child)?;
+                self.check_include(parent, child, allowed)?;
+            } else if !allowed.contains(&name) {
+                self.push(
+                    FindingKind::InvalidChild {
+                        parent: parent.name.as_str().to_string(),
+                        child: name.to_string(),
+                    },
+                    child.span.into(),
+                );
+            } else {
+                self.check_node(child)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// check the elements of an included template, a list of elements being checked as
+    /// children of the parent of the `mj-include`
+    fn check_include(&mut self, parent: &Node, node: &Node, allowed: &[&str]) -> Result<(), Error> {
+        let include = Include::parse(node)?;
+        if include.kind != IncludeType::Mjml {
+            return Ok(());
+        }
+        let template = include.load(self.header)?;
+        let content = template.content.as_str();
+        let mut validator = Validator {
+            source: content,
+            file: Some(template.path.as_str()),
+            header: &template.header,
+            report: Report::default(),
+        };
+        let parse_error = |err| {
+            template.error(IncludeError::Template(Box::new(crate::Error::from_parser(
+                content, err,
+            ))))
+        };
+        let result = if template.is_document() {
+            let root = parser::parse(content).map_err(parse_error)?;
+            validator.check_node(&root)
+        } else {
+            let children = parser::parse_fragment(content).map_err(parse_error)?;
+            validator.check_children(parent, &children, allowed)
+        };
+        result.map_err(|err| {
+            template.error(IncludeError::Template(Box::new(crate::Error::from_mjml(
+                content, err,
+            ))))
+        })?;
+        self.report.findings.extend(validator.report.findings);
+        Ok(())
+    }
+}
+
+/// validate the parsed template against the mjml rules, the included templates being
+/// loaded and validated too
+pub fn validate(source: &str, root: &Node, header: &Header) -> Result<Report, Error> {
+    let mut validator = Validator {
+        source,
+        file: None,
+        header,
+        report: Report::default(),
+    };
+    validator.check_node(root)?;
+    Ok(validator.report)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::util::include::MemoryIncludeLoader;
+    use crate::{to_html, Options};
+    use std::sync::Arc;
+
+    fn report(template: &str) -> Report {
+        crate::validate(template, &Options::default()).unwrap()
+    }
+
+    #[test]
+    fn valid_template() {
+        let result = report(include_str!("../test/mj-group.mjml"));
+        assert!(result.findings.is_empty(), "{}", result);
+    }
+
+    #[test]
+    fn documented_attributes() {
+        let result = report(include_str!("../test/mj-spacer.mjml"));
+        assert!(result.findings.is_empty(), "{}", result);
+    }
+
+    #[test]
+    fn unitless_sizes() {
+        let result = report(r#"<mjml><mj-body width="420"></mj-body></mjml>"#);
+        assert!(result.findings.is_empty(), "{}", result);
+        let result = report(r#"<mjml><mj-body width="wide"></mj-body></mjml>"#);
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn unknown_element() {
+        let template = "<mjml>\n  <mj-body>\n    <mj-unknown />\n  </mj-body>\n</mjml>";
+        let result = report(template);
+        assert_eq!(result.findings.len(), 1);
+        let finding = &result.findings[0];
+        assert_eq!(
+            finding.kind,
+            FindingKind::UnknownElement("mj-unknown".into())
+        );
+        assert_eq!(finding.severity, Severity::Error);
+        assert_eq!(finding.location.line, 3);
+        assert_eq!(finding.location.column, 5);
+    }
+
+    #[test]
+    fn invalid_child() {
+        let template =
+            "<mjml><mj-body><mj-column><mj-text>Hello</mj-text></mj-column></mj-body></mjml>";
+        let result = report(template);
+        assert_eq!(result.findings.len(), 1);
+        assert_eq!(
+            result.findings[0].kind,
+            FindingKind::InvalidChild {
+                parent: "mj-body".into(),
+                child: "mj-column".into(),
+            }
+        );
+        assert_eq!(
+            result.findings[0].location.path,
+            "mjml > mj-body > mj-column"
+        );
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn attributes() {
+        let template = r#"<mjml><mj-body><mj-section><mj-column><mj-text padding="ten" color="red" foo="bar">Hello</mj-text></mj-column></mj-section></mj-body></mjml>"#;
+        let result = report(template);
+        assert_eq!(result.findings.len(), 2);
+        let error = result.errors().next().unwrap();
+        assert_eq!(
+            error.kind,
+            FindingKind::InvalidAttribute {
+                element: "mj-text".into(),
+                attribute: "padding".into(),
+                value: "ten".into(),
+                expected: AttributeType::Spacing,
+            }
+        );
+        assert_eq!(
+            &template[error.location.span.start..error.location.span.end],
+            "ten"
+        );
+        let warning = result.warnings().next().unwrap();
+        assert_eq!(
+            warning.kind,
+            FindingKind::UnknownAttribute {
+                element: "mj-text".into(),
+                attribute: "foo".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn included_templates() {
+        let mut loader = MemoryIncludeLoader::default();
+        loader.insert(
+            "partials/header.mjml",
+            "<mj-column>\n  <mj-text />\n</mj-column>",
+        );
+        loader.insert(
+            "partials/footer.mjml",
+            "<mjml><mj-body><mj-section><mj-unknown /></mj-section></mj-body></mjml>",
+        );
+        let options = Options {
+            include_loader: Arc::new(loader),
+            ..Options::default()
+        };
+        let template = r#"<mjml><mj-body><mj-include path="partials/header.mjml" /><mj-include path="partials/footer.mjml" /></mj-body></mjml>"#;
+        let result = crate::validate(template, &options).unwrap();
+        assert_eq!(result.findings.len(), 2);
+        let finding = &result.findings[0];
+        assert_eq!(
+            finding.kind,
+            FindingKind::InvalidChild {
+                parent: "mj-body".into(),
+                child: "mj-column".into(),
+            }
+        );
+        assert_eq!(finding.file.as_deref(), Some("partials/header.mjml"));
+        assert_eq!(finding.location.line, 1);
+        let finding = &result.findings[1];
+        assert_eq!(
+            finding.kind,
+            FindingKind::UnknownElement("mj-unknown".into())
+        );
+        assert_eq!(finding.file.as_deref(), Some("partials/footer.mjml"));
+        assert!(finding.to_string().contains(r#"of "partials/footer.mjml""#));
+
+        let strict = Options {
+            validation_level: ValidationLevel::Strict,
+            ..options
+        };
+        let error = to_html(template, strict).unwrap_err();
+        assert_eq!(error.report().unwrap().errors().count(), 2);
+    }
+
+    #[test]
+    fn missing_include() {
+        let template = r#"<mjml><mj-body><mj-include path="missing.mjml" /></mj-body></mjml>"#;
+        let options = Options {
+            include_loader: Arc::new(MemoryIncludeLoader::default()),
+            ..Options::default()
+        };
+        let error = crate::validate(template, &options).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unable to include \"missing.mjml\""));
+    }
+
+    #[test]
+    fn levels() {
+        let template = "<mjml><mj-body><mj-unknown /></mj-body></mjml>";
+        let strict = Options {
+            validation_level: ValidationLevel::Strict,
+            ..Options::default()
+        };
+        let error = to_html(template, strict).unwrap_err();
+        assert_eq!(error.report().unwrap().findings.len(), 1);
+        assert_eq!(error.location().column, 16);
+        assert!(to_html(template, Options::default()).is_ok());
+        let soft = Options {
+            validation_level: ValidationLevel::Soft,
+            ..Options::default()
+        };
+        assert!(to_html(template, soft.clone()).is_ok());
+        let (_, report) = crate::parse_with_report(template, soft).unwrap();
+        assert_eq!(report.findings.len(), 1);
+        let (_, report) = crate::parse_with_report(template, Options::default()).unwrap();
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn warnings_dont_fail_strict() {
+        let template = r#"<mjml><mj-body foo="bar"></mj-body></mjml>"#;
+        let strict = Options {
+            validation_level: ValidationLevel::Strict,
+            ..Options::default()
+        };
+        assert!(to_html(template, strict).is_ok());
+    }
+
+    #[test]
+    fn level_from_str() {
+        assert_eq!(
+            "strict".parse::<ValidationLevel>().unwrap(),
+            ValidationLevel::Strict
+        );
+        assert!("nope".parse::<ValidationLevel>().is_err());
+    }
+}
@@ -1,12 +1,128 @@
+use js_sys::{Error as JsError, Object, Reflect};
+use mrml::util::size::Size;
+use std::str::FromStr;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
-#[wasm_bindgen(js_name = toHtml)]
-pub fn to_html(input: &str) -> Result<String, JsValue> {
-    match mrml::to_html(input, mrml::Options::default()) {
-        Ok(value) => Ok(value),
-        Err(err) => {
-            let message = format!("{:?}", err);
-            Err(JsValue::from(message.as_str()))
+fn set(target: &JsValue, key: &str, value: JsValue) {
+    Reflect::set(target, &JsValue::from_str(key), &value).unwrap_throw();
+}
+
+/// error thrown to javascript, with the position of the failure in the template
+/// when there is one
+fn error(kind: &str, message: &str, position: Option<(usize, usize)>) -> JsValue {
+    let result: JsValue = JsError::new(message).into();
+    set(&result, "kind", JsValue::from_str(kind));
+    let (line, column) = match position {
+        Some((line, column)) => (JsValue::from(line as u32), JsValue::from(column as u32)),
+        None => (JsValue::NULL, JsValue::NULL),
+    };
+    set(&result, "line", line);
+    set(&result, "column", column);
+    result
+}
+
+fn from_error(err: mrml::Error) -> JsValue {
+    let kind = match err {
+        mrml::Error::MJMLError(_, _) => "mjml",
+        mrml::Error::ParserError(_, _) => "parser",
+        mrml::Error::ValidationError(_, _) => "validation",
+    };
+    let location = err.location();
+    error(kind, &err.message(), Some((location.line, location.column)))
+}
+
+fn invalid_option(key: &str, expected: &str) -> JsValue {
+    error(
+        "options",
+        &format!("invalid option {}: expected {}", key, expected),
+        None,
+    )
+}
+
+/// property of the object, `None` when undefined or null
+fn get(object: &JsValue, key: &str) -> Result<Option<JsValue>, JsValue> {
+    let value = Reflect::get(object, &JsValue::from_str(key))?;
+    if value.is_undefined() || value.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(value))
+    }
+}
+
+fn get_bool(object: &JsValue, key: &str) -> Result<Option<bool>, JsValue> {
+    match get(object, key)? {
+        Some(value) => value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| invalid_option(key, "a boolean")),
+        None => Ok(None),
+    }
+}
+
+/// options of the renderer given as a javascript object like
+/// `{ breakpoint: "320px", keepComments: false, minify: true, fonts: { Name: "url" } }`,
+/// the missing ones keeping their default value
+fn options(value: &JsValue) -> Result<mrml::Options, JsValue> {
+    let mut result = mrml::Options::default();
+    if value.is_undefined() || value.is_null() {
+        return Ok(result);
+    }
+    if !value.is_object() {
+        return Err(error("options", "options should be an object", None));
+    }
+    if let Some(item) = get(value, "breakpoint")? {
+        result.breakpoint = match (item.as_f64(), item.as_string()) {
+            (Some(pixels), _) => Size::Pixel(pixels as f32),
+            (_, Some(size)) => Size::from_str(&size)
+                .map_err(|_| invalid_option("breakpoint", "a size like 480px"))?,
+            _ => return Err(invalid_option("breakpoint", "a size like 480px")),
+        };
+    }
+    if let Some(keep_comments) = get_bool(value, "keepComments")? {
+        result.keep_comments = keep_comments;
+    }
+    if let Some(minify) = get_bool(value, "minify")? {
+        result.minify = minify;
+    }
+    if let Some(item) = get(value, "fonts")? {
+        let fonts = item
+            .dyn_ref::<Object>()
+            .ok_or_else(|| invalid_option("fonts", "an object"))?;
+        for name in Object::keys(fonts).iter() {
+            let url = Reflect::get(fonts, &name)?
+                .as_string()
+                .ok_or_else(|| invalid_option("fonts", "urls as strings"))?;
+            result
+                .fonts
+                .add(&name.as_string().unwrap_or_default(), &url);
         }
     }
+    Ok(result)
+}
+
+#[wasm_bindgen(js_name = toHtml)]
+pub fn to_html(input: &str, opts: JsValue) -> Result<String, JsValue> {
+    mrml::to_html(input, options(&opts)?).map_err(from_error)
+}
+
+#[wasm_bindgen(js_name = toTitle)]
+pub fn to_title(input: &str) -> Result<String, JsValue> {
+    mrml::to_title(input, mrml::Options::default()).map_err(from_error)
+}
+
+#[wasm_bindgen(js_name = toPreview)]
+pub fn to_preview(input: &str) -> Result<String, JsValue> {
+    mrml::to_preview(input, mrml::Options::default()).map_err(from_error)
+}
+
+/// subject, plain text and html of the email, as `{ subject, text, html }`
+#[wasm_bindgen(js_name = toEmail)]
+pub fn to_email(input: &str, opts: JsValue) -> Result<JsValue, JsValue> {
+    let email = mrml::to_email(input, options(&opts)?).map_err(from_error)?;
+    let result: JsValue = Object::new().into();
+    set(&result, "subject", JsValue::from_str(&email.subject));
+    set(&result, "text", JsValue::from_str(&email.text));
+    set(&result, "html", JsValue::from_str(&email.html));
+    Ok(result)
 }
@@ -1,16 +1,76 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
 use wasm_bindgen_test::*;
 
+const TEMPLATE: &str = r#"<mjml>
+  <mj-head>
+    <mj-title>Hello</mj-title>
+    <mj-preview>World</mj-preview>
+  </mj-head>
+  <mj-body>
+    <mj-section><mj-column><mj-text>Hello World</mj-text></mj-column></mj-section>
+  </mj-body>
+</mjml>"#;
+
+fn get(value: &JsValue, key: &str) -> JsValue {
+    Reflect::get(value, &JsValue::from_str(key)).unwrap()
+}
+
+fn object(content: &str) -> JsValue {
+    js_sys::JSON::parse(content).unwrap()
+}
+
 #[wasm_bindgen_test]
 fn pass() {
-    let result = mrml_wasm::to_html("<mjml></mjml>");
+    let result = mrml_wasm::to_html("<mjml></mjml>", JsValue::UNDEFINED);
     assert!(result.is_ok());
 }
 
 #[wasm_bindgen_test]
 fn raise() {
-    let result = mrml_wasm::to_html("<mjml");
+    let result = mrml_wasm::to_html("<mjml", JsValue::UNDEFINED);
     assert!(result.is_err());
 }
+
+#[wasm_bindgen_test]
+fn with_options() {
+    let options = object(
+        r#"{"breakpoint":"320px","minify":true,"fonts":{"Inter":"https://example.com/inter.css"}}"#,
+    );
+    let result = mrml_wasm::to_html(TEMPLATE, options).unwrap();
+    assert!(result.contains("min-width:320px"));
+    let err = mrml_wasm::to_html(TEMPLATE, object(r#"{"minify":"yes"}"#)).unwrap_err();
+    assert_eq!(get(&err, "kind"), "options");
+}
+
+#[wasm_bindgen_test]
+fn title_preview_and_email() {
+    assert_eq!(mrml_wasm::to_title(TEMPLATE).unwrap(), "Hello");
+    assert_eq!(mrml_wasm::to_preview(TEMPLATE).unwrap(), "World");
+    let email = mrml_wasm::to_email(TEMPLATE, JsValue::UNDEFINED).unwrap();
+    assert_eq!(get(&email, "subject"), "Hello");
+    assert!(get(&email, "text")
+        .as_string()
+        .unwrap()
+        .contains("Hello World"));
+    assert!(get(&email, "html")
+        .as_string()
+        .unwrap()
+        .contains("Hello World"));
+}
+
+#[wasm_bindgen_test]
+fn structured_error() {
+    let err = mrml_wasm::to_html(
+        "<mjml>\n  <mj-head>\n    <mj-unknown />\n  </mj-head>\n</mjml>",
+        JsValue::UNDEFINED,
+    )
+    .unwrap_err();
+    assert_eq!(get(&err, "kind"), "mjml");
+    assert_eq!(get(&err, "message"), "unexpected element <mj-unknown>");
+    assert_eq!(get(&err, "line"), 3);
+    assert_eq!(get(&err, "column"), 5);
+}